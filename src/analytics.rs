@@ -0,0 +1,234 @@
+use std::env;
+use std::time::Duration;
+
+use anyhow::{Context as _, bail};
+use axum::http::{HeaderMap, HeaderName, header};
+use prometheus_client::metrics::counter::Counter;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::{hash, metrics::HttpMetrics};
+
+/// Events buffered for sending, further events are dropped while it is full.
+const QUEUE_SIZE: usize = 4096;
+
+/// Events sent per request to the endpoint at most.
+const BATCH_SIZE: usize = 256;
+
+/// How long events wait for a batch to fill up.
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+
+/// How long connecting to the endpoint may take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A sampled request that got redirected.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectEvent {
+    pub host: String,
+    /// FNV-1a of the request path, hex encoded
+    pub path_hash: String,
+    /// from the country header set by a CDN or load balancer
+    pub country: Option<String>,
+    pub timestamp: String,
+}
+
+/// How batches are sent to the endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// a JSON array of events
+    Json,
+    /// records for a topic of the Kafka REST proxy, `/topics/<topic>`
+    KafkaRest,
+}
+
+#[derive(Serialize)]
+struct KafkaRecords<'a> {
+    records: Vec<KafkaRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct KafkaRecord<'a> {
+    value: &'a RedirectEvent,
+}
+
+impl Format {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::KafkaRest => "application/vnd.kafka.json.v2+json",
+        }
+    }
+
+    fn body(self, batch: &[RedirectEvent]) -> serde_json::Result<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec(batch),
+            Self::KafkaRest => serde_json::to_vec(&KafkaRecords {
+                records: batch.iter().map(|value| KafkaRecord { value }).collect(),
+            }),
+        }
+    }
+}
+
+/// Mirrors a sample of redirect events to an HTTP endpoint, without delaying responses.
+#[derive(Clone)]
+pub struct Analytics {
+    sender: mpsc::Sender<RedirectEvent>,
+    sample_rate: f64,
+    country_header: HeaderName,
+    dropped: Counter,
+}
+
+impl Analytics {
+    /// Configured by `ANALYTICS_ENDPOINT`, `ANALYTICS_FORMAT`, `ANALYTICS_SAMPLE_RATE`,
+    /// `ANALYTICS_COUNTRY_HEADER` and `ANALYTICS_TIMEOUT_SECONDS`.
+    ///
+    /// `None` if no endpoint is set. Events are POSTed as JSON arrays, or with the `kafka-rest`
+    /// format as records to a topic of the Kafka REST proxy. Kafka brokers are not spoken to
+    /// directly, `kafka://` endpoints are rejected.
+    pub fn from_env(metrics: &HttpMetrics) -> anyhow::Result<Option<Self>> {
+        let Ok(endpoint) = env::var("ANALYTICS_ENDPOINT") else {
+            return Ok(None);
+        };
+        if endpoint.starts_with("kafka://") {
+            bail!("ANALYTICS_ENDPOINT must be a Kafka REST proxy topic, not a broker");
+        }
+        let format = match env::var("ANALYTICS_FORMAT").as_deref() {
+            Ok("json") | Err(_) => Format::Json,
+            Ok("kafka-rest") => Format::KafkaRest,
+            Ok(other) => bail!("unknown ANALYTICS_FORMAT {other}, json or kafka-rest"),
+        };
+        let sample_rate = match env::var("ANALYTICS_SAMPLE_RATE") {
+            Ok(rate) => rate.parse::<f64>()?.clamp(0., 1.),
+            Err(_) => 0.01,
+        };
+        let country_header = env::var("ANALYTICS_COUNTRY_HEADER")
+            .unwrap_or("cf-ipcountry".to_string())
+            .parse()?;
+
+        let timeout = match env::var("ANALYTICS_TIMEOUT_SECONDS") {
+            Ok(timeout) => timeout
+                .parse()
+                .context("invalid ANALYTICS_TIMEOUT_SECONDS")?,
+            Err(_) => 10,
+        };
+
+        let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(Duration::from_secs(timeout))
+            .build()?;
+        let dropped = metrics.analytics_dropped.clone();
+        info!("mirroring {} of redirects to {}", sample_rate, endpoint);
+        tokio::spawn(send_batches(
+            client,
+            endpoint,
+            format,
+            receiver,
+            dropped.clone(),
+        ));
+
+        Ok(Some(Self {
+            sender,
+            sample_rate,
+            country_header,
+            dropped,
+        }))
+    }
+
+    /// Records a redirected request if it is sampled.
+    pub fn record(&self, host: &str, path: &str, headers: &HeaderMap) {
+        #[allow(clippy::cast_precision_loss)]
+        let sampled = (hash::random_u64() as f64 / u64::MAX as f64) < self.sample_rate;
+        if !sampled {
+            return;
+        }
+
+        let event = RedirectEvent {
+            host: host.to_string(),
+            path_hash: format!("{:016x}", hash::fnv1a(path)),
+            country: headers
+                .get(&self.country_header)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            timestamp: k8s_openapi::jiff::Timestamp::now().to_string(),
+        };
+        // never wait for the sender, a full queue would warn on every request
+        if self.sender.try_send(event).is_err() {
+            self.dropped.inc();
+        }
+    }
+}
+
+async fn send_batches(
+    client: reqwest::Client,
+    endpoint: String,
+    format: Format,
+    mut receiver: mpsc::Receiver<RedirectEvent>,
+    dropped: Counter,
+) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    while receiver.recv_many(&mut batch, BATCH_SIZE).await > 0 {
+        // give the batch a moment to fill up
+        tokio::time::sleep(BATCH_DELAY).await;
+        while batch.len() < BATCH_SIZE {
+            match receiver.try_recv() {
+                Ok(event) => batch.push(event),
+                Err(_) => break,
+            }
+        }
+
+        let body = match format.body(&batch) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("cannot serialize analytics events: {}", e);
+                dropped.inc_by(batch.len() as u64);
+                batch.clear();
+                continue;
+            }
+        };
+        let result = client
+            .post(&endpoint)
+            .header(header::CONTENT_TYPE, format.content_type())
+            .body(body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            warn!("sending {} analytics events failed: {}", batch.len(), e);
+            dropped.inc_by(batch.len() as u64);
+        }
+        batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn wraps_events_in_kafka_records() {
+        let batch = [RedirectEvent {
+            host: "example.com".to_string(),
+            path_hash: "cbf29ce484222325".to_string(),
+            country: None,
+            timestamp: "2030-01-01T00:00:00Z".to_string(),
+        }];
+        let event = json!({
+            "host": "example.com",
+            "pathHash": "cbf29ce484222325",
+            "country": null,
+            "timestamp": "2030-01-01T00:00:00Z",
+        });
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&Format::Json.body(&batch).unwrap()).unwrap();
+        assert_eq!(json, json!([event]));
+        let kafka: serde_json::Value =
+            serde_json::from_slice(&Format::KafkaRest.body(&batch).unwrap()).unwrap();
+        assert_eq!(kafka, json!({ "records": [{ "value": event }] }));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::{
+    ResourceExt,
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+};
+use serde_json::json;
+
+use crate::{
+    controller::{Ingresses, NetworkingBackend, RenderContext, condition, ingresses_for_redirect},
+    types::{Error, Redirect, RedirectSpec, RedirectStatus, RedirectStatusCertificate},
+};
+
+/// Condition type reporting whether all Certificates are issued.
+pub const CONDITION_CERTIFICATE_READY: &str = "CertificateReady";
+
+/// The cert-manager `cert-manager.io/v1` Certificate API.
+pub fn api_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk(
+        "cert-manager.io",
+        "v1",
+        "Certificate",
+    ))
+}
+
+/// Whether cert-manager reports a Certificate as issued.
+fn is_ready(certificate: &DynamicObject) -> bool {
+    certificate
+        .data
+        .pointer("/status/conditions")
+        .and_then(|c| c.as_array())
+        .is_some_and(|conditions| {
+            conditions
+                .iter()
+                .any(|c| c["type"] == "Ready" && c["status"] == "True")
+        })
+}
+
+/// cert-manager Certificates for the TLS Secrets of the generated Ingresses.
+///
+/// One per Secret, named like it, for all hosts using it.
+pub struct Certificates;
+
+impl NetworkingBackend for Certificates {
+    fn kind(&self) -> &'static str {
+        "Certificate"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        api_resource()
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        spec.wants_ingress() && spec.ingress.tls.enabled && spec.ingress.tls.issuer_ref.is_some()
+    }
+
+    /// Next to the Ingresses, which use their Secrets.
+    fn namespace<'a>(&self, redirect: &'a Redirect, same_namespace: bool) -> Option<&'a str> {
+        Ingresses.namespace(redirect, same_namespace)
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let Some(issuer_ref) = &redirect.spec.ingress.tls.issuer_ref else {
+            return (Vec::new(), Vec::new());
+        };
+        let redirect_ingress = rctx.defaults.apply(&redirect.spec.ingress);
+
+        let mut hosts_by_secret: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let ingresses = ingresses_for_redirect(rctx, redirect, &redirect_ingress);
+        for tls in ingresses
+            .iter()
+            .filter_map(|i| i.spec.as_ref()?.tls.as_ref())
+            .flatten()
+        {
+            if let Some(secret_name) = &tls.secret_name {
+                hosts_by_secret
+                    .entry(secret_name.clone())
+                    .or_default()
+                    .extend(tls.hosts.iter().flatten().cloned());
+            }
+        }
+
+        let certificates = hosts_by_secret
+            .into_iter()
+            .map(|(secret_name, hosts)| {
+                let mut certificate = DynamicObject::new(&secret_name, &api_resource())
+                    .within(rctx.namespace)
+                    .data(json!({
+                        "spec": {
+                            "secretName": secret_name,
+                            "dnsNames": hosts,
+                            "issuerRef": {
+                                "name": issuer_ref.name,
+                                "kind": issuer_ref.kind,
+                                "group": issuer_ref.group,
+                            },
+                        },
+                    }));
+                certificate.metadata.labels = redirect_ingress.labels.clone();
+                certificate
+            })
+            .collect();
+        (certificates, Vec::new())
+    }
+
+    fn existing(&self, redirect: &Redirect, _self_namespace: &str) -> BTreeSet<(String, String)> {
+        redirect
+            .status
+            .iter()
+            .flat_map(|status| &status.certificates)
+            .map(|c| (c.namespace.clone(), c.name.clone()))
+            .collect()
+    }
+
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]) {
+        status.certificates = applied
+            .iter()
+            .map(|certificate| RedirectStatusCertificate {
+                name: certificate.name_any(),
+                namespace: namespace.to_string(),
+                ready: is_ready(certificate),
+            })
+            .collect();
+        if status.certificates.is_empty() {
+            return;
+        }
+
+        let pending: Vec<&str> = status
+            .certificates
+            .iter()
+            .filter(|c| !c.ready)
+            .map(|c| c.name.as_str())
+            .collect();
+        let ready = if pending.is_empty() {
+            condition(
+                CONDITION_CERTIFICATE_READY,
+                true,
+                "Issued",
+                "all certificates are issued",
+            )
+        } else {
+            condition(
+                CONDITION_CERTIFICATE_READY,
+                false,
+                "Pending",
+                format!("waiting for {}", pending.join(", ")),
+            )
+        };
+        status.conditions.push(ready);
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::CertificateCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::CertificateDeletionFailed(error)
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read as _;
+use std::sync::Arc;
+
+use anyhow::{Context as _, bail};
+use k8s_openapi::api::{
+    core::v1::{ConfigMap, Secret},
+    networking::v1::Ingress,
+};
+use kube::{
+    Api, ResourceExt,
+    api::{DeleteParams, ListParams, Patch, PatchParams},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    controller, dryrun,
+    edge::EdgeRules,
+    export::{self, Format},
+    gc, host,
+    import::{self, Grouping},
+    pathmap::{CONFIG_MAP_LABEL, PathMaps},
+    types::Redirect,
+};
+
+const USAGE: &str = "usage: controller [--dry-run] [COMMAND]
+
+Runs the operator when no command is given, with --dry-run (or DRY_RUN=true) it logs the
+changes it would make instead of making them.
+
+commands:
+  render [FILE]   print the objects generated for a Redirect manifest (stdin if no FILE)
+  impact [FILE]   report how applying a Redirect manifest would affect the live cluster
+  orphans [--delete]
+                  list Ingresses without their Redirect and Redirects without their Ingress,
+                  deleting the orphaned Ingresses with --delete
+  export [--format nginx|caddy|json]
+                  print the redirects of all Redirects as nginx or Caddy configuration, or as
+                  JSON (default nginx)
+  import [--format csv|nginx] [--group host|target] [--namespace NAMESPACE] [--host HOST]
+         [--include-request-uri] [FILE]
+                  create or update Redirects from `host[/path],target` lines or the entries of
+                  an nginx map (default csv, stdin if no FILE), one per host or, with
+                  --group target, one for all hosts with the same target and paths. Paths go
+                  into a path map ConfigMap, sources without host are paths of --host";
+
+/// Field manager of imported objects, separate from the operator's.
+const IMPORT_FIELD_MANAGER: &str = "redirect-operator-import";
+
+/// Runs a one-shot subcommand instead of the operator.
+pub async fn run(command: &str, args: &[String]) -> anyhow::Result<()> {
+    match command {
+        "render" => render(args.first().map(String::as_str)),
+        "impact" => impact(args.first().map(String::as_str)).await,
+        "orphans" => orphans(args.iter().any(|a| a == "--delete")).await,
+        "export" => export(args).await,
+        "import" => import(args).await,
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => bail!("unknown command {command}\n\n{USAGE}"),
+    }
+}
+
+fn render(file: Option<&str>) -> anyhow::Result<()> {
+    let redirect: Redirect = from_str(&read_input(file)?)?;
+    let namespace = controller::self_namespace();
+    let service_name = controller::self_service_name();
+
+    for object in controller::render(&redirect, &namespace, &service_name) {
+        print_document(&object)?;
+    }
+    Ok(())
+}
+
+/// Reports host conflicts, affected Ingresses and TLS changes, read-only.
+async fn impact(file: Option<&str>) -> anyhow::Result<()> {
+    let proposed: Redirect = from_str(&read_input(file)?)?;
+    let proposed_ns = proposed.namespace().unwrap_or("default".to_string());
+    let proposed_name = proposed.name_any();
+    let (hosts, invalid_hosts) = host::served_hosts(&proposed.spec);
+
+    let client = kube::Client::try_default().await?;
+    let namespace = controller::self_namespace();
+    let service_name = controller::self_service_name();
+
+    println!("Redirect {proposed_ns}/{proposed_name}");
+    for invalid in invalid_hosts {
+        println!("  invalid host {invalid} will be ignored");
+    }
+
+    println!("host conflicts:");
+    let redirects: Api<Redirect> = Api::all(client.clone());
+    let mut conflicts = 0;
+    for other in redirects.list(&ListParams::default()).await? {
+        if other.namespace() == Some(proposed_ns.clone()) && other.name_any() == proposed_name {
+            continue;
+        }
+        let (other_hosts, _) = host::served_hosts(&other.spec);
+        for host in hosts.intersection(&other_hosts) {
+            conflicts += 1;
+            println!(
+                "  {host} is already claimed by Redirect {}/{}",
+                other.namespace().unwrap_or_default(),
+                other.name_any()
+            );
+        }
+    }
+    if conflicts == 0 {
+        println!("  none");
+    }
+
+    let mut proposed = proposed;
+    proposed.metadata.namespace = Some(proposed_ns);
+    let generated: Vec<Ingress> = controller::render(&proposed, &namespace, &service_name)
+        .into_iter()
+        .filter(|o| o["kind"] == "Ingress")
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()?;
+
+    println!("affected Ingresses:");
+    let ingresses: Api<Ingress> = Api::all(client.clone());
+    let existing = ingresses.list(&ListParams::default()).await?;
+    for ingress in &generated {
+        let name = ingress.name_any();
+        match existing
+            .iter()
+            .find(|i| i.namespace() == ingress.namespace() && i.name_any() == name)
+        {
+            Some(current) => {
+                let (added, removed) = diff(&ingress_hosts(ingress), &ingress_hosts(current));
+                println!("  update {namespace}/{name}: +{added:?} -{removed:?}");
+            }
+            None => println!("  create {namespace}/{name}"),
+        }
+    }
+    for other in existing.iter().filter(|i| {
+        !generated
+            .iter()
+            .any(|g| g.namespace() == i.namespace() && g.name_any() == i.name_any())
+    }) {
+        let shared: Vec<_> = ingress_hosts(other).intersection(&hosts).cloned().collect();
+        if !shared.is_empty() {
+            println!(
+                "  Ingress {}/{} also routes {shared:?}",
+                other.namespace().unwrap_or_default(),
+                other.name_any()
+            );
+        }
+    }
+    if generated.is_empty() {
+        println!("  none (ingress disabled)");
+    }
+
+    println!("TLS:");
+    let secrets: Api<Secret> = Api::namespaced(client, &namespace);
+    for ingress in &generated {
+        let annotations = ingress.annotations();
+        let cert_manager = annotations
+            .keys()
+            .any(|k| k.starts_with("cert-manager.io/"));
+        for tls in ingress.spec.iter().flat_map(|s| s.tls.iter().flatten()) {
+            let Some(secret_name) = &tls.secret_name else {
+                continue;
+            };
+            let exists = secrets.get_metadata_opt(secret_name).await?.is_some();
+            let action = match (exists, cert_manager) {
+                (true, true) => "exists, cert-manager may reissue it for the new host set",
+                (true, false) => "exists, make sure it covers all hosts",
+                (false, true) => "will be issued by cert-manager",
+                (false, false) => "is missing, HTTPS will not work",
+            };
+            println!("  secret {namespace}/{secret_name} {action}");
+        }
+    }
+    if generated.iter().all(|i| {
+        i.spec
+            .as_ref()
+            .is_none_or(|s| s.tls.as_ref().is_none_or(|t| t.is_empty()))
+    }) {
+        println!("  TLS disabled");
+    }
+
+    Ok(())
+}
+
+/// Lists generated Ingresses whose Redirect is gone and Redirects missing their Ingresses.
+///
+/// Only orphaned Ingresses are deleted, the operator recreates missing ones on the next
+/// reconcile.
+async fn orphans(delete: bool) -> anyhow::Result<()> {
+    let client = kube::Client::try_default().await?;
+    let redirects: Api<Redirect> = Api::all(client.clone());
+    let redirects: BTreeMap<(String, String), Redirect> = redirects
+        .list(&ListParams::default())
+        .await?
+        .into_iter()
+        .map(|r| ((r.namespace().unwrap_or_default(), r.name_any()), r))
+        .collect();
+    let ingresses: Api<Ingress> = Api::all(client.clone());
+    let ingresses = ingresses
+        .list_metadata(&ListParams::default().labels(gc::OWNER_LABEL))
+        .await?;
+
+    println!("Ingresses without their Redirect:");
+    let mut owners = BTreeSet::new();
+    let mut orphaned = 0;
+    for ingress in &ingresses {
+        let Some(owner) = gc::owner(ingress) else {
+            continue;
+        };
+        let owner = (owner.namespace.unwrap_or_default(), owner.name);
+        if redirects.contains_key(&owner) {
+            owners.insert(owner);
+            continue;
+        }
+        orphaned += 1;
+        let ns = ingress.namespace().unwrap_or_default();
+        let name = ingress.name_any();
+        println!("  {ns}/{name} of Redirect {}/{}", owner.0, owner.1);
+        if delete {
+            let api: Api<Ingress> = Api::namespaced(client.clone(), &ns);
+            match api.delete(&name, &DeleteParams::default()).await {
+                Ok(_) => println!("    deleted"),
+                Err(kube::Error::Api(response)) if response.code == 404 => {}
+                Err(e) => return Err(e).with_context(|| format!("cannot delete {ns}/{name}")),
+            }
+        }
+    }
+    if orphaned == 0 {
+        println!("  none");
+    }
+
+    println!("Redirects without their Ingress:");
+    let mut missing = 0;
+    for (owner, redirect) in &redirects {
+        if !redirect.spec.wants_ingress() || redirect.spec.ingress.shared || owners.contains(owner)
+        {
+            continue;
+        }
+        missing += 1;
+        println!("  {}/{}", owner.0, owner.1);
+    }
+    if missing == 0 {
+        println!("  none");
+    }
+    Ok(())
+}
+
+/// Prints the redirect table for migrating off the operator or serving it elsewhere.
+async fn export(args: &[String]) -> anyhow::Result<()> {
+    let format = match args {
+        [] => Format::Nginx,
+        [flag, format] if flag == "--format" => format.parse().map_err(anyhow::Error::msg)?,
+        _ => bail!("usage: export [--format nginx|caddy|json]"),
+    };
+
+    let client = kube::Client::try_default().await?;
+    let redirects: Api<Redirect> = Api::all(client.clone());
+    let redirects = redirects
+        .list(&ListParams::default())
+        .await?
+        .into_iter()
+        .map(Arc::new)
+        .collect();
+    let config_maps: Api<ConfigMap> = Api::all(client);
+    let path_maps = PathMaps::from_config_maps(
+        config_maps
+            .list(&ListParams::default().labels(CONFIG_MAP_LABEL))
+            .await?
+            .items,
+    );
+
+    let rules = EdgeRules::from_redirects(redirects, &path_maps, None);
+    print!("{}", export::render(&rules, format));
+    Ok(())
+}
+
+/// Creates or updates Redirects for legacy redirects, server-side applied so that later
+/// imports only change what they imported.
+async fn import(args: &[String]) -> anyhow::Result<()> {
+    let mut format = import::Format::Csv;
+    let mut grouping = Grouping::Host;
+    let mut namespace = None;
+    let mut default_host = None;
+    let mut include_request_uri = false;
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().with_context(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--format" => format = value()?.parse().map_err(anyhow::Error::msg)?,
+            "--group" => grouping = value()?.parse().map_err(anyhow::Error::msg)?,
+            "--namespace" | "-n" => namespace = Some(value()?.clone()),
+            "--host" => default_host = Some(value()?.clone()),
+            "--include-request-uri" => include_request_uri = true,
+            _ if file.is_none() && (arg == "-" || !arg.starts_with('-')) => {
+                file = Some(arg.as_str())
+            }
+            _ => bail!("unknown argument {arg}\n\n{USAGE}"),
+        }
+    }
+
+    let entries = import::parse(&read_input(file)?, format, default_host.as_deref())
+        .map_err(anyhow::Error::msg)?;
+    let groups = import::group(&entries, grouping).map_err(anyhow::Error::msg)?;
+
+    let client = kube::Client::try_default().await?;
+    let namespace = namespace.unwrap_or_else(|| client.default_namespace().to_string());
+    let dry_run = dryrun::enabled();
+    let redirects: Api<Redirect> = Api::namespaced(client.clone(), &namespace);
+    let config_maps: Api<ConfigMap> = Api::namespaced(client, &namespace);
+    for group in &groups {
+        if let Some(config_map) = group.config_map(&namespace) {
+            apply(&config_maps, &group.name, &config_map, dry_run).await?;
+        }
+        let redirect = group.redirect(&namespace, include_request_uri);
+        apply(&redirects, &group.name, &redirect, dry_run).await?;
+    }
+    println!(
+        "{} {} entries as {} Redirects in {namespace}",
+        if dry_run { "would import" } else { "imported" },
+        entries.len(),
+        groups.len()
+    );
+    Ok(())
+}
+
+/// Server-side applies `object`, in dry runs only logging how it would change.
+async fn apply<K>(api: &Api<K>, name: &str, object: &Value, dry_run: bool) -> anyhow::Result<()>
+where
+    K: kube::Resource<DynamicType = ()> + Clone + DeserializeOwned + Serialize + std::fmt::Debug,
+{
+    let kind = K::kind(&());
+    let mut params = PatchParams::apply(IMPORT_FIELD_MANAGER);
+    if dry_run {
+        params = params.dry_run();
+    }
+    let applied = api
+        .patch(name, &params, &Patch::Apply(object))
+        .await
+        .with_context(|| format!("cannot apply {kind} {name}"))?;
+    if dry_run {
+        let before = api.get_opt(name).await?.map(|o| dryrun::comparable(&o));
+        let after = dryrun::comparable(&applied);
+        let namespace = applied.meta().namespace.clone().unwrap_or_default();
+        dryrun::log_diff(&kind, &namespace, name, before.as_ref(), &after);
+    }
+    Ok(())
+}
+
+fn ingress_hosts(ingress: &Ingress) -> BTreeSet<String> {
+    ingress
+        .spec
+        .iter()
+        .flat_map(|s| s.rules.iter().flatten())
+        .filter_map(|r| r.host.clone())
+        .collect()
+}
+
+/// Hosts only in `new`, and only in `old`.
+fn diff(new: &BTreeSet<String>, old: &BTreeSet<String>) -> (Vec<String>, Vec<String>) {
+    (
+        new.difference(old).cloned().collect(),
+        old.difference(new).cloned().collect(),
+    )
+}
+
+/// Reads `file`, or stdin if it is `None` or `-`.
+fn read_input(file: Option<&str>) -> anyhow::Result<String> {
+    match file {
+        None | Some("-") => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .context("cannot read stdin")?;
+            Ok(input)
+        }
+        Some(file) => std::fs::read_to_string(file).with_context(|| format!("cannot read {file}")),
+    }
+}
+
+fn from_str<T: DeserializeOwned>(input: &str) -> anyhow::Result<T> {
+    serde_yaml::from_str(input).context("cannot parse YAML")
+}
+
+fn print_document<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    print!("---\n{}", serde_yaml::to_string(value)?);
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::env;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http::{Method, Request, Response, StatusCode, header};
+use kube::{
+    Client, Config,
+    client::{Body, ClientBuilder},
+};
+use tower::{
+    BoxError, Service, ServiceBuilder, buffer::BufferLayer, limit::RateLimitLayer,
+    retry::RetryLayer, timeout::TimeoutLayer,
+};
+use tracing::debug;
+
+/// Requests queued for the rate limiter before callers wait.
+const QUEUE: usize = 1024;
+
+/// Delay before the first retry, doubled for each further one.
+const RETRY_BASE: Duration = Duration::from_millis(200);
+
+/// Longest delay between retries, also for longer `Retry-After`s.
+const RETRY_MAX: Duration = Duration::from_secs(10);
+
+fn var<T: FromStr>(name: &str) -> anyhow::Result<Option<T>>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    env::var(name)
+        .ok()
+        .map(|v| v.parse().with_context(|| format!("invalid {name}")))
+        .transpose()
+}
+
+/// The client for the controller and its reflectors, tuned for congested API servers.
+///
+/// Reads `KUBE_CLIENT_QPS` to limit the requests per second, `KUBE_CLIENT_TIMEOUT_SECONDS`
+/// to bound connecting and waiting for a response, and `KUBE_CLIENT_RETRIES` (default 3) for
+/// how often idempotent requests are retried on throttling, unavailable API servers and
+/// connection errors.
+pub async fn from_env() -> anyhow::Result<Client> {
+    let qps: Option<u64> = var("KUBE_CLIENT_QPS")?;
+    let timeout = var("KUBE_CLIENT_TIMEOUT_SECONDS")?.map(Duration::from_secs);
+    let retries = var("KUBE_CLIENT_RETRIES")?.unwrap_or(3);
+
+    let mut config = Config::infer().await?;
+    if let Some(timeout) = timeout {
+        config.connect_timeout = Some(timeout);
+    }
+    let layers = ServiceBuilder::new()
+        .layer_fn(CollectBody)
+        .layer(RetryLayer::new(Backoff {
+            retries,
+            attempt: 0,
+        }))
+        .option_layer(timeout.map(TimeoutLayer::new))
+        .layer(BufferLayer::new(QUEUE))
+        .option_layer(
+            qps.filter(|qps| *qps > 0)
+                .map(|qps| RateLimitLayer::new(qps, Duration::from_secs(1))),
+        );
+    Ok(ClientBuilder::try_from(config)?.with_layer(&layers).build())
+}
+
+/// The request body, kept for retries.
+#[derive(Clone)]
+struct Collected(Bytes);
+
+/// Reads request bodies into memory, so that requests can be sent again.
+#[derive(Clone)]
+struct CollectBody<S>(S);
+
+impl<S> Service<Request<Body>> for CollectBody<S>
+where
+    S: Service<Request<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<S::Response, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // the ready service handles the request, the clone waits for the next one
+        let clone = self.0.clone();
+        let mut inner = std::mem::replace(&mut self.0, clone);
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+            let body = body.collect_bytes().await?;
+            parts.extensions.insert(Collected(body.clone()));
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+                .map_err(Into::into)
+        })
+    }
+}
+
+/// Whether sending `req` twice does no harm.
+///
+/// Server-side apply is the only idempotent patch, creates are never retried.
+fn idempotent(req: &Request<Body>) -> bool {
+    match *req.method() {
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE => true,
+        Method::PATCH => req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|v| v.as_bytes().starts_with(b"application/apply-patch")),
+        _ => false,
+    }
+}
+
+/// Whether the API server may answer differently when asked again.
+fn transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Retries idempotent requests with exponential backoff, honoring `Retry-After`.
+#[derive(Clone)]
+struct Backoff {
+    retries: u32,
+    attempt: u32,
+}
+
+impl<B, E> tower::retry::Policy<Request<Body>, Response<B>, E> for Backoff {
+    type Future = tokio::time::Sleep;
+
+    fn retry(
+        &mut self,
+        req: &mut Request<Body>,
+        result: &mut Result<Response<B>, E>,
+    ) -> Option<Self::Future> {
+        let retry_after = match result {
+            Ok(res) if transient(res.status()) => res
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok()?.parse().ok())
+                .map(Duration::from_secs),
+            Ok(_) => return None,
+            Err(_) => None,
+        };
+        if self.attempt >= self.retries || !idempotent(req) {
+            return None;
+        }
+        let delay = RETRY_BASE
+            .saturating_mul(1 << self.attempt.min(16))
+            .max(retry_after.unwrap_or_default())
+            .min(RETRY_MAX);
+        self.attempt += 1;
+        debug!(
+            "retrying {} {} in {:?} (attempt {})",
+            req.method(),
+            req.uri(),
+            delay,
+            self.attempt
+        );
+        Some(tokio::time::sleep(delay))
+    }
+
+    fn clone_request(&mut self, req: &Request<Body>) -> Option<Request<Body>> {
+        if !idempotent(req) {
+            return None;
+        }
+        let body = req.extensions().get::<Collected>()?;
+        let mut clone = Request::new(Body::from(body.0.clone()));
+        *clone.method_mut() = req.method().clone();
+        *clone.uri_mut() = req.uri().clone();
+        *clone.version_mut() = req.version();
+        *clone.headers_mut() = req.headers().clone();
+        *clone.extensions_mut() = req.extensions().clone();
+        Some(clone)
+    }
+}
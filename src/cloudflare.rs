@@ -0,0 +1,267 @@
+use std::env;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{Api, Client};
+use reqwest::{Method, header};
+use serde_json::{Value, json};
+use tracing::info;
+
+use crate::{
+    dns::{DnsProvider, Record},
+    dryrun,
+};
+
+const API: &str = "https://api.cloudflare.com/client/v4";
+
+/// Key of the API token in the Secret.
+const TOKEN_KEY: &str = "token";
+
+/// How long the API token is used before reading the Secret again.
+const TOKEN_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Cloudflare DNS, for hosts in zones the API token can edit.
+///
+/// Records are marked with the tag in their comment.
+pub struct Cloudflare {
+    http: reqwest::Client,
+    secrets: Api<Secret>,
+    secret_name: String,
+    token: Mutex<Option<(Instant, String)>>,
+    proxied: bool,
+    /// seconds, 1 for automatic
+    ttl: u32,
+    dry_run: bool,
+}
+
+impl Cloudflare {
+    /// Reads `CLOUDFLARE_API_TOKEN_SECRET`, a Secret in the operator's namespace with the API
+    /// token in `token`, `CLOUDFLARE_PROXIED` and `CLOUDFLARE_TTL`.
+    ///
+    /// The Secret is read again every minute, rotated tokens need no restart.
+    pub fn from_env(
+        client: Client,
+        namespace: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(secret_name) = env::var("CLOUDFLARE_API_TOKEN_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())
+        else {
+            return Ok(None);
+        };
+        let ttl = match env::var("CLOUDFLARE_TTL") {
+            Ok(ttl) => ttl.parse().context("invalid CLOUDFLARE_TTL")?,
+            Err(_) => 1,
+        };
+        Ok(Some(Self {
+            http: reqwest::Client::new(),
+            secrets: Api::namespaced(client, namespace),
+            secret_name,
+            token: Mutex::new(None),
+            proxied: env::var("CLOUDFLARE_PROXIED").is_ok_and(|v| v == "true"),
+            ttl,
+            dry_run,
+        }))
+    }
+
+    async fn token(&self) -> Result<String, String> {
+        let cached = self.token.lock().unwrap().clone();
+        if let Some((read, token)) = cached
+            && read.elapsed() < TOKEN_MAX_AGE
+        {
+            return Ok(token);
+        }
+        let secret = self
+            .secrets
+            .get(&self.secret_name)
+            .await
+            .map_err(|e| format!("cannot read Secret {}: {e}", self.secret_name))?;
+        let token = secret
+            .data
+            .as_ref()
+            .and_then(|data| data.get(TOKEN_KEY))
+            .map(|token| String::from_utf8_lossy(&token.0).trim().to_string())
+            .ok_or_else(|| format!("Secret {} has no key {TOKEN_KEY}", self.secret_name))?;
+        *self.token.lock().unwrap() = Some((Instant::now(), token.clone()));
+        Ok(token)
+    }
+
+    /// Calls the API, returning the `result` of successful calls.
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value, String> {
+        let mut request = self
+            .http
+            .request(method, format!("{API}{path}"))
+            .bearer_auth(self.token().await?);
+        if let Some(body) = body {
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Cloudflare API unreachable: {e}"))?;
+        let status = response.status();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Cloudflare API unreachable: {e}"))?;
+        let mut body: Value = serde_json::from_slice(&body)
+            .map_err(|_| format!("Cloudflare API answered {status} without JSON"))?;
+        if body["success"] != true {
+            let message = body["errors"][0]["message"]
+                .as_str()
+                .unwrap_or("unknown error");
+            return Err(format!("Cloudflare API answered {status}: {message}"));
+        }
+        Ok(body["result"].take())
+    }
+
+    /// The id of the zone `host` is in, none if the token cannot see it.
+    async fn find_zone(&self, host: &str) -> Result<Option<String>, String> {
+        let host = host.strip_prefix("*.").unwrap_or(host);
+        let labels: Vec<&str> = host.split('.').collect();
+        // from the most specific candidate, top-level domains are no zones
+        for i in 0..labels.len().saturating_sub(1) {
+            let name = labels[i..].join(".");
+            let zones = self
+                .call(Method::GET, &format!("/zones?name={name}"), None)
+                .await?;
+            if let Some(id) = zones[0]["id"].as_str() {
+                return Ok(Some(id.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The address records of `host`, the operator's and those of others.
+    async fn records(
+        &self,
+        zone: &str,
+        host: &str,
+        tag: &str,
+    ) -> Result<(Vec<Value>, Vec<Value>), String> {
+        let records = self
+            .call(
+                Method::GET,
+                &format!("/zones/{zone}/dns_records?name={host}"),
+                None,
+            )
+            .await?;
+        Ok(records
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|r| matches!(r["type"].as_str(), Some("A" | "AAAA" | "CNAME")))
+            .cloned()
+            .partition(|r| r["comment"] == tag))
+    }
+
+    /// Makes the records of `host` match `wanted`.
+    async fn sync_records(
+        &self,
+        zone: &str,
+        host: &str,
+        tag: &str,
+        wanted: &[Record],
+    ) -> Result<(), String> {
+        let (ours, others) = self.records(zone, host, tag).await?;
+        // deleting records of others is no error, there are none of ours
+        if !others.is_empty() && !wanted.is_empty() {
+            return Err(format!(
+                "{host} has DNS records not managed by the operator"
+            ));
+        }
+        for record in &ours {
+            let current = (record["type"].as_str(), record["content"].as_str());
+            let id = record["id"].as_str().unwrap_or_default();
+            let Some((type_, content)) = wanted
+                .iter()
+                .find(|(type_, content)| current == (Some(*type_), Some(content.as_str())))
+            else {
+                self.write(Method::DELETE, zone, host, Some(id), None)
+                    .await?;
+                continue;
+            };
+            if record["proxied"] != self.proxied || record["ttl"] != self.ttl {
+                let body = self.body(host, tag, type_, content);
+                self.write(Method::PUT, zone, host, Some(id), Some(body))
+                    .await?;
+            }
+        }
+        for (type_, content) in wanted {
+            if !ours.iter().any(|r| {
+                r["type"].as_str() == Some(*type_)
+                    && r["content"].as_str() == Some(content.as_str())
+            }) {
+                let body = self.body(host, tag, type_, content);
+                self.write(Method::POST, zone, host, None, Some(body))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn body(&self, host: &str, tag: &str, type_: &str, content: &str) -> Value {
+        json!({
+            "type": type_,
+            "name": host,
+            "content": content,
+            "ttl": self.ttl,
+            "proxied": self.proxied,
+            "comment": tag,
+        })
+    }
+
+    /// Creates, updates or deletes a record, only logging it in dry runs.
+    async fn write(
+        &self,
+        method: Method,
+        zone: &str,
+        host: &str,
+        id: Option<&str>,
+        body: Option<Value>,
+    ) -> Result<(), String> {
+        if self.dry_run {
+            match &body {
+                Some(body) => dryrun::log_diff("DNS record", zone, host, None, body),
+                None => dryrun::log_delete("DNS record", zone, host),
+            }
+            return Ok(());
+        }
+        info!("{} Cloudflare DNS record for {}", method, host);
+        let path = match id {
+            Some(id) => format!("/zones/{zone}/dns_records/{id}"),
+            None => format!("/zones/{zone}/dns_records"),
+        };
+        self.call(method, &path, body).await.map(|_| ())
+    }
+}
+
+impl DnsProvider for Cloudflare {
+    fn name(&self) -> &'static str {
+        "Cloudflare"
+    }
+
+    fn proxied(&self) -> bool {
+        self.proxied
+    }
+
+    fn zone<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(self.find_zone(host))
+    }
+
+    fn sync_host<'a>(
+        &'a self,
+        zone: &'a str,
+        host: &'a str,
+        tag: &'a str,
+        wanted: &'a [Record],
+    ) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(self.sync_records(zone, host, tag, wanted))
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeSet;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::{
+    ResourceExt,
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+};
+use serde_json::{Value, json};
+
+use crate::{
+    controller::{
+        NetworkingBackend, REDIRECT_SERVICE_PORT, RenderContext, ingress_name_for_redirect,
+    },
+    host, route,
+    types::{
+        Error, PathMatchType, Redirect, RedirectHttpProxy, RedirectPathMatch, RedirectSpec,
+        RedirectStatus, RedirectStatusHostObject,
+    },
+};
+
+/// The Contour `projectcontour.io/v1` HTTPProxy API.
+pub fn api_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk(
+        "projectcontour.io",
+        "v1",
+        "HTTPProxy",
+    ))
+}
+
+/// Name of the HTTPProxy serving `host`, root HTTPProxies have a single host each.
+pub fn http_proxy_name(redirect: &Redirect, host: &str) -> String {
+    format!("{}.{}", ingress_name_for_redirect(redirect), host)
+}
+
+/// Contour routes for `match.paths`, everything if there are none or any is a regex.
+fn routes(service_name: &str, match_paths: &[RedirectPathMatch]) -> Vec<Value> {
+    let route = |condition: Value| {
+        json!({
+            "conditions": [condition],
+            "services": [{ "name": service_name, "port": REDIRECT_SERVICE_PORT }],
+            // plain HTTP requests are redirected by the operator, not upgraded by Contour
+            "permitInsecure": true,
+        })
+    };
+    if match_paths.is_empty()
+        || match_paths
+            .iter()
+            .any(|p| p.path_type == PathMatchType::Regex)
+    {
+        return vec![route(json!({ "prefix": "/" }))];
+    }
+    match_paths
+        .iter()
+        .map(|p| match p.path_type {
+            PathMatchType::Exact => route(json!({ "exact": p.path })),
+            _ => route(json!({ "prefix": p.path })),
+        })
+        .collect()
+}
+
+/// The HTTPProxies serving a Redirect, one per host.
+///
+/// HTTPProxies have to be in the operator's namespace, they cannot route to
+/// Services elsewhere.
+pub fn http_proxies_for_redirect(
+    namespace: &str,
+    service_name: &str,
+    redirect: &Redirect,
+    settings: &RedirectHttpProxy,
+) -> Vec<DynamicObject> {
+    let (hosts, _) = host::served_hosts(&redirect.spec);
+    let routes = routes(service_name, &redirect.spec.match_.paths);
+
+    hosts
+        .iter()
+        .map(|host| {
+            let mut virtualhost = json!({ "fqdn": host });
+            if let Some(secret_name) = &settings.tls_secret_name {
+                virtualhost["tls"] = json!({ "secretName": secret_name });
+            }
+            let mut spec = json!({
+                "virtualhost": virtualhost,
+                "routes": routes,
+            });
+            if let Some(class) = &settings.ingress_class_name {
+                spec["ingressClassName"] = json!(class);
+            }
+
+            let mut http_proxy =
+                DynamicObject::new(&http_proxy_name(redirect, host), &api_resource())
+                    .within(namespace)
+                    .data(json!({ "spec": spec }));
+            http_proxy.metadata.annotations = settings.annotations.clone();
+            http_proxy.metadata.labels = settings.labels.clone();
+            http_proxy
+        })
+        .collect()
+}
+
+/// Contour HTTPProxies, one per host.
+pub struct HttpProxies;
+
+impl NetworkingBackend for HttpProxies {
+    fn kind(&self) -> &'static str {
+        "HTTPProxy"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        api_resource()
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        spec.wants_http_proxy()
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let http_proxies = http_proxies_for_redirect(
+            rctx.namespace,
+            rctx.service_name,
+            redirect,
+            &redirect.spec.http_proxy,
+        );
+        (http_proxies, Vec::new())
+    }
+
+    fn existing(&self, redirect: &Redirect, _self_namespace: &str) -> BTreeSet<(String, String)> {
+        redirect
+            .status
+            .iter()
+            .flat_map(|status| &status.http_proxies)
+            .map(|p| (p.namespace.clone(), p.name.clone()))
+            .collect()
+    }
+
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]) {
+        status.http_proxies = applied
+            .iter()
+            .map(|http_proxy| RedirectStatusHostObject {
+                name: http_proxy.name_any(),
+                namespace: namespace.to_string(),
+                host: route::object_host(http_proxy, "/spec/virtualhost/fqdn"),
+            })
+            .collect();
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::HttpProxyCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::HttpProxyDeletionFailed(error)
+    }
+}
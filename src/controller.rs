@@ -1,92 +1,906 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::{metrics::Metrics, types::*};
+use crate::{
+    certificate, contour,
+    defaults::{
+        CERT_MANAGER_CLUSTER_ISSUER_ANNOTATION, CERT_MANAGER_ISSUER_ANNOTATION, NamespaceDefaults,
+        OperatorDefaults,
+    },
+    dns, dnsverify, dryrun, emissary,
+    external_dns::{self, ExternalDnsDefaults},
+    gc, generator, host, hostpolicy, ingressclass, istio, kong, loops, matcher,
+    metrics::Metrics,
+    multicluster, offload,
+    pathmap::{self, CONFIG_MAP_LABEL, PathMaps},
+    probe, requeue, resync, route, shard, shared, shortlink, target, tls, ttl,
+    types::*,
+    watch,
+    wildcard::WildcardSecret,
+};
 
 use anyhow::Context as _;
-use futures::StreamExt;
-use k8s_openapi::api::networking::v1::{
-    HTTPIngressPath, HTTPIngressRuleValue, Ingress, IngressBackend, IngressRule,
-    IngressServiceBackend, IngressSpec, IngressTLS, ServiceBackendPort,
+use futures::{
+    Stream, StreamExt,
+    channel::mpsc::{self, UnboundedReceiver},
+    future::BoxFuture,
+    stream::BoxStream,
+};
+use k8s_openapi::{
+    api::{
+        core::v1::{ConfigMap, Secret, Service, ServicePort, ServiceSpec},
+        networking::v1::{
+            HTTPIngressPath, HTTPIngressRuleValue, Ingress, IngressBackend, IngressRule,
+            IngressServiceBackend, IngressSpec, IngressTLS, ServiceBackendPort,
+        },
+    },
+    apimachinery::pkg::apis::meta::v1::{Condition, Time},
 };
 use kube::{
-    Api, Client, ResourceExt,
-    api::{ObjectMeta, Patch, PatchParams},
-    runtime::{Config, Controller, controller::Action, finalizer, reflector::Store, watcher},
+    Api, Client, Resource, ResourceExt,
+    api::{
+        ApiResource, DeleteParams, DynamicObject, GroupVersionKind, ListParams, ObjectMeta, Patch,
+        PatchParams,
+    },
+    discovery,
+    runtime::{
+        Config, Controller, WatchStreamExt,
+        controller::Action,
+        events::{Event, EventType, Recorder, Reporter},
+        finalizer,
+        reflector::{self, ObjectRef, Store, store::Writer},
+        watcher,
+    },
 };
 use kube_coordinate::{LeaderElector, LeaderElectorHandle, LeaderState};
 use serde_json::json;
-use tokio::{sync::watch::Receiver, task::JoinHandle};
+use tokio::{
+    sync::{Notify, oneshot, watch::Receiver},
+    task::JoinHandle,
+};
 use tracing::{info, instrument, warn};
 
 pub const REDIRECT_KUBE_SLUG: &str = "redirect.kube.ibotty.net";
 pub const REDIRECT_KUBE_FINALIZER_SLUG: &str = "redirect.kube.ibotty.net/cleanup";
 
+/// Port of the operator's Service that generated Ingresses point at.
+pub const REDIRECT_SERVICE_PORT: i32 = 8080;
+
+/// Condition type reporting whether the backend Service is usable.
+pub const CONDITION_BACKEND_READY: &str = "BackendReady";
+
+/// Condition type reporting whether the Redirect is paused.
+pub const CONDITION_PAUSED: &str = "Paused";
+
+/// Condition type reporting whether the Redirect has a usable target.
+pub const CONDITION_TARGET_VALID: &str = "TargetValid";
+
+/// Condition type reporting whether all hosts are valid domain names.
+pub const CONDITION_HOSTS_VALID: &str = "HostsValid";
+
+/// Condition type reporting whether an older Redirect claims some of the hosts.
+pub const CONDITION_HOST_CONFLICT: &str = "HostConflict";
+
+/// Condition type summarizing whether the Redirect is served as specified.
+pub const CONDITION_READY: &str = "Ready";
+
+/// Condition type reporting whether the Ingresses serving the Redirect exist.
+pub const CONDITION_INGRESS_READY: &str = "IngressReady";
+
+/// Condition type reporting whether the Ingresses' TLS certificates are usable.
+pub const CONDITION_TLS_READY: &str = "TLSReady";
+
+/// Conditions keeping a Redirect from being Ready, with the status that does.
+const BLOCKING_CONDITIONS: &[(&str, &str)] = &[
+    (CONDITION_BACKEND_READY, "False"),
+    (CONDITION_TARGET_VALID, "False"),
+    (target::CONDITION_TARGET_DENIED, "True"),
+    (host::CONDITION_HOST_DENIED, "True"),
+    (host::CONDITION_QUOTA_EXCEEDED, "True"),
+    (ingressclass::CONDITION_INGRESS_CLASS_MISSING, "True"),
+    (loops::CONDITION_LOOP_DETECTED, "True"),
+    (CONDITION_HOST_CONFLICT, "True"),
+    (CONDITION_NETWORKING_AVAILABLE, "False"),
+    (CONDITION_INGRESS_NAMESPACE_ALLOWED, "False"),
+    (CONDITION_APPLY_CONFLICT, "True"),
+    (CONDITION_INGRESS_READY, "False"),
+    (CONDITION_TLS_READY, "False"),
+];
+
+/// Condition type reporting objects that could not be applied, as someone else manages them.
+pub const CONDITION_APPLY_CONFLICT: &str = "ApplyConflict";
+
+/// Condition type reporting whether the web tier answers requests with the Redirect.
+pub const CONDITION_SERVING: &str = "Serving";
+
+/// Condition type reporting whether the Ingress may be created in `spec.ingress.namespace`.
+pub const CONDITION_INGRESS_NAMESPACE_ALLOWED: &str = "IngressNamespaceAllowed";
+
+const SERVICE_ACCOUNT_NAMESPACE_FILE: &str =
+    "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+
 #[derive(Clone)]
 pub struct Context {
     pub self_namespace: String,
     pub self_service_name: String,
 
     pub client: Client,
-    pub api: Api<Redirect>,
+    /// the namespaces to watch, all namespaces if empty
+    pub watch_namespaces: BTreeSet<String>,
+    /// never list or watch cluster-wide, see [`watch::namespaced_only`]
+    pub namespaced_only: bool,
+    /// only Redirects matching this label selector are reconciled
+    pub redirect_selector: Option<String>,
     // pub diagnostics: Arc<RwLock<Diagnostics>>,
     pub metrics: Arc<Metrics>,
 
     pub leader_state: Receiver<LeaderState>,
+
+    /// cache of the operator's own Service, kept up to date by a watch
+    pub services: Store<Service>,
+
+    pub path_maps: PathMaps,
+
+    /// the Redirect controller's store
+    pub redirects: Store<Redirect>,
+
+    /// hosts of the Redirects in `redirects`, kept up to date by the Redirect controller
+    pub hosts: host::HostIndex,
+
+    pub recorder: Recorder,
+
+    /// domains Redirects may lead to
+    pub target_policy: Arc<target::TargetPolicy>,
+    pub requeue: requeue::Requeue,
+
+    /// kinds of optional backends the cluster does not serve
+    pub unavailable_kinds: BTreeSet<&'static str>,
+
+    /// Ingress settings for all Redirects
+    pub operator_defaults: OperatorDefaults,
+
+    /// external-dns settings for Redirects not setting their own
+    pub external_dns: ExternalDnsDefaults,
+
+    /// triggers rebuilding the shared Ingresses
+    pub shared_ingress_sync: Arc<Notify>,
+
+    /// namespaces besides its own the operator may create Ingresses in, `*` for all
+    pub ingress_namespaces: BTreeSet<String>,
+
+    /// create Ingresses in the Redirects' namespaces unless they say otherwise
+    pub ingress_same_namespace: bool,
+
+    /// what to do about objects of the same name managed by someone else
+    pub conflict_policy: ConflictPolicy,
+
+    /// requests to reconcile everything
+    pub reconcile_all: resync::ReconcileAll,
+
+    /// which namespaces may claim which hosts
+    pub host_policies: hostpolicy::HostPolicies,
+
+    /// the cluster's IngressClasses
+    pub ingress_classes: ingressclass::IngressClasses,
+
+    /// compute and log changes, writes are only sent as server-side dry runs
+    pub dry_run: bool,
+
+    /// check that the hosts resolve to the Ingresses' load balancers
+    pub dns_verify: bool,
+
+    /// probes the targets, if enabled
+    pub prober: Option<probe::Prober>,
+
+    /// manages the hosts' DNS records, if configured
+    pub dns_records: Option<dns::DnsRecords>,
+
+    /// the Redirects this replica reconciles, all without sharding
+    pub shard: Option<shard::Shard>,
+
+    /// the TLS Secret copied next to Ingresses without their own certificate
+    pub wildcard_tls: Option<WildcardSecret>,
+
+    /// clean up with a finalizer, otherwise only the sweep deletes objects of deleted Redirects
+    pub finalizers: bool,
+
+    /// concurrency and debounce of the controllers
+    pub controller_config: Config,
+}
+
+/// How to apply an object that exists with fields owned by another field manager.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// leave it alone and report it in the `ApplyConflict` condition
+    #[default]
+    Fail,
+    /// take over the conflicting fields
+    Force,
+    /// take over objects labeled with the Redirect's owner label, fail for others
+    Adopt,
+}
+
+impl ConflictPolicy {
+    /// Reads `APPLY_CONFLICT_POLICY`: `fail`, `force` or `adopt`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match env::var("APPLY_CONFLICT_POLICY").as_deref() {
+            Err(_) | Ok("") | Ok("fail") => Ok(Self::Fail),
+            Ok("force") => Ok(Self::Force),
+            Ok("adopt") => Ok(Self::Adopt),
+            Ok(other) => anyhow::bail!("invalid APPLY_CONFLICT_POLICY {other}"),
+        }
+    }
+}
+
+/// Reads `CONTROLLER_CONCURRENCY` (default 2, 0 for unbounded) and
+/// `CONTROLLER_DEBOUNCE_MILLISECONDS` (default 500, 0 to reconcile right away).
+///
+/// Debouncing collapses bursts of changes to an object, e.g. from GitOps syncs, into one
+/// reconcile.
+fn controller_config_from_env() -> anyhow::Result<Config> {
+    let concurrency = match env::var("CONTROLLER_CONCURRENCY") {
+        Ok(v) => v.parse().context("invalid CONTROLLER_CONCURRENCY")?,
+        Err(_) => 2,
+    };
+    let debounce = match env::var("CONTROLLER_DEBOUNCE_MILLISECONDS") {
+        Ok(v) => v
+            .parse()
+            .context("invalid CONTROLLER_DEBOUNCE_MILLISECONDS")?,
+        Err(_) => 500,
+    };
+    Ok(Config::default()
+        .concurrency(concurrency)
+        .debounce(Duration::from_millis(debounce)))
+}
+
+/// The namespace the operator runs in.
+///
+/// Prefers `POD_NAMESPACE` (downward API), then the mounted service account namespace.
+pub fn self_namespace() -> String {
+    env::var("POD_NAMESPACE")
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string(SERVICE_ACCOUNT_NAMESPACE_FILE)
+                .ok()
+                .map(|ns| ns.trim().to_string())
+        })
+        .filter(|ns| !ns.is_empty())
+        .unwrap_or("redirect-operator".to_string())
 }
 
+/// The name of the Service generated Ingresses point at.
+pub fn self_service_name() -> String {
+    env::var("REDIRECT_SERVICE_NAME")
+        .or_else(|_| env::var("SERVICE_NAME"))
+        .unwrap_or("redirect-operator".to_string())
+}
+
+/// Spawns leader election, configured by `LEASE_NAME`, `LEASE_NAMESPACE`, `LEASE_IDENTITY`,
+/// `LEASE_DURATION_SECONDS` and `LEASE_RENEW_DEADLINE_SECONDS`.
+///
+/// The lease is named after the operator, in its namespace, held under the pod's name. With
+/// sharding every shard has its own lease.
 pub async fn setup_leader_election(client: Client) -> anyhow::Result<LeaderElectorHandle> {
-    let self_namespace = env::var("POD_NAMESPACE").unwrap_or("redirect-operator".to_string());
     let self_pod_name = env::var("POD_NAME").unwrap_or("redirect-operator".to_string());
-    let self_service_name =
-        env::var("REDIRECT_SERVICE_NAME").unwrap_or("redirect-operator".to_string());
-
-    let config = kube_coordinate::Config {
-        name: REDIRECT_KUBE_SLUG.to_string(),
-        namespace: self_namespace,
-        identity: self_pod_name,
-        manager: self_service_name,
+    let seconds = |var: &str| -> anyhow::Result<Option<i32>> {
+        env::var(var)
+            .ok()
+            .map(|s| s.parse().with_context(|| format!("invalid {var}")))
+            .transpose()
+    };
+
+    // a dry-running operator must not keep the one already deployed from leading
+    let mut default_lease_name = if dryrun::enabled() {
+        format!("{REDIRECT_KUBE_SLUG}-dry-run")
+    } else {
+        REDIRECT_KUBE_SLUG.to_string()
+    };
+    if let Some(shard) = shard::Shard::from_env()? {
+        default_lease_name = shard.lease_name(&default_lease_name);
+    }
+    let mut config = kube_coordinate::Config {
+        name: env::var("LEASE_NAME").unwrap_or(default_lease_name),
+        namespace: env::var("LEASE_NAMESPACE").unwrap_or_else(|_| self_namespace()),
+        identity: env::var("LEASE_IDENTITY").unwrap_or(self_pod_name),
+        manager: self_service_name(),
         ..Default::default()
     };
+    if let Some(duration) = seconds("LEASE_DURATION_SECONDS")? {
+        config.lease_duration_seconds = duration;
+    }
+    if let Some(deadline) = seconds("LEASE_RENEW_DEADLINE_SECONDS")? {
+        config.renew_deadline_seconds = deadline;
+    }
     LeaderElector::spawn(config, client.clone()).context("cannot spawn leader election")
 }
 
+/// Keeps the `is_leader` gauge up to date.
+fn export_leadership(mut leader_state: Receiver<LeaderState>, metrics: Arc<Metrics>) {
+    tokio::spawn(async move {
+        loop {
+            let is_leader = leader_state.borrow_and_update().is_leader();
+            metrics.reconcile.leader.set(is_leader.into());
+            if leader_state.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Hashes what reconciles depend on: the spec, through `metadata.generation`, and metadata
+/// users or other controllers set.
+fn spec_or_metadata_changed<K: Resource>(obj: &K) -> Option<u64> {
+    let meta = obj.meta();
+    let mut hasher = DefaultHasher::new();
+    meta.generation.hash(&mut hasher);
+    meta.labels.hash(&mut hasher);
+    meta.annotations.hash(&mut hasher);
+    meta.finalizers.hash(&mut hasher);
+    meta.deletion_timestamp.is_some().hash(&mut hasher);
+    Some(hasher.finish())
+}
+
 impl Context {
+    /// The context for the Redirect controller reflecting into `redirects`.
     pub async fn from_env_with_leader_state(
         client: Client,
         leader_state: Receiver<LeaderState>,
+        reconcile_all: resync::ReconcileAll,
+        redirects: Store<Redirect>,
     ) -> anyhow::Result<Self> {
-        let self_namespace = env::var("POD_NAMESPACE").unwrap_or("redirect-operator".to_string());
-        let self_service_name =
-            env::var("REDIRECT_SERVICE_NAME").unwrap_or("redirect-operator".to_string());
-
-        let api = match env::var("WATCH_NAMESPACE") {
-            Ok(ns) => Api::namespaced(client.clone(), &ns),
-            Err(env::VarError::NotPresent) => Api::all(client.clone()),
-            Err(e) => Err(e)?,
+        let self_namespace = self_namespace();
+        let self_service_name = self_service_name();
+
+        let watch_namespaces = watch::namespaces_from_env();
+        let namespaced_only = watch::namespaced_only()?;
+        let ingress_namespaces: BTreeSet<String> = env::var("INGRESS_NAMESPACES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|ns| !ns.is_empty())
+            .map(str::to_string)
+            .collect();
+        if namespaced_only && ingress_namespaces.contains("*") {
+            anyhow::bail!("NAMESPACED_RBAC cannot create Ingresses in all namespaces");
+        }
+
+        let metrics = Arc::new(Metrics::from_env()?);
+
+        let (services, writer) = reflector::store();
+        let service_api: Api<Service> = Api::namespaced(client.clone(), &self_namespace);
+        let service_watcher = watcher(
+            service_api,
+            watcher::Config::default().fields(&format!("metadata.name={self_service_name}")),
+        );
+        tokio::spawn(
+            reflector::reflector(writer, service_watcher)
+                .default_backoff()
+                .touched_objects()
+                .for_each(|res| async move {
+                    if let Err(e) = res {
+                        warn!("watching own service failed: {:?}", e);
+                    }
+                }),
+        );
+
+        let path_maps = PathMaps::spawn(watch::watch(
+            &client,
+            &watch_namespaces,
+            watcher::Config::default().labels(CONFIG_MAP_LABEL),
+        ));
+
+        let recorder = Recorder::new(
+            client.clone(),
+            Reporter {
+                controller: REDIRECT_KUBE_SLUG.to_string(),
+                instance: env::var("POD_NAME").ok(),
+            },
+        );
+
+        let unavailable_kinds = unavailable_kinds(&client).await;
+
+        let (host_policies, ingress_classes) = if namespaced_only {
+            (
+                hostpolicy::HostPolicies::none(),
+                ingressclass::IngressClasses::none(),
+            )
+        } else {
+            (
+                hostpolicy::HostPolicies::spawn(client.clone(), reconcile_all.clone()),
+                ingressclass::IngressClasses::spawn(client.clone(), reconcile_all.clone()),
+            )
         };
 
-        let metrics = Default::default();
+        let dry_run = dryrun::enabled();
+        if dry_run {
+            warn!("dry run: changes are logged, not made");
+        }
 
-        // let lease = Arc::new(LeaseLock::new(
-        //     client.clone(),
-        //     &self_namespace,
-        //     LeaseLockParams {
-        //         lease_name: self_service_name.clone(),
-        //         holder_id: self_pod_name,
-        //         lease_ttl: Duration::from_secs(15),
-        //     },
-        // ));
+        let mut operator_defaults = OperatorDefaults::from_env()?;
+        let wildcard_tls = WildcardSecret::from_env()?;
+        if let Some(wildcard) = &wildcard_tls {
+            operator_defaults.tls_secret_name = Some(wildcard.name.clone());
+            let mut params = PatchParams::apply(REDIRECT_KUBE_SLUG);
+            params.dry_run = dry_run;
+            let copy_namespaces = if namespaced_only {
+                watch_namespaces
+                    .union(&ingress_namespaces)
+                    .cloned()
+                    .collect()
+            } else {
+                BTreeSet::new()
+            };
+            wildcard.clone().spawn(
+                client.clone(),
+                self_namespace.clone(),
+                copy_namespaces,
+                params,
+            );
+        }
 
         Ok(Self {
             client,
-            api,
+            watch_namespaces,
+            namespaced_only,
+            redirect_selector: env::var("REDIRECT_LABEL_SELECTOR")
+                .ok()
+                .filter(|s| !s.is_empty()),
             metrics,
             self_namespace,
             self_service_name,
             leader_state,
+            services,
+            path_maps,
+            redirects,
+            hosts: host::HostIndex::new().with_quota(host::quota_from_env()?),
+            recorder,
+            target_policy: Arc::new(target::TargetPolicy::from_env()?),
+            requeue: requeue::Requeue::from_env()?,
+            unavailable_kinds,
+            external_dns: ExternalDnsDefaults::from_env()?,
+            operator_defaults,
+            shared_ingress_sync: Arc::new(Notify::new()),
+            ingress_namespaces,
+            ingress_same_namespace: env::var("INGRESS_SAME_NAMESPACE").is_ok_and(|v| v == "true"),
+            conflict_policy: ConflictPolicy::from_env()?,
+            host_policies,
+            ingress_classes,
+            reconcile_all,
+            dry_run,
+            dns_verify: dnsverify::enabled(),
+            prober: probe::Prober::from_env()?,
+            dns_records: dns::DnsRecords::from_env(client.clone(), &self_namespace, dry_run)
+                .await?,
+            shard: shard::Shard::from_env()?,
+            wildcard_tls,
+            finalizers: !env::var("REDIRECT_FINALIZERS").is_ok_and(|v| v == "false"),
+            controller_config: controller_config_from_env()?,
+        })
+    }
+
+    /// `Api`s for the watched namespaces, or for all namespaces.
+    pub fn watched_apis<K>(&self) -> Vec<Api<K>>
+    where
+        K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope, DynamicType = ()>,
+    {
+        if self.watch_namespaces.is_empty() {
+            return vec![Api::all(self.client.clone())];
+        }
+        self.watch_namespaces
+            .iter()
+            .map(|ns| Api::namespaced(self.client.clone(), ns))
+            .collect()
+    }
+
+    /// Watches the watched namespaces, see [`watch::watch`].
+    pub fn watch<K>(&self, config: watcher::Config) -> BoxStream<'static, watch::WatchEvent<K>>
+    where
+        K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope, DynamicType = ()>
+            + Clone
+            + std::fmt::Debug
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        watch::watch(&self.client, &self.watch_namespaces, config)
+    }
+
+    /// A controller for `K` in the watched namespaces.
+    ///
+    /// Only changes to the spec or to metadata reconcile, not the controller's own status
+    /// updates. Requeues still repair drift.
+    pub fn controller<K>(&self, config: watcher::Config) -> Controller<K>
+    where
+        K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope, DynamicType = ()>
+            + Clone
+            + std::fmt::Debug
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.controller_with_store(reflector::store(), self.watch(config))
+    }
+
+    /// A controller for `K` fed by `events`, reflected into `store`, see [`Self::controller`].
+    pub fn controller_with_store<K>(
+        &self,
+        (reader, writer): (Store<K>, Writer<K>),
+        events: impl Stream<Item = watch::WatchEvent<K>> + Send + 'static,
+    ) -> Controller<K>
+    where
+        K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope, DynamicType = ()>
+            + Clone
+            + std::fmt::Debug
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        let objects = reflector::reflector(writer, events)
+            .applied_objects()
+            .predicate_filter(spec_or_metadata_changed, Default::default());
+        Controller::for_stream(objects, reader)
+    }
+
+    /// The defaults for Redirects in `ns`, the operator's alone without cluster-wide access.
+    pub async fn namespace_defaults(&self, ns: &str) -> Result<NamespaceDefaults, Error> {
+        let defaults = if self.namespaced_only {
+            NamespaceDefaults::default()
+        } else {
+            NamespaceDefaults::fetch(self.client.clone(), ns).await?
+        };
+        Ok(defaults.with_operator_defaults(&self.operator_defaults))
+    }
+
+    /// Whether this replica reconciles `redirect`: it leads and the Redirect is in its shard.
+    pub fn reconciles(&self, redirect: &Redirect) -> bool {
+        self.leader_state.borrow().is_leader()
+            && self.shard.is_none_or(|shard| {
+                shard.owns(
+                    redirect.metadata.namespace.as_deref().unwrap_or_default(),
+                    &redirect.name_any(),
+                )
+            })
+    }
+
+    /// Whether this replica runs cluster-wide work: it leads the first or only shard.
+    pub fn leads_cluster(&self) -> bool {
+        self.leader_state.borrow().is_leader() && self.shard.is_none_or(|s| s.is_primary())
+    }
+
+    /// `params` as a server-side dry run in dry-run mode.
+    pub fn patch_params(&self, params: PatchParams) -> PatchParams {
+        if self.dry_run {
+            params.dry_run()
+        } else {
+            params
+        }
+    }
+
+    /// `DeleteParams`, as a server-side dry run in dry-run mode.
+    pub fn delete_params(&self) -> DeleteParams {
+        DeleteParams {
+            dry_run: self.dry_run,
+            ..DeleteParams::default()
+        }
+    }
+
+    /// Publishes an Event on `redirect`, failing to do so is only logged.
+    pub async fn publish_event(
+        &self,
+        redirect: &Redirect,
+        type_: EventType,
+        reason: &str,
+        note: impl ToString,
+        action: &str,
+    ) {
+        if self.dry_run {
+            info!(
+                "dry run: not publishing {} event on {}/{}: {}",
+                reason,
+                redirect.namespace().unwrap_or_default(),
+                redirect.name_any(),
+                note.to_string()
+            );
+            return;
+        }
+        let event = Event {
+            type_,
+            reason: reason.to_string(),
+            note: Some(note.to_string()),
+            action: action.to_string(),
+            secondary: None,
+        };
+        if let Err(e) = self
+            .recorder
+            .publish(&event, &redirect.object_ref(&()))
+            .await
+        {
+            warn!("cannot publish event: {:?}", e);
+        }
+    }
+
+    /// Warns about `condition` if it has the `problem` status, and publishes a Warning event
+    /// when the problem is new, not on every reconcile.
+    async fn announce(
+        &self,
+        redirect: &Redirect,
+        condition: Condition,
+        problem: &str,
+    ) -> Condition {
+        let condition = warn_on(redirect, condition, problem);
+        let before = redirect.status.as_ref().is_some_and(|s| {
+            s.conditions
+                .iter()
+                .any(|c| c.type_ == condition.type_ && c.status == problem)
+        });
+        if condition.status == problem && !before {
+            self.publish_event(
+                redirect,
+                EventType::Warning,
+                &condition.type_,
+                &condition.message,
+                "Reconcile",
+            )
+            .await;
+        }
+        condition
+    }
+
+    /// Whether Ingresses may be created in `namespace`.
+    pub fn ingress_namespace_allowed(&self, namespace: &str) -> bool {
+        namespace == self.self_namespace
+            || self.ingress_namespaces.contains("*")
+            || self.ingress_namespaces.contains(namespace)
+    }
+
+    /// Whether objects for `redirect` may be applied in `namespace`. The Redirect's own
+    /// namespace is always fine, its owner could create them as well.
+    fn may_apply_in(&self, redirect: &Redirect, namespace: &str) -> bool {
+        redirect.metadata.namespace.as_deref() == Some(namespace)
+            || self.ingress_namespace_allowed(namespace)
+    }
+
+    /// Applies an ExternalName Service in `namespace` aliasing the operator's Service.
+    ///
+    /// Ingresses can only point at Services in their own namespace. The alias is
+    /// shared by all Redirects using the namespace and is left in place.
+    async fn apply_service_alias(&self, namespace: &str) -> Result<(), Error> {
+        let api: Api<Service> = Api::namespaced(self.client.clone(), namespace);
+        let service = Service {
+            metadata: ObjectMeta {
+                name: Some(self.self_service_name.clone()),
+                namespace: Some(namespace.to_string()),
+                ..ObjectMeta::default()
+            },
+            spec: Some(ServiceSpec {
+                type_: Some("ExternalName".to_string()),
+                external_name: Some(format!(
+                    "{}.{}.svc.cluster.local",
+                    self.self_service_name, self.self_namespace
+                )),
+                ports: Some(vec![ServicePort {
+                    name: Some("http".to_string()),
+                    port: REDIRECT_SERVICE_PORT,
+                    ..ServicePort::default()
+                }]),
+                ..ServiceSpec::default()
+            }),
+            status: None,
+        };
+        api.patch(
+            &self.self_service_name,
+            &self.patch_params(PatchParams::apply(REDIRECT_KUBE_SLUG)),
+            &Patch::Apply(service),
+        )
+        .await
+        .map_err(Error::ServiceApplyFailed)?;
+        Ok(())
+    }
+
+    /// Checks that the operator's Service exists and exposes the redirect port.
+    fn backend_condition(&self) -> Condition {
+        let service_ref = ObjectRef::new(&self.self_service_name).within(&self.self_namespace);
+        let service_name = format!("{}/{}", self.self_namespace, self.self_service_name);
+        let has_port = |service: &Service| {
+            service
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.ports.as_ref())
+                .is_some_and(|ports| ports.iter().any(|p| p.port == REDIRECT_SERVICE_PORT))
+        };
+
+        match self.services.get(&service_ref) {
+            None => condition(
+                CONDITION_BACKEND_READY,
+                false,
+                "ServiceNotFound",
+                format!("Service {service_name} does not exist"),
+            ),
+            Some(service) if !has_port(&service) => condition(
+                CONDITION_BACKEND_READY,
+                false,
+                "PortNotFound",
+                format!("Service {service_name} does not expose port {REDIRECT_SERVICE_PORT}"),
+            ),
+            Some(_) => condition(
+                CONDITION_BACKEND_READY,
+                true,
+                "ServiceFound",
+                format!("Service {service_name} exposes port {REDIRECT_SERVICE_PORT}"),
+            ),
+        }
+    }
+}
+
+pub(crate) fn condition(
+    type_: &str,
+    status: bool,
+    reason: &str,
+    message: impl ToString,
+) -> Condition {
+    Condition {
+        type_: type_.to_string(),
+        status: if status { "True" } else { "False" }.to_string(),
+        reason: reason.to_string(),
+        message: message.to_string(),
+        last_transition_time: Time(k8s_openapi::jiff::Timestamp::now()),
+        observed_generation: None,
+    }
+}
+
+/// Warns about `condition` if it has the `problem` status.
+fn warn_on(redirect: &Redirect, condition: Condition, problem: &str) -> Condition {
+    if condition.status == problem {
+        warn!(
+            "Redirect {}/{}: {}",
+            redirect.namespace().unwrap_or_default(),
+            redirect.name_any(),
+            condition.message
+        );
+    }
+    condition
+}
+
+/// Whether the conflict policy allows taking over the fields of object `name`.
+async fn may_take_over(
+    ctx: &Context,
+    api: &Api<DynamicObject>,
+    name: &str,
+    redirect: &Redirect,
+) -> Result<bool, Error> {
+    Ok(match ctx.conflict_policy {
+        ConflictPolicy::Fail => false,
+        ConflictPolicy::Force => true,
+        ConflictPolicy::Adopt => api
+            .get_metadata_opt(name)
+            .await
+            .map_err(Error::ObjectFetchFailed)?
+            .is_some_and(|object| {
+                object.labels().get(gc::OWNER_LABEL) == Some(&gc::owner_label_value(redirect))
+            }),
+    })
+}
+
+/// The distinct IPs and host names the load balancers of `ingresses` report.
+fn load_balancer_addresses(ingresses: &[DynamicObject]) -> Vec<String> {
+    ingresses
+        .iter()
+        .filter_map(|ingress| ingress.data.pointer("/status/loadBalancer/ingress"))
+        .filter_map(|lb| lb.as_array())
+        .flatten()
+        .filter_map(|lb| lb.get("ip").or_else(|| lb.get("hostname"))?.as_str())
+        .map(String::from)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Whether `live` already is what applying `desired` would make it.
+///
+/// Fields defaulted by the API server make some kinds always look changed, those are applied
+/// on every reconcile as before.
+fn up_to_date(live: &DynamicObject, desired: &DynamicObject) -> bool {
+    let contains = |live: &Option<BTreeMap<String, String>>,
+                    desired: &Option<BTreeMap<String, String>>| {
+        desired.iter().flatten().all(|(key, value)| {
+            live.as_ref()
+                .is_some_and(|live| live.get(key) == Some(value))
         })
+    };
+    live.metadata.deletion_timestamp.is_none()
+        && live.data.get("spec") == desired.data.get("spec")
+        && contains(&live.metadata.labels, &desired.metadata.labels)
+        && contains(&live.metadata.annotations, &desired.metadata.annotations)
+        && (desired.metadata.owner_references.is_none()
+            || live.metadata.owner_references == desired.metadata.owner_references)
+}
+
+/// Applies `status` as the operator's field manager.
+///
+/// Status fields another manager holds are taken over with a warning, e.g. those written by
+/// merge patches of older operator versions.
+pub(crate) async fn apply_status<K>(
+    api: &Api<K>,
+    name: &str,
+    status: &impl serde::Serialize,
+) -> Result<(), Error>
+where
+    K: Resource<DynamicType = ()> + Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let patch = Patch::Apply(json!({
+        "apiVersion": K::api_version(&()),
+        "kind": K::kind(&()),
+        "status": status,
+    }));
+    let params = PatchParams::apply(REDIRECT_KUBE_SLUG);
+    match api.patch_status(name, &params, &patch).await {
+        Err(kube::Error::Api(response)) if response.code == 409 => {
+            warn!(
+                "status of {} {} conflicts with another field manager, taking it over: {}",
+                K::kind(&()),
+                name,
+                response.message
+            );
+            api.patch_status(name, &params.force(), &patch).await
+        }
+        res => res,
+    }
+    .map(|_| ())
+    .map_err(Error::StatusUpdateFailed)
+}
+
+/// A short description of where requests go, for `kubectl get`.
+fn target_summary(spec: &RedirectSpec) -> String {
+    match spec.mode {
+        RedirectMode::CanonicalHost => spec.canonical_host.clone().unwrap_or_default(),
+        RedirectMode::Page => "page".to_string(),
+        RedirectMode::Redirect | RedirectMode::Proxy => match &spec.split {
+            Some(split) if !split.targets.is_empty() => {
+                format!("split across {} targets", split.targets.len())
+            }
+            _ => spec.to.uri.clone(),
+        },
+    }
+}
+
+/// The Ready condition, false with the first blocking condition's reason if there is one.
+fn ready_condition(conditions: &[Condition]) -> Condition {
+    let blocking = BLOCKING_CONDITIONS.iter().find_map(|(type_, bad)| {
+        conditions
+            .iter()
+            .find(|c| c.type_ == *type_ && c.status == *bad)
+    });
+    match blocking {
+        Some(c) => condition(
+            CONDITION_READY,
+            false,
+            &c.reason,
+            format!("{}: {}", c.type_, c.message),
+        ),
+        None => condition(CONDITION_READY, true, "Ready", "serving as specified"),
+    }
+}
+
+/// Sets `observedGeneration` and keeps the transition time of conditions whose status did not change.
+fn settle_conditions(
+    conditions: &mut [Condition],
+    previous: &[Condition],
+    generation: Option<i64>,
+) {
+    for c in conditions {
+        c.observed_generation = generation;
+        if let Some(before) = previous
+            .iter()
+            .find(|p| p.type_ == c.type_ && p.status == c.status)
+        {
+            c.last_transition_time = before.last_transition_time.clone();
+        }
     }
 }
 
@@ -97,25 +911,81 @@ fn ingress_backend(service_name: impl ToString) -> IngressBackend {
             name: service_name.to_string(),
             port: Some(ServiceBackendPort {
                 name: None,
-                number: Some(8080),
+                number: Some(REDIRECT_SERVICE_PORT),
             }),
         }),
     }
 }
-fn ingress_for_redirect(ctx: &Arc<Context>, redirect: &Redirect) -> Ingress {
-    let redirect_ingress = redirect.spec.ingress.clone();
-    let ingress_name = ingress_name_for_redirect(redirect);
+/// Hosts per generated Ingress, Redirects with more hosts get several Ingresses.
+///
+/// Keeps Ingresses well below the object size limit and certificates within the
+/// 100 names Let's Encrypt allows.
+pub const MAX_HOSTS_PER_INGRESS: usize = 100;
+
+/// Name of the `index`th Ingress of a Redirect, the first one keeps the plain name.
+fn ingress_chunk_name(redirect: &Redirect, index: usize) -> String {
+    let name = ingress_name_for_redirect(redirect);
+    if index == 0 {
+        name
+    } else {
+        format!("{name}-{index}")
+    }
+}
 
+/// Ingress paths as configured in `ingress.paths`, else for `match.paths`,
+/// everything if there are none or any is a regex.
+pub(crate) fn ingress_paths(
+    service_name: &str,
+    redirect_ingress: &RedirectIngress,
+    match_paths: &[RedirectPathMatch],
+) -> Vec<HTTPIngressPath> {
+    let path = |path: &str, path_type: &str| HTTPIngressPath {
+        backend: ingress_backend(service_name),
+        path: Some(path.to_string()),
+        path_type: path_type.to_string(),
+    };
+    if !redirect_ingress.paths.is_empty() {
+        return redirect_ingress
+            .paths
+            .iter()
+            .map(|p| path(&p.path, p.path_type.as_str()))
+            .collect();
+    }
+    if match_paths.is_empty()
+        || match_paths
+            .iter()
+            .any(|p| p.path_type == PathMatchType::Regex)
+    {
+        return vec![path("/", "Prefix")];
+    }
+    match_paths
+        .iter()
+        .map(|p| match p.path_type {
+            PathMatchType::Exact => path(&p.path, "Exact"),
+            _ => path(&p.path, "Prefix"),
+        })
+        .collect()
+}
+
+fn ingress_for_hosts(
+    namespace: &str,
+    service_name: &str,
+    redirect_ingress: &RedirectIngress,
+    match_paths: &[RedirectPathMatch],
+    ingress_name: String,
+    hosts: &[&String],
+) -> Ingress {
     // cannot own across namespaces
     // let oref = redirect.controller_owner_ref(&()).unwrap();
 
     let tls = if redirect_ingress.tls.enabled {
         Some(vec![IngressTLS {
-            hosts: Some(redirect.spec.hosts.clone().into_iter().collect()),
+            hosts: Some(hosts.iter().map(|h| h.to_string()).collect()),
             secret_name: Some(
                 redirect_ingress
                     .tls
                     .secret_name
+                    .clone()
                     .unwrap_or_else(|| format!("{}-tls-certs", ingress_name)),
             ),
         }])
@@ -123,19 +993,13 @@ fn ingress_for_redirect(ctx: &Arc<Context>, redirect: &Redirect) -> Ingress {
         None
     };
     let http_rule = Some(HTTPIngressRuleValue {
-        paths: vec![HTTPIngressPath {
-            backend: ingress_backend(&ctx.self_service_name),
-            path: Some("/".to_string()),
-            path_type: "Prefix".to_string(),
-        }],
+        paths: ingress_paths(service_name, redirect_ingress, match_paths),
     });
     let rules = Some(
-        redirect
-            .spec
-            .hosts
+        hosts
             .iter()
             .map(|host| IngressRule {
-                host: Some(host.clone()),
+                host: Some(host.to_string()),
                 http: http_rule.clone(),
             })
             .collect(),
@@ -144,16 +1008,16 @@ fn ingress_for_redirect(ctx: &Arc<Context>, redirect: &Redirect) -> Ingress {
     Ingress {
         metadata: ObjectMeta {
             name: Some(ingress_name),
-            namespace: Some(ctx.self_namespace.clone()),
+            namespace: Some(namespace.to_string()),
 
             // cannot own across namespaces
             // owner_references: Some(vec![oref]),
-            annotations: redirect_ingress.annotations,
-            labels: redirect_ingress.labels,
+            annotations: ingress_annotations(redirect_ingress),
+            labels: redirect_ingress.labels.clone(),
             ..ObjectMeta::default()
         },
         spec: Some(IngressSpec {
-            ingress_class_name: redirect_ingress.ingress_class_name,
+            ingress_class_name: redirect_ingress.ingress_class_name.clone(),
             rules,
             tls,
             ..IngressSpec::default()
@@ -162,6 +1026,542 @@ fn ingress_for_redirect(ctx: &Arc<Context>, redirect: &Redirect) -> Ingress {
     }
 }
 
+/// The Ingress annotations, with the issuer fields translated for cert-manager's ingress-shim.
+pub(crate) fn ingress_annotations(
+    redirect_ingress: &RedirectIngress,
+) -> Option<BTreeMap<String, String>> {
+    let tls = &redirect_ingress.tls;
+    let issuers = [
+        (CERT_MANAGER_CLUSTER_ISSUER_ANNOTATION, &tls.cluster_issuer),
+        (CERT_MANAGER_ISSUER_ANNOTATION, &tls.issuer),
+    ];
+    let mut annotations = redirect_ingress.annotations.clone();
+    for (annotation, issuer) in issuers {
+        if let Some(issuer) = issuer.as_ref().filter(|_| tls.enabled) {
+            annotations
+                .get_or_insert_default()
+                .insert(annotation.to_string(), issuer.clone());
+        }
+    }
+    annotations
+}
+
+/// The Ingresses serving a Redirect, one per `MAX_HOSTS_PER_INGRESS` hosts.
+///
+/// `redirect_ingress` are the Redirect's Ingress settings with namespace defaults applied.
+/// An explicit `tls.secretName` is used for all of them. Hosts conflicting with
+/// older Redirects are left out.
+pub(crate) fn ingresses_for_redirect(
+    rctx: &RenderContext,
+    redirect: &Redirect,
+    redirect_ingress: &RedirectIngress,
+) -> Vec<Ingress> {
+    let (hosts, _) = host::served_hosts(&redirect.spec);
+    let hosts: Vec<&String> = hosts
+        .iter()
+        .filter(|h| !rctx.conflicting_hosts.contains(*h))
+        .collect();
+
+    hosts
+        .chunks(MAX_HOSTS_PER_INGRESS)
+        .enumerate()
+        .map(|(index, chunk)| {
+            ingress_for_hosts(
+                rctx.namespace,
+                rctx.service_name,
+                redirect_ingress,
+                &redirect.spec.match_.paths,
+                ingress_chunk_name(redirect, index),
+                chunk,
+            )
+        })
+        .collect()
+}
+
+/// Where backends render objects: the operator's namespace and Service, and the
+/// defaults of the Redirect's namespace.
+pub struct RenderContext<'a> {
+    pub namespace: &'a str,
+    pub service_name: &'a str,
+    pub defaults: &'a NamespaceDefaults,
+    /// the operator's external-dns settings
+    pub external_dns: &'a ExternalDnsDefaults,
+    /// whether backends may answer without the operator, false while it refuses the Redirect
+    pub allow_native: bool,
+    /// hosts older Redirects claim, left out of Ingresses
+    pub conflicting_hosts: &'a BTreeSet<String>,
+}
+
+/// A kind of networking object routing a Redirect's hosts to the operator.
+///
+/// Objects are server-side applied in the operator's namespace. Objects applied
+/// before, by the names the status records, are deleted when not rendered anymore.
+pub trait NetworkingBackend: Sync {
+    /// the kind of the objects, for logs and conditions
+    fn kind(&self) -> &'static str;
+
+    fn api_resource(&self) -> ApiResource;
+
+    /// Whether the API might be missing from a cluster, it is checked on startup then.
+    fn optional(&self) -> bool {
+        false
+    }
+
+    /// Whether the backend's objects should exist for `spec` right now.
+    fn wanted(&self, spec: &RedirectSpec) -> bool;
+
+    /// The namespace to apply the objects for `redirect` in, `None` for the operator's namespace.
+    ///
+    /// `same_namespace` is the operator's default for `spec.ingress.sameNamespace`.
+    fn namespace<'a>(&self, _redirect: &'a Redirect, _same_namespace: bool) -> Option<&'a str> {
+        None
+    }
+
+    /// The objects serving `redirect`, with conditions describing them.
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>);
+
+    /// Namespaces and names of the objects applied before, according to the status.
+    ///
+    /// Objects the status records without a namespace are in `self_namespace`.
+    fn existing(&self, redirect: &Redirect, self_namespace: &str) -> BTreeSet<(String, String)>;
+
+    /// Conditions on what the objects need besides the Redirect, checked on every reconcile.
+    fn check<'a>(
+        &'a self,
+        _ctx: &'a Context,
+        _redirect: &'a Redirect,
+        _defaults: &'a NamespaceDefaults,
+    ) -> BoxFuture<'a, Vec<Condition>> {
+        Box::pin(async { Vec::new() })
+    }
+
+    /// Sets up what the objects need in `namespace` before they are applied.
+    fn prepare<'a>(
+        &'a self,
+        _ctx: &'a Context,
+        _redirect: &'a Redirect,
+        _namespace: &'a str,
+        _defaults: &'a NamespaceDefaults,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Records the applied objects, as returned by the API server, in the status.
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]);
+
+    /// Completes the status once all backends are applied, e.g. with readiness conditions.
+    fn finish<'a>(
+        &'a self,
+        _ctx: &'a Context,
+        _redirect: &'a Redirect,
+        _defaults: &'a NamespaceDefaults,
+        _status: &'a mut RedirectStatus,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error;
+
+    fn delete_failed(&self, error: kube::Error) -> Error;
+}
+
+/// All networking backends, in the order they are applied.
+pub const BACKENDS: &[&dyn NetworkingBackend] = &[
+    &Ingresses,
+    &certificate::Certificates,
+    &route::Routes,
+    &istio::VirtualServices,
+    &contour::HttpProxies,
+    &emissary::Mappings,
+    &kong::KongPlugins,
+];
+
+/// Condition type reporting whether the cluster serves the APIs of all wanted backends.
+pub const CONDITION_NETWORKING_AVAILABLE: &str = "NetworkingAvailable";
+
+/// Kinds of optional backends whose API the cluster does not serve.
+///
+/// Checked once on startup, installing e.g. Istio later needs an operator restart.
+async fn unavailable_kinds(client: &Client) -> BTreeSet<&'static str> {
+    let mut unavailable = BTreeSet::new();
+    for backend in BACKENDS.iter().filter(|b| b.optional()) {
+        let resource = backend.api_resource();
+        let gvk = GroupVersionKind::gvk(&resource.group, &resource.version, &resource.kind);
+        if discovery::pinned_kind(client, &gvk).await.is_err() {
+            info!("{} API not found, not generating them", backend.kind());
+            unavailable.insert(backend.kind());
+        }
+    }
+    unavailable
+}
+
+/// Ingresses, one per `MAX_HOSTS_PER_INGRESS` hosts.
+pub struct Ingresses;
+
+impl Ingresses {
+    /// Records the shared Ingresses serving `redirect`, they are rebuilt from all Redirects
+    /// at once, outside of its reconcile.
+    async fn record_shared(
+        &self,
+        ctx: &Context,
+        redirect: &Redirect,
+        defaults: &NamespaceDefaults,
+        status: &mut RedirectStatus,
+    ) -> Result<(), Error> {
+        let settings = defaults.apply(&redirect.spec.ingress);
+        let external_dns = ctx
+            .external_dns
+            .resolve(&redirect.spec.ingress.external_dns);
+        let group = shared::group_name(
+            &settings,
+            external_dns.as_ref(),
+            shared::partition(ctx.redirect_selector.as_deref()).as_deref(),
+        );
+        let api: Api<DynamicObject> = Api::namespaced_with(
+            ctx.client.clone(),
+            &ctx.self_namespace,
+            &self.api_resource(),
+        );
+        // the group's Ingresses are all served by the same controller
+        if let Some(ingress) = api
+            .get_opt(&group)
+            .await
+            .map_err(Error::IngressFetchFailed)?
+        {
+            status.addresses = load_balancer_addresses(&[ingress]);
+        }
+        if settings.tls.enabled {
+            status.tls_secrets = vec![RedirectStatusTlsSecret {
+                name: settings.tls.secret_name.clone().unwrap_or_else(|| {
+                    format!("{}-tls-certs", ingress_name_for_redirect(redirect))
+                }),
+                namespace: ctx.self_namespace.clone(),
+            }];
+        }
+        status.shared_ingress = Some(group);
+        Ok(())
+    }
+
+    /// The IngressReady and TLSReady conditions.
+    async fn readiness(
+        &self,
+        ctx: &Context,
+        redirect: &Redirect,
+        defaults: &NamespaceDefaults,
+        status: &mut RedirectStatus,
+    ) {
+        let namespace_allowed = self
+            .namespace(redirect, ctx.ingress_same_namespace)
+            .is_none_or(|namespace| ctx.may_apply_in(redirect, namespace));
+        status
+            .conditions
+            .push(if let Some(group) = &status.shared_ingress {
+                condition(
+                    CONDITION_INGRESS_READY,
+                    true,
+                    "Shared",
+                    format!("served by the shared Ingresses {group}"),
+                )
+            } else if !namespace_allowed {
+                condition(
+                    CONDITION_INGRESS_READY,
+                    false,
+                    "NamespaceNotAllowed",
+                    "the Ingress namespace is not allowed",
+                )
+            } else if status.ingresses.is_empty() {
+                condition(
+                    CONDITION_INGRESS_READY,
+                    false,
+                    "NoHosts",
+                    "no host left to serve",
+                )
+            } else {
+                condition(
+                    CONDITION_INGRESS_READY,
+                    true,
+                    "Applied",
+                    format!("{} Ingresses applied", status.ingresses.len()),
+                )
+            });
+
+        if defaults.apply(&redirect.spec.ingress).tls.enabled {
+            let tls_condition = match status
+                .conditions
+                .iter()
+                .find(|c| c.type_ == certificate::CONDITION_CERTIFICATE_READY)
+            {
+                // the Secrets are missing or outdated until the Certificates are issued
+                Some(certificates) if certificates.status != "True" => Condition {
+                    type_: CONDITION_TLS_READY.to_string(),
+                    ..certificates.clone()
+                },
+                _ => {
+                    let (condition, expiry) =
+                        tls::readiness(&ctx.client, &status.tls_secrets).await;
+                    status.certificate_expiry = expiry;
+                    condition
+                }
+            };
+            status.conditions.push(tls_condition);
+        }
+    }
+}
+
+impl NetworkingBackend for Ingresses {
+    fn kind(&self) -> &'static str {
+        "Ingress"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        ApiResource::erase::<Ingress>(&())
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        spec.wants_ingress() && !spec.ingress.shared
+    }
+
+    fn namespace<'a>(&self, redirect: &'a Redirect, same_namespace: bool) -> Option<&'a str> {
+        let redirect_namespace = redirect.metadata.namespace.as_deref()?;
+        redirect
+            .spec
+            .ingress
+            .target_namespace(redirect_namespace, same_namespace)
+    }
+
+    /// Whether the IngressClass exists and the operator may create Ingresses in their namespace.
+    fn check<'a>(
+        &'a self,
+        ctx: &'a Context,
+        redirect: &'a Redirect,
+        defaults: &'a NamespaceDefaults,
+    ) -> BoxFuture<'a, Vec<Condition>> {
+        Box::pin(async move {
+            let mut conditions = Vec::new();
+            if self.wanted(&redirect.spec)
+                && let Some(class) = defaults.apply(&redirect.spec.ingress).ingress_class_name
+            {
+                let class_condition = if ctx.ingress_classes.missing(&class) {
+                    condition(
+                        ingressclass::CONDITION_INGRESS_CLASS_MISSING,
+                        true,
+                        "NotFound",
+                        format!("IngressClass {class} does not exist"),
+                    )
+                } else {
+                    condition(
+                        ingressclass::CONDITION_INGRESS_CLASS_MISSING,
+                        false,
+                        "Found",
+                        format!("IngressClass {class} exists"),
+                    )
+                };
+                conditions.push(ctx.announce(redirect, class_condition, "True").await);
+            }
+            if let Some(namespace) = self.namespace(redirect, ctx.ingress_same_namespace) {
+                let namespace_condition = if ctx.may_apply_in(redirect, namespace) {
+                    condition(
+                        CONDITION_INGRESS_NAMESPACE_ALLOWED,
+                        true,
+                        "NamespaceAllowed",
+                        format!("Ingresses are created in {namespace}"),
+                    )
+                } else {
+                    condition(
+                        CONDITION_INGRESS_NAMESPACE_ALLOWED,
+                        false,
+                        "NamespaceNotAllowed",
+                        format!("the operator may not create Ingresses in {namespace}"),
+                    )
+                };
+                conditions.push(warn_on(redirect, namespace_condition, "False"));
+            }
+            conditions
+        })
+    }
+
+    /// Ingresses can only point at Services in their own namespace, outside of the
+    /// operator's they need an alias of its Service and a copy of the wildcard certificate.
+    fn prepare<'a>(
+        &'a self,
+        ctx: &'a Context,
+        redirect: &'a Redirect,
+        namespace: &'a str,
+        defaults: &'a NamespaceDefaults,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if namespace == ctx.self_namespace {
+                return Ok(());
+            }
+            ctx.apply_service_alias(namespace).await?;
+            if let Some(wildcard) = &ctx.wildcard_tls
+                && defaults.apply(&redirect.spec.ingress).tls.secret_name
+                    == Some(wildcard.name.clone())
+            {
+                wildcard
+                    .copy_to(
+                        &ctx.client,
+                        namespace,
+                        &ctx.patch_params(PatchParams::apply(REDIRECT_KUBE_SLUG)),
+                    )
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let redirect_ingress = rctx.defaults.apply(&redirect.spec.ingress);
+        let external_dns = rctx
+            .external_dns
+            .resolve(&redirect.spec.ingress.external_dns);
+        let (offload_annotations, offloaded) = offload::ingress_annotations(
+            &redirect_ingress.offload.clone().unwrap_or_default(),
+            redirect,
+            rctx.allow_native,
+        );
+        let ingresses = ingresses_for_redirect(rctx, redirect, &redirect_ingress)
+            .into_iter()
+            .map(|mut ingress| {
+                if let Some(external_dns) = &external_dns {
+                    external_dns.annotate(&mut ingress);
+                }
+                if !offload_annotations.is_empty() {
+                    ingress
+                        .metadata
+                        .annotations
+                        .get_or_insert_default()
+                        .extend(offload_annotations.clone());
+                }
+                serde_json::to_value(ingress)
+                    .and_then(serde_json::from_value)
+                    .expect("Ingress converts to a DynamicObject")
+            })
+            .collect();
+        (ingresses, offloaded.into_iter().collect())
+    }
+
+    /// The plain name, if enabled, and the names of all chunks according to the status.
+    fn existing(&self, redirect: &Redirect, self_namespace: &str) -> BTreeSet<(String, String)> {
+        let mut names = BTreeSet::new();
+        if redirect.spec.ingress.enabled {
+            names.insert((
+                self_namespace.to_string(),
+                ingress_name_for_redirect(redirect),
+            ));
+        }
+        if let Some(status) = &redirect.status {
+            names.extend(
+                status
+                    .ingresses
+                    .iter()
+                    .filter(|i| !i.name.is_empty())
+                    .map(|i| (i.namespace.clone(), i.name.clone())),
+            );
+        }
+        names
+    }
+
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]) {
+        status.ingresses = applied
+            .iter()
+            .map(|ingress| RedirectStatusIngress {
+                name: ingress.name_any(),
+                namespace: namespace.to_string(),
+                hosts: ingress
+                    .data
+                    .pointer("/spec/rules")
+                    .and_then(|rules| rules.as_array())
+                    .map_or(0, Vec::len),
+                host_names: ingress
+                    .data
+                    .pointer("/spec/rules")
+                    .and_then(|rules| rules.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|rule| rule.get("host")?.as_str())
+                    .map(String::from)
+                    .collect(),
+            })
+            .collect();
+        if let Some(first) = status.ingresses.first() {
+            status.ingress = first.clone();
+        }
+        status.addresses = load_balancer_addresses(applied);
+        status.tls_secrets = tls::secrets(applied);
+        status
+            .conditions
+            .extend(external_dns::published_condition(applied));
+    }
+
+    fn finish<'a>(
+        &'a self,
+        ctx: &'a Context,
+        redirect: &'a Redirect,
+        defaults: &'a NamespaceDefaults,
+        status: &'a mut RedirectStatus,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let shared = redirect.spec.wants_ingress() && redirect.spec.ingress.shared;
+            if shared {
+                self.record_shared(ctx, redirect, defaults, status).await?;
+            }
+            let was_shared = redirect
+                .status
+                .as_ref()
+                .is_some_and(|s| s.shared_ingress.is_some());
+            if shared || was_shared {
+                ctx.shared_ingress_sync.notify_one();
+            }
+            if redirect.spec.wants_ingress() {
+                self.readiness(ctx, redirect, defaults, status).await;
+            }
+            Ok(())
+        })
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::IngressCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::IngressDeletionFailed(error)
+    }
+}
+
+/// Renders all objects the controller would apply for `redirect`, without namespace defaults.
+///
+/// `namespace` and `service_name` are the operator's namespace and Service.
+pub fn render(redirect: &Redirect, namespace: &str, service_name: &str) -> Vec<serde_json::Value> {
+    let defaults = NamespaceDefaults::default();
+    let external_dns = ExternalDnsDefaults::default();
+    let conflicting_hosts = BTreeSet::new();
+    BACKENDS
+        .iter()
+        .filter(|backend| backend.wanted(&redirect.spec))
+        .flat_map(|backend| {
+            let rctx = RenderContext {
+                namespace: backend.namespace(redirect, false).unwrap_or(namespace),
+                service_name,
+                defaults: &defaults,
+                external_dns: &external_dns,
+                allow_native: true,
+                conflicting_hosts: &conflicting_hosts,
+            };
+            backend.render(&rctx, redirect).0
+        })
+        .map(|object| serde_json::to_value(object).expect("objects serialize"))
+        .collect()
+}
+
 pub fn ingress_name_for_redirect(redirect: &Redirect) -> String {
     format!(
         "{}.{}",
@@ -175,37 +1575,592 @@ pub async fn reconcile(
     redirect: Arc<Redirect>,
     ctx: Arc<Context>,
 ) -> Result<Action, finalizer::Error<Error>> {
-    if !ctx.leader_state.borrow().is_leader() {
-        info!("not acting because we are not leader");
-        return Ok(Action::requeue(Duration::from_secs(300)));
+    // the store and HTTP serving stay up, everything is reconciled once we become leader
+    if !ctx.reconciles(&redirect) {
+        // shared Ingresses are built by the first shard from all Redirects
+        if ctx.leads_cluster() {
+            ctx.shared_ingress_sync.notify_one();
+        }
+        if ctx.shard.is_some() {
+            info!("not acting because we are not leader of its shard");
+        } else {
+            info!("not acting because we are not leader");
+        }
+        return Ok(Action::await_change());
+    }
+
+    // adding and removing the finalizer are writes, too
+    if ctx.dry_run {
+        return if redirect.metadata.deletion_timestamp.is_some() {
+            cleanup(redirect, ctx)
+                .await
+                .map_err(finalizer::Error::CleanupFailed)
+        } else {
+            apply(redirect, ctx)
+                .await
+                .map_err(finalizer::Error::ApplyFailed)
+        };
     }
 
     let ns = redirect.metadata.namespace.as_deref().unwrap();
     let api: Api<Redirect> = Api::namespaced(ctx.client.clone(), ns);
-    finalizer(
+    let has_finalizer = redirect
+        .finalizers()
+        .iter()
+        .any(|f| f == REDIRECT_KUBE_FINALIZER_SLUG);
+    if !ctx.finalizers {
+        match (
+            redirect.metadata.deletion_timestamp.is_some(),
+            has_finalizer,
+        ) {
+            // a deletion started with the finalizer is finished with it
+            (true, true) => {}
+            // the sweep deletes the objects once the Redirect is gone
+            (true, false) => return Ok(Action::await_change()),
+            (false, _) => {
+                // Redirects of earlier runs with finalizers are released
+                if has_finalizer {
+                    remove_finalizer(&api, &redirect)
+                        .await
+                        .map_err(finalizer::Error::RemoveFinalizer)?;
+                }
+                let result = apply(redirect.clone(), ctx.clone())
+                    .await
+                    .map_err(finalizer::Error::ApplyFailed);
+                if let Err(finalizer::Error::ApplyFailed(e)) = &result {
+                    ctx.publish_event(&redirect, EventType::Warning, &e.reason(), e, "Reconcile")
+                        .await;
+                }
+                return result;
+            }
+        }
+    }
+
+    let result = finalizer(
         &api,
         REDIRECT_KUBE_FINALIZER_SLUG,
-        redirect,
+        redirect.clone(),
         |event| async {
             match event {
-                finalizer::Event::Apply(redirect) => apply(redirect, ctx).await,
-                finalizer::Event::Cleanup(redirect) => cleanup(redirect, ctx).await,
+                finalizer::Event::Apply(redirect) => apply(redirect, ctx.clone()).await,
+                finalizer::Event::Cleanup(redirect) => cleanup(redirect, ctx.clone()).await,
             }
         },
     )
-    .await
+    .await;
+
+    // make failures visible in `kubectl describe`, not only in the operator's logs
+    if let Err(finalizer::Error::ApplyFailed(e) | finalizer::Error::CleanupFailed(e)) = &result {
+        ctx.publish_event(&redirect, EventType::Warning, &e.reason(), e, "Reconcile")
+            .await;
+    }
+    result
+}
+
+/// Removes the operator's finalizer, failing if the Redirect changed in the meantime.
+async fn remove_finalizer(api: &Api<Redirect>, redirect: &Redirect) -> Result<(), kube::Error> {
+    let finalizers: Vec<&String> = redirect
+        .finalizers()
+        .iter()
+        .filter(|f| *f != REDIRECT_KUBE_FINALIZER_SLUG)
+        .collect();
+    let patch = Patch::Merge(json!({
+        "metadata": {
+            "finalizers": finalizers,
+            "resourceVersion": redirect.resource_version(),
+        }
+    }));
+    info!("removing the finalizer, objects are cleaned up by the sweep");
+    api.patch(&redirect.name_any(), &PatchParams::default(), &patch)
+        .await?;
+    Ok(())
+}
+
+fn is_not_found(error: &kube::Error) -> bool {
+    matches!(error, kube::Error::Api(response) if response.code == 404)
+}
+
+/// Deletes a generated object, it not existing is fine.
+///
+/// Returns whether there was an object to delete.
+async fn delete_object(
+    ctx: &Context,
+    backend: &dyn NetworkingBackend,
+    namespace: &str,
+    name: &str,
+) -> Result<bool, Error> {
+    let api: Api<DynamicObject> =
+        Api::namespaced_with(ctx.client.clone(), namespace, &backend.api_resource());
+    if ctx.dry_run {
+        dryrun::log_delete(backend.kind(), namespace, name);
+    }
+    match api.delete(name, &ctx.delete_params()).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(backend.delete_failed(e)),
+    }
 }
 
 #[instrument(skip(ctx), fields(trace_id))]
 pub async fn cleanup(redirect: Arc<Redirect>, ctx: Arc<Context>) -> Result<Action, Error> {
-    let ingress_api: Api<Ingress> = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+    let selector = format!("{}={}", gc::OWNER_LABEL, gc::owner_label_value(&redirect));
+    for backend in BACKENDS
+        .iter()
+        .filter(|b| !ctx.unavailable_kinds.contains(b.kind()))
+    {
+        let mut existing = backend.existing(&redirect, &ctx.self_namespace);
+        // also objects the status lost track of
+        let namespace = backend
+            .namespace(&redirect, ctx.ingress_same_namespace)
+            .unwrap_or(&ctx.self_namespace);
+        let api: Api<DynamicObject> =
+            Api::namespaced_with(ctx.client.clone(), namespace, &backend.api_resource());
+        match api
+            .list_metadata(&ListParams::default().labels(&selector))
+            .await
+        {
+            Ok(labeled) => existing.extend(
+                labeled
+                    .items
+                    .iter()
+                    .filter(|o| gc::owner(o) == Some(ObjectRef::from_obj(&*redirect)))
+                    .map(|o| (namespace.to_string(), o.name_any())),
+            ),
+            // the namespace may have become off-limits, the sweep catches those later
+            Err(e) => warn!("cannot list {} in {}: {:?}", backend.kind(), namespace, e),
+        }
+        // objects already gone count as deleted, shared Ingresses are never in this list
+        for (namespace, name) in existing {
+            delete_object(&ctx, *backend, &namespace, &name).await?;
+        }
+    }
+    if let Some(dns_records) = &ctx.dns_records
+        && let Some(status) = &redirect.status
+        && !status.dns_records.is_empty()
+        && let Err(e) = dns_records
+            .delete(&redirect, &status.dns_records.iter().cloned().collect())
+            .await
+    {
+        // leftover records only point at the load balancers, not worth blocking the deletion
+        warn!(
+            "cannot delete DNS records of {}: {}",
+            redirect.name_any(),
+            e
+        );
+    }
+    // the shared Ingresses are rebuilt without the Redirect's hosts
+    if redirect.spec.ingress.shared {
+        ctx.shared_ingress_sync.notify_one();
+    }
+    ctx.requeue.forget(&*redirect);
+    ctx.metrics.reconcile.forget(
+        &redirect.namespace().unwrap_or_default(),
+        &redirect.name_any(),
+    );
+    Ok(Action::await_change())
+}
 
-    let ingress_name = ingress_name_for_redirect(&redirect);
-    ingress_api
-        .delete(&ingress_name, &Default::default())
-        .await
-        .map_err(Error::IngressDeletionFailed)?;
-    Ok(Action::requeue(Duration::from_secs(300)))
+/// Checks the hosts, returning those not to serve: hosts older Redirects claim, hosts the
+/// namespace may not claim and hosts beyond its quota.
+async fn check_hosts(
+    ctx: &Context,
+    redirect: &Redirect,
+    status: &mut RedirectStatus,
+) -> BTreeSet<String> {
+    let ns = redirect.namespace().unwrap_or_default();
+
+    let (_, mut invalid_hosts) = host::normalize_all(&redirect.spec.hosts);
+    invalid_hosts.extend(
+        host::unmatched_overrides(&redirect.spec)
+            .into_iter()
+            .map(|h| format!("{h} (override for unlisted host)")),
+    );
+    let hosts_condition = if invalid_hosts.is_empty() {
+        condition(
+            CONDITION_HOSTS_VALID,
+            true,
+            "HostsValid",
+            "all hosts are valid",
+        )
+    } else {
+        condition(
+            CONDITION_HOSTS_VALID,
+            false,
+            "InvalidHosts",
+            format!("ignoring invalid hosts: {}", invalid_hosts.join(", ")),
+        )
+    };
+    status
+        .conditions
+        .push(warn_on(redirect, hosts_condition, "False"));
+
+    let conflicts = ctx.hosts.conflicts(redirect);
+    let mut withheld: BTreeSet<String> = conflicts.keys().cloned().collect();
+    let conflict_condition = if conflicts.is_empty() {
+        condition(
+            CONDITION_HOST_CONFLICT,
+            false,
+            "NoConflict",
+            "no older Redirect claims the hosts",
+        )
+    } else {
+        condition(
+            CONDITION_HOST_CONFLICT,
+            true,
+            "HostClaimed",
+            format!(
+                "not serving hosts claimed by older Redirects: {}",
+                conflicts
+                    .iter()
+                    .map(|(host, other)| format!(
+                        "{host} ({}/{})",
+                        other.namespace().unwrap_or_default(),
+                        other.name_any()
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    };
+    status
+        .conditions
+        .push(ctx.announce(redirect, conflict_condition, "True").await);
+
+    let denied_hosts = ctx
+        .host_policies
+        .denied(&ns, &host::served_hosts(&redirect.spec).0);
+    let denied_condition = if denied_hosts.is_empty() {
+        condition(
+            host::CONDITION_HOST_DENIED,
+            false,
+            "HostsAllowed",
+            "the namespace may claim all hosts",
+        )
+    } else {
+        condition(
+            host::CONDITION_HOST_DENIED,
+            true,
+            "HostNotAllowed",
+            format!(
+                "not serving, the namespace may not claim {}",
+                denied_hosts
+                    .iter()
+                    .map(|(host, policies)| format!("{host} ({})", policies.join(", ")))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    };
+    status
+        .conditions
+        .push(warn_on(redirect, denied_condition, "True"));
+    // keep the hosts from reaching the edge
+    withheld.extend(denied_hosts.into_keys());
+
+    let over_quota = ctx.hosts.over_quota(redirect);
+    let quota_condition = if over_quota.is_empty() {
+        condition(
+            host::CONDITION_QUOTA_EXCEEDED,
+            false,
+            "WithinQuota",
+            "the namespace's host quota covers all hosts",
+        )
+    } else {
+        condition(
+            host::CONDITION_QUOTA_EXCEEDED,
+            true,
+            "QuotaExceeded",
+            format!(
+                "not serving hosts beyond the namespace's host quota: {}",
+                over_quota.iter().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        )
+    };
+    status
+        .conditions
+        .push(ctx.announce(redirect, quota_condition, "True").await);
+    withheld.extend(over_quota);
+
+    withheld
+}
+
+/// Whether `spec` has a usable target for its mode.
+fn target_condition(spec: &RedirectSpec) -> Condition {
+    match spec.mode {
+        RedirectMode::Redirect | RedirectMode::Proxy
+            if spec.to.uri.is_empty() && spec.split.is_none() =>
+        {
+            condition(
+                CONDITION_TARGET_VALID,
+                false,
+                "MissingTarget",
+                "to.uri is empty",
+            )
+        }
+        RedirectMode::Page
+            if spec
+                .page
+                .as_ref()
+                .is_none_or(|p| p.html.is_none() && p.config_map_ref.is_none()) =>
+        {
+            condition(
+                CONDITION_TARGET_VALID,
+                false,
+                "MissingPage",
+                "page mode needs page.html or page.configMapRef",
+            )
+        }
+        RedirectMode::CanonicalHost if host::canonical_host(spec).is_none() => condition(
+            CONDITION_TARGET_VALID,
+            false,
+            "MissingCanonicalHost",
+            "canonicalHost mode needs a valid canonicalHost",
+        ),
+        _ => {
+            let invalid = target::check_all(spec);
+            if invalid.is_empty() {
+                condition(CONDITION_TARGET_VALID, true, "TargetValid", "target is set")
+            } else {
+                condition(
+                    CONDITION_TARGET_VALID,
+                    false,
+                    "InvalidTarget",
+                    invalid.join("; "),
+                )
+            }
+        }
+    }
+}
+
+/// Checks the TTL, the request conditions and the ConfigMaps with path maps and pages.
+async fn check_sources(
+    ctx: &Context,
+    redirect: &Redirect,
+    status: &mut RedirectStatus,
+) -> Result<(), Error> {
+    let ns = redirect.namespace().unwrap_or_default();
+    let mut conditions = Vec::new();
+    if let Some(ttl) = &redirect.spec.ttl {
+        conditions.push(match ttl::check(ttl) {
+            Ok(_) => condition(
+                ttl::CONDITION_TTL_VALID,
+                true,
+                "TTLValid",
+                "the TTL is valid",
+            ),
+            Err(problem) => condition(ttl::CONDITION_TTL_VALID, false, "InvalidTTL", problem),
+        });
+    }
+    if !redirect.spec.match_.is_empty() {
+        conditions.push(matcher::check(&redirect.spec.match_));
+    }
+    if let Some(path_map) = &redirect.spec.path_map {
+        conditions.push(pathmap::check(ctx.client.clone(), &ns, path_map).await?);
+    }
+    if let Some(source) = redirect
+        .spec
+        .not_found
+        .as_ref()
+        .and_then(|n| n.config_map_ref.as_ref())
+    {
+        conditions.push(
+            pathmap::check_page(
+                ctx.client.clone(),
+                &ns,
+                source,
+                pathmap::CONDITION_NOT_FOUND_PAGE_VALID,
+            )
+            .await?,
+        );
+    }
+    if let Some(source) = redirect
+        .spec
+        .page
+        .as_ref()
+        .and_then(|p| p.config_map_ref.as_ref())
+    {
+        conditions.push(
+            pathmap::check_page(
+                ctx.client.clone(),
+                &ns,
+                source,
+                pathmap::CONDITION_PAGE_VALID,
+            )
+            .await?,
+        );
+    }
+    status.conditions.extend(
+        conditions
+            .into_iter()
+            .map(|c| warn_on(redirect, c, "False")),
+    );
+    Ok(())
+}
+
+/// Applies `object`, returning it as the API server stored it, or `None` when someone else
+/// manages it and the conflict policy does not allow taking it over.
+async fn apply_object(
+    ctx: &Context,
+    backend: &dyn NetworkingBackend,
+    api: &Api<DynamicObject>,
+    redirect: &Redirect,
+    (namespace, name): &(String, String),
+    object: DynamicObject,
+    live: Option<&DynamicObject>,
+) -> Result<Option<DynamicObject>, Error> {
+    // objects labeled as ours are, take back fields someone edited
+    let params = ctx.patch_params(if live.is_some() {
+        PatchParams::apply(REDIRECT_KUBE_SLUG).force()
+    } else {
+        PatchParams::apply(REDIRECT_KUBE_SLUG)
+    });
+    let object = match api.patch(name, &params, &Patch::Apply(&object)).await {
+        Err(kube::Error::Api(response)) if response.code == 409 => {
+            if !may_take_over(ctx, api, name, redirect).await? {
+                warn!(
+                    "not applying {} {}/{}: {}",
+                    backend.kind(),
+                    namespace,
+                    name,
+                    response.message
+                );
+                return Ok(None);
+            }
+            info!("taking over {} {}/{}", backend.kind(), namespace, name);
+            api.patch(name, &params.force(), &Patch::Apply(&object))
+                .await
+                .map_err(|e| backend.apply_failed(e))?
+        }
+        res => res.map_err(|e| backend.apply_failed(e))?,
+    };
+    if ctx.dry_run {
+        // objects of older operator versions lack the owner label
+        let before = match live {
+            Some(live) => Some(live.clone()),
+            None => api.get_opt(name).await.map_err(Error::ObjectFetchFailed)?,
+        };
+        dryrun::log_diff(
+            backend.kind(),
+            namespace,
+            name,
+            before.map(|b| dryrun::comparable(&b)).as_ref(),
+            &dryrun::comparable(&object),
+        );
+    }
+    Ok(Some(object))
+}
+
+/// Applies the objects `backend` renders for `redirect` and removes those it does not
+/// render anymore. Objects someone else manages are added to `conflicts` and left alone.
+///
+/// `spec_applied` is whether the Redirect's generation was applied before, unchanged
+/// objects are not written again then.
+async fn apply_backend(
+    ctx: &Context,
+    backend: &dyn NetworkingBackend,
+    redirect: &Redirect,
+    rctx: &RenderContext<'_>,
+    spec_applied: bool,
+    status: &mut RedirectStatus,
+    conflicts: &mut Vec<(String, String)>,
+) -> Result<(), Error> {
+    let ns = redirect.namespace().unwrap_or_default();
+    let namespace = rctx.namespace;
+    let mut applied = Vec::new();
+    let mut existing = backend.existing(redirect, &ctx.self_namespace);
+    if backend.wanted(&redirect.spec) && ctx.may_apply_in(redirect, namespace) {
+        backend
+            .prepare(ctx, redirect, namespace, rctx.defaults)
+            .await?;
+        let api: Api<DynamicObject> =
+            Api::namespaced_with(ctx.client.clone(), namespace, &backend.api_resource());
+        let selector = format!("{}={}", gc::OWNER_LABEL, gc::owner_label_value(redirect));
+        let live: BTreeMap<String, DynamicObject> = api
+            .list(&ListParams::default().labels(&selector))
+            .await
+            .map_err(Error::ObjectListFailed)?
+            .items
+            .into_iter()
+            .filter(|o| {
+                gc::owner(o).is_some_and(|owner| {
+                    owner.name == redirect.name_any()
+                        && owner.namespace.as_deref() == Some(ns.as_str())
+                })
+            })
+            .map(|o| (o.name_any(), o))
+            .collect();
+        // the status misses objects applied right before a failed status update
+        existing.extend(
+            live.keys()
+                .map(|name| (namespace.to_string(), name.clone())),
+        );
+        let (objects, conditions) = backend.render(rctx, redirect);
+        status.conditions.extend(conditions);
+        for mut object in objects {
+            gc::mark(&mut object, redirect);
+            // only objects next to the Redirect can be owned, and garbage collected, by it
+            if namespace == ns {
+                object.metadata.owner_references =
+                    redirect.controller_owner_ref(&()).map(|o| vec![o]);
+            }
+            let key = (namespace.to_string(), object.name_any());
+            // resyncs of unchanged Redirects should not write anything; fields dropped
+            // from the spec are only noticed by applying, so not after spec changes
+            if spec_applied
+                && let Some(live) = live.get(&key.1)
+                && up_to_date(live, &object)
+            {
+                applied.push(live.clone());
+                continue;
+            }
+            let Some(object) =
+                apply_object(ctx, backend, &api, redirect, &key, object, live.get(&key.1)).await?
+            else {
+                conflicts.push(key);
+                continue;
+            };
+            // the status does not know about objects of never reconciled Redirects
+            if redirect.status.is_none() || !existing.contains(&key) {
+                ctx.publish_event(
+                    redirect,
+                    EventType::Normal,
+                    &format!("{}Created", backend.kind()),
+                    format!("created {} {}/{}", backend.kind(), key.0, key.1),
+                    "Create",
+                )
+                .await;
+            }
+            applied.push(object);
+        }
+    }
+    backend.record(status, namespace, &applied);
+
+    // remove objects left over from a larger host set, another namespace, or no longer wanted at all
+    for (stale_namespace, stale) in existing.into_iter().filter(|key| {
+        (key.0 != namespace || !applied.iter().any(|o| o.name_any() == key.1))
+            && !conflicts.contains(key)
+    }) {
+        info!(
+            "removing surplus {} {}/{}",
+            backend.kind(),
+            stale_namespace,
+            stale
+        );
+        if delete_object(ctx, backend, &stale_namespace, &stale).await? {
+            ctx.publish_event(
+                redirect,
+                EventType::Normal,
+                &format!("{}Deleted", backend.kind()),
+                format!(
+                    "deleted surplus {} {stale_namespace}/{stale}",
+                    backend.kind()
+                ),
+                "Delete",
+            )
+            .await;
+        }
+    }
+    Ok(())
 }
 
 #[instrument(skip(ctx), fields(trace_id))]
@@ -218,67 +2173,560 @@ pub async fn apply(redirect: Arc<Redirect>, ctx: Arc<Context>) -> Result<Action,
 
     let api: Api<Redirect> = Api::namespaced(ctx.client.clone(), &ns);
 
-    let mut status = RedirectStatus::default();
-    if redirect.spec.ingress.enabled {
-        let ingress = ingress_for_redirect(&ctx, &redirect);
-        let ingress_name = ingress.name_any();
+    let mut requeue_after = ctx.requeue.resync(&*redirect);
+    let mut remaining_seconds = None;
+    let expires_at = ttl::expires_at(&redirect);
+    if let Some(expires_at) = expires_at {
+        let remaining = expires_at.duration_since(k8s_openapi::jiff::Timestamp::now());
+        match Duration::try_from(remaining) {
+            Ok(remaining) if !remaining.is_zero() => {
+                requeue_after = requeue_after.min(remaining + Duration::from_secs(1));
+                remaining_seconds = Some(remaining.as_secs());
+            }
+            _ => {
+                info!("Redirect {}/{} expired, deleting it", ns, redirect_name);
+                ctx.publish_event(
+                    &redirect,
+                    EventType::Normal,
+                    "Expired",
+                    format!("expired at {expires_at}, deleting"),
+                    "Delete",
+                )
+                .await;
+                // the finalizer removes the generated objects, without one nothing would until
+                // the sweep
+                if !ctx.finalizers {
+                    cleanup(redirect.clone(), ctx.clone()).await?;
+                }
+                if ctx.dry_run {
+                    dryrun::log_delete("Redirect", &ns, &redirect_name);
+                }
+                api.delete(&redirect_name, &ctx.delete_params())
+                    .await
+                    .map_err(Error::RedirectExpiryFailed)?;
+                return Ok(Action::await_change());
+            }
+        }
+    }
+
+    let mut status = RedirectStatus {
+        short_links: shortlink::assign_codes(&redirect),
+        expires_at: expires_at.map(Time),
+        remaining_seconds,
+        served_hosts: Some(
+            host::served_hosts(&redirect.spec)
+                .0
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        target: Some(target_summary(&redirect.spec)),
+        ..Default::default()
+    };
+
+    let conflicting_hosts = check_hosts(&ctx, &redirect, &mut status).await;
+    // serve the hosts soon once older Redirects, or others of the namespace, are gone
+    if status.conditions.iter().any(|c| {
+        (c.type_ == CONDITION_HOST_CONFLICT || c.type_ == host::CONDITION_QUOTA_EXCEEDED)
+            && c.status == "True"
+    }) {
+        requeue_after = requeue_after.min(Duration::from_secs(60));
+    }
+
+    status.conditions.push(warn_on(
+        &redirect,
+        target_condition(&redirect.spec),
+        "False",
+    ));
+
+    let looping = loops::detect(&redirect, &ctx.redirects.state());
+    ctx.metrics.reconcile.set_loop(&redirect, looping.is_some());
+    let loop_condition = match looping {
+        Some(chain) => condition(
+            loops::CONDITION_LOOP_DETECTED,
+            true,
+            "RedirectLoop",
+            format!(
+                "target leads back to a managed host: {}",
+                loops::describe(&chain)
+            ),
+        ),
+        None => condition(
+            loops::CONDITION_LOOP_DETECTED,
+            false,
+            "NoLoop",
+            "target does not lead back to a managed host",
+        ),
+    };
+    status
+        .conditions
+        .push(warn_on(&redirect, loop_condition, "True"));
+
+    let path_map = ctx.path_maps.table_for(&redirect);
+    let denied = ctx.target_policy.denied(
+        &redirect.spec,
+        path_map.iter().flat_map(|table| table.values()),
+    );
+    let denied_condition = if denied.is_empty() {
+        condition(
+            target::CONDITION_TARGET_DENIED,
+            false,
+            "TargetAllowed",
+            "all targets are allowed",
+        )
+    } else {
+        condition(
+            target::CONDITION_TARGET_DENIED,
+            true,
+            "TargetNotAllowed",
+            format!(
+                "not serving, targets outside the allowed domains: {}",
+                denied.join(", ")
+            ),
+        )
+    };
+    status
+        .conditions
+        .push(warn_on(&redirect, denied_condition, "True"));
+
+    check_sources(&ctx, &redirect, &mut status).await?;
+
+    status.conditions.push(if redirect.spec.paused {
+        condition(
+            CONDITION_PAUSED,
+            true,
+            "Paused",
+            format!(
+                "answering with {} while paused",
+                redirect.spec.pause.status_code
+            ),
+        )
+    } else {
+        condition(CONDITION_PAUSED, false, "Active", "redirecting")
+    });
 
-        let ingress_api: Api<Ingress> = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+    let wanted: Vec<_> = BACKENDS
+        .iter()
+        .filter(|b| b.wanted(&redirect.spec))
+        .collect();
+    let shared = redirect.spec.wants_ingress() && redirect.spec.ingress.shared;
+    let defaults = if wanted.is_empty() && !shared {
+        NamespaceDefaults::default()
+    } else {
+        status
+            .conditions
+            .push(warn_on(&redirect, ctx.backend_condition(), "False"));
 
-        ingress_api
-            .patch(
-                &ingress_name,
-                &PatchParams::apply(REDIRECT_KUBE_SLUG),
-                &Patch::Apply(ingress),
+        let unavailable: Vec<&str> = wanted
+            .iter()
+            .map(|b| b.kind())
+            .filter(|kind| ctx.unavailable_kinds.contains(kind))
+            .collect();
+        let available_condition = if unavailable.is_empty() {
+            condition(
+                CONDITION_NETWORKING_AVAILABLE,
+                true,
+                "Available",
+                "the cluster serves all requested kinds",
             )
-            .await
-            .map_err(Error::IngressCreationFailed)?;
+        } else {
+            condition(
+                CONDITION_NETWORKING_AVAILABLE,
+                false,
+                "Unavailable",
+                format!("the cluster does not serve {}", unavailable.join(", ")),
+            )
+        };
+        status
+            .conditions
+            .push(warn_on(&redirect, available_condition, "False"));
 
-        status.ingress = RedirectStatusIngress {
-            name: ingress_name,
-            namespace: ctx.self_namespace.clone(),
+        ctx.namespace_defaults(&ns).await?
+    };
+
+    for backend in BACKENDS {
+        let conditions = backend.check(&ctx, &redirect, &defaults).await;
+        status.conditions.extend(conditions);
+    }
+
+    let refused = status.conditions.iter().any(|c| {
+        matches!(
+            (c.type_.as_str(), c.status.as_str()),
+            (loops::CONDITION_LOOP_DETECTED, "True")
+                | (target::CONDITION_TARGET_DENIED, "True")
+                | (CONDITION_TARGET_VALID, "False")
+        )
+    });
+
+    let mut conflicts = Vec::new();
+    let spec_applied = redirect.status.as_ref().is_some_and(|s| {
+        s.observed_generation.is_some() && s.observed_generation == redirect.metadata.generation
+    });
+    for backend in BACKENDS
+        .iter()
+        .filter(|b| !ctx.unavailable_kinds.contains(b.kind()))
+    {
+        let rctx = RenderContext {
+            namespace: backend
+                .namespace(&redirect, ctx.ingress_same_namespace)
+                .unwrap_or(&ctx.self_namespace),
+            service_name: &ctx.self_service_name,
+            defaults: &defaults,
+            external_dns: &ctx.external_dns,
+            allow_native: !redirect.spec.paused && !refused,
+            conflicting_hosts: &conflicting_hosts,
         };
+        apply_backend(
+            &ctx,
+            *backend,
+            &redirect,
+            &rctx,
+            spec_applied,
+            &mut status,
+            &mut conflicts,
+        )
+        .await?;
     }
 
-    api.patch_status(
-        &redirect_name,
-        &PatchParams::default(),
-        &Patch::Merge(json!({"status": status})),
-    )
-    .await
-    .map_err(Error::StatusUpdateFailed)?;
+    let ingress_hosts = |status: &RedirectStatus| -> BTreeSet<String> {
+        status
+            .ingresses
+            .iter()
+            .flat_map(|i| i.host_names.iter().cloned())
+            .collect()
+    };
+    if let Some(previous) = &redirect.status {
+        for dropped in ingress_hosts(previous).difference(&ingress_hosts(&status)) {
+            info!("no longer serving {} through an Ingress", dropped);
+        }
+    }
 
-    Ok(Action::requeue(Duration::from_secs(300)))
+    for backend in BACKENDS {
+        backend
+            .finish(&ctx, &redirect, &defaults, &mut status)
+            .await?;
+    }
+    status.conditions.push(if conflicts.is_empty() {
+        condition(
+            CONDITION_APPLY_CONFLICT,
+            false,
+            "Applied",
+            "all objects applied",
+        )
+    } else {
+        condition(
+            CONDITION_APPLY_CONFLICT,
+            true,
+            "ManagedElsewhere",
+            format!(
+                "{} exist and are managed by someone else; label them {}={} or set APPLY_CONFLICT_POLICY",
+                conflicts
+                    .iter()
+                    .map(|(ns, name)| format!("{ns}/{name}"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                gc::OWNER_LABEL,
+                gc::owner_label_value(&redirect),
+            ),
+        )
+    });
+    if ctx.dns_verify && (Ingresses.wanted(&redirect.spec) || shared) {
+        let hosts = host::served_hosts(&redirect.spec)
+            .0
+            .into_iter()
+            .filter(|h| !conflicting_hosts.contains(h))
+            .collect();
+        let (dns_condition, mismatches) = dnsverify::verify(&hosts, &status.addresses).await;
+        ctx.metrics
+            .reconcile
+            .set_dns_mismatches(&redirect, mismatches);
+        status.conditions.push(dns_condition);
+    }
+    if let Some(dns_records) = &ctx.dns_records {
+        let hosts = if Ingresses.wanted(&redirect.spec) || shared {
+            host::served_hosts(&redirect.spec)
+                .0
+                .into_iter()
+                .filter(|h| !conflicting_hosts.contains(h))
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+        let previous = redirect
+            .status
+            .iter()
+            .flat_map(|s| s.dns_records.iter().cloned())
+            .collect();
+        let (managed, records_condition) = dns_records
+            .sync(&redirect, &hosts, &previous, &status.addresses)
+            .await;
+        status.dns_records = managed;
+        status.conditions.extend(records_condition);
+    }
+
+    // refused targets are not to be requested from within the cluster either
+    if let Some(prober) = &ctx.prober
+        && !refused
+        && let Some(probe_condition) = prober.check(&redirect, &ctx.metrics).await
+    {
+        status.conditions.push(probe_condition);
+        requeue_after = requeue_after.min(prober.interval);
+    }
+
+    // the web tier answers from the controller's store, which got the Redirect first
+    let propagated = ctx
+        .redirects
+        .get(&ObjectRef::from_obj(&*redirect))
+        .is_some_and(|r| r.metadata.generation == redirect.metadata.generation);
+    status.observed_hosts = host::served_hosts(&redirect.spec)
+        .0
+        .into_iter()
+        .filter(|h| propagated && !conflicting_hosts.contains(h))
+        .collect();
+    let denied = status.conditions.iter().find(|c| {
+        (c.type_ == target::CONDITION_TARGET_DENIED || c.type_ == host::CONDITION_HOST_DENIED)
+            && c.status == "True"
+    });
+    status.conditions.push(if !propagated {
+        condition(
+            CONDITION_SERVING,
+            false,
+            "NotPropagated",
+            "the web tier does not know this generation yet",
+        )
+    } else if redirect.spec.paused {
+        condition(CONDITION_SERVING, false, "Paused", "answering as paused")
+    } else if let Some(denied) = denied {
+        condition(CONDITION_SERVING, false, "Refused", &denied.message)
+    } else if status.observed_hosts.is_empty() {
+        condition(CONDITION_SERVING, false, "NoHosts", "no host left to serve")
+    } else {
+        condition(
+            CONDITION_SERVING,
+            true,
+            "Serving",
+            format!("serving {}", status.observed_hosts.join(", ")),
+        )
+    });
+    status.conditions.push(ready_condition(&status.conditions));
+    status.observed_generation = redirect.metadata.generation;
+    settle_conditions(
+        &mut status.conditions,
+        redirect
+            .status
+            .as_ref()
+            .map_or(&[], |s| s.conditions.as_slice()),
+        redirect.metadata.generation,
+    );
+
+    let unchanged = redirect.status.as_ref().is_some_and(|previous| {
+        serde_json::to_value(previous).ok() == serde_json::to_value(&status).ok()
+    });
+    // a burst of edits is still being worked through, the reconcile of the latest one writes
+    let superseded = ctx
+        .redirects
+        .get(&ObjectRef::from_obj(&*redirect))
+        .is_some_and(|r| r.metadata.generation > redirect.metadata.generation);
+    if ctx.dry_run {
+        dryrun::log_diff(
+            "Redirect status",
+            &ns,
+            &redirect_name,
+            Some(&json!({ "status": redirect.status })),
+            &json!({ "status": status }),
+        );
+    } else if superseded {
+        info!("not writing the status of a superseded generation");
+    } else if !unchanged {
+        apply_status(&api, &redirect_name, &status).await?;
+    }
+
+    // check back soon on certificates still being issued and addresses still being assigned
+    if status.conditions.iter().any(|c| {
+        (c.type_ == certificate::CONDITION_CERTIFICATE_READY
+            || c.type_ == external_dns::CONDITION_DNS_PUBLISHED
+            || c.type_ == dns::CONDITION_DNS_RECORDS_READY)
+            && c.status != "True"
+    }) {
+        requeue_after = requeue_after.min(Duration::from_secs(30));
+    }
+    // flip TLSReady when a certificate expires without being renewed
+    if let Some(expiry) = &status.certificate_expiry
+        && let Ok(remaining) =
+            Duration::try_from(expiry.0.duration_since(k8s_openapi::jiff::Timestamp::now()))
+        && !remaining.is_zero()
+    {
+        requeue_after = requeue_after.min(remaining + Duration::from_secs(1));
+    }
+
+    Ok(Action::requeue(requeue_after))
 }
 
+/// Fires whenever this replica becomes leader, to reconcile what followers skipped.
+pub fn leadership_acquired(mut leader_state: Receiver<LeaderState>) -> UnboundedReceiver<()> {
+    let (tx, rx) = mpsc::unbounded();
+    tokio::spawn(async move {
+        let mut was_leader = leader_state.borrow_and_update().is_leader();
+        while leader_state.changed().await.is_ok() {
+            let is_leader = leader_state.borrow_and_update().is_leader();
+            if is_leader && !was_leader && tx.unbounded_send(()).is_err() {
+                break;
+            }
+            was_leader = is_leader;
+        }
+    });
+    rx
+}
+
+/// Starts the Redirect and RedirectGenerator controllers, the shared Ingress sync, the
+/// orphaned Ingress sweep and the member cluster sync.
+///
+/// They shut down gracefully once `shutdown` fires or its sender is dropped.
 pub async fn get_controller(
     client: Client,
     leader_state: Receiver<LeaderState>,
-) -> anyhow::Result<(Store<Redirect>, Arc<Metrics>, JoinHandle<()>)> {
-    let ctx = Context::from_env_with_leader_state(client, leader_state).await?;
-    let controller_config = Config::default().concurrency(2);
+    reconcile_all: resync::ReconcileAll,
+    shutdown: oneshot::Receiver<()>,
+) -> anyhow::Result<(
+    Store<Redirect>,
+    host::HostIndex,
+    Arc<Metrics>,
+    PathMaps,
+    Arc<target::TargetPolicy>,
+    hostpolicy::HostPolicies,
+    JoinHandle<()>,
+)> {
+    let (redirects, redirect_writer) = reflector::store();
+    let ctx =
+        Context::from_env_with_leader_state(client, leader_state, reconcile_all, redirects.clone())
+            .await?;
 
-    let controller = Controller::new(ctx.api.clone(), watcher::Config::default())
-        // cannot own across namespaces
-        // .owns(ctx.ingress_api.clone(), watcher::Config::default())
-        .with_config(controller_config)
-        // .reconcile_all_on(reload_rx.map(|_| (())))
-        .shutdown_on_signal();
+    export_leadership(ctx.leader_state.clone(), ctx.metrics.clone());
+
+    let (stop_generators, generators_stopped) = oneshot::channel();
+    let (stop_shared_ingresses, shared_ingresses_stopped) = oneshot::channel();
+    let (stop_gc, gc_stopped) = oneshot::channel();
+    let (stop_multicluster, multicluster_stopped) = oneshot::channel();
+    // several operator instances can partition the Redirects by label
+    let redirect_config = match &ctx.redirect_selector {
+        Some(selector) => watcher::Config::default().labels(selector),
+        None => watcher::Config::default(),
+    };
+    let controller = ctx.controller_with_store(
+        (redirects, redirect_writer),
+        track_hosts(ctx.hosts.clone(), ctx.watch::<Redirect>(redirect_config)),
+    );
+    let redirects = controller.store();
+    let controller = controller
+        .watches_stream(
+            ctx.watch::<ConfigMap>(watcher::Config::default().labels(CONFIG_MAP_LABEL))
+                .touched_objects(),
+            move |config_map| {
+                let ns = config_map.namespace();
+                let name = config_map.name_any();
+                redirects
+                    .state()
+                    .into_iter()
+                    .filter(|r| r.namespace() == ns && r.spec.config_map_names().any(|n| n == name))
+                    .map(|r| ObjectRef::from_obj(&*r))
+                    .collect::<Vec<_>>()
+            },
+        )
+        .with_config(ctx.controller_config.clone())
+        .reconcile_all_on(leadership_acquired(ctx.leader_state.clone()))
+        .reconcile_all_on(ctx.reconcile_all.subscribe())
+        .graceful_shutdown_on(async move {
+            let _ = shutdown.await;
+            let _ = stop_generators.send(());
+            let _ = stop_shared_ingresses.send(());
+            let _ = stop_gc.send(());
+            let _ = stop_multicluster.send(());
+        });
+
+    // repair edited or deleted Ingresses right away instead of on the next requeue;
+    // Ingresses in INGRESS_NAMESPACES are only covered by the requeue
+    let ingress_config = watcher::Config::default().labels(gc::OWNER_LABEL);
+    let ingresses = if ctx.ingress_same_namespace {
+        ctx.watch::<Ingress>(ingress_config)
+    } else {
+        let api = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+        watcher(api, ingress_config).default_backoff().boxed()
+    };
+    let controller =
+        controller.watches_stream(ingresses.touched_objects(), |ingress| gc::owner(&ingress));
+
+    // TLSReady follows issued, renewed and deleted certificates
+    let secret_config = watcher::Config::default().fields(tls::SECRET_FIELD_SELECTOR);
+    let secrets = if ctx.ingress_same_namespace {
+        ctx.watch::<Secret>(secret_config)
+    } else {
+        let api = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+        watcher(api, secret_config).default_backoff().boxed()
+    };
+    let redirects = controller.store();
+    let controller = controller.watches_stream(secrets.touched_objects(), move |secret| {
+        let secret = RedirectStatusTlsSecret {
+            name: secret.name_any(),
+            namespace: secret.namespace().unwrap_or_default(),
+        };
+        redirects
+            .state()
+            .into_iter()
+            .filter(|r| {
+                r.status
+                    .as_ref()
+                    .is_some_and(|s| s.tls_secrets.contains(&secret))
+            })
+            .map(|r| ObjectRef::from_obj(&*r))
+            .collect::<Vec<_>>()
+    });
 
     // r/o store for redirects
     let store = controller.store();
+    let hosts = ctx.hosts.clone();
     let metrics = ctx.metrics.clone();
+    let path_maps = ctx.path_maps.clone();
+    let target_policy = ctx.target_policy.clone();
+    let host_policies = ctx.host_policies.clone();
+    let ctx = Arc::new(ctx);
 
     let future = controller
-        .run(reconcile, error_policy, Arc::new(ctx))
+        .run(reconcile, error_policy, ctx.clone())
         .for_each(|res| async move {
             match res {
                 Ok(o) => info!("reconciled {:?}", o),
                 Err(e) => warn!("reconcile failed: {:?}", e),
             }
         });
+    let generators = generator::run(ctx.clone(), generators_stopped);
+    let shared_ingresses = shared::run(ctx.clone(), shared_ingresses_stopped);
+    let gc = gc::run(ctx.clone(), gc_stopped);
+    let multicluster = multicluster::run(ctx, multicluster_stopped);
+
+    let handle = tokio::spawn(async move {
+        tokio::join!(future, generators, shared_ingresses, gc, multicluster);
+    });
+    Ok((
+        store,
+        hosts,
+        metrics,
+        path_maps,
+        target_policy,
+        host_policies,
+        handle,
+    ))
+}
 
-    Ok((store, metrics, tokio::spawn(future)))
+/// Passes on the Redirect watcher's events, keeping `hosts` up to date.
+fn track_hosts(
+    hosts: host::HostIndex,
+    events: impl Stream<Item = watcher::Result<watcher::Event<Redirect>>> + Send + 'static,
+) -> impl Stream<Item = watcher::Result<watcher::Event<Redirect>>> + Send + 'static {
+    events.inspect(move |event| {
+        if let Ok(event) = event {
+            hosts.apply_watcher_event(event);
+        }
+    })
 }
 
 fn error_policy(
@@ -288,6 +2736,57 @@ fn error_policy(
 ) -> Action {
     ctx.metrics.reconcile.set_failure(&redirect, error);
 
-    // just requeue
-    Action::requeue(Duration::from_secs(1))
+    Action::requeue(ctx.requeue.backoff(&*redirect))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// Compares `render` output against `tests/fixtures/render/*.golden.yaml`.
+    ///
+    /// Run with `UPDATE_GOLDEN=1` to rewrite the golden files.
+    #[test]
+    fn render_matches_golden_files() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/render");
+        let mut checked = 0;
+
+        for entry in fs::read_dir(&fixtures).unwrap() {
+            let path = entry.unwrap().path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".redirect.yaml"))
+            else {
+                continue;
+            };
+
+            let redirect: Redirect =
+                serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            let rendered = render(&redirect, "redirect-operator", "redirect-operator");
+
+            let golden_path = fixtures.join(format!("{name}.golden.yaml"));
+            if env::var_os("UPDATE_GOLDEN").is_some() {
+                let docs: Vec<String> = rendered
+                    .iter()
+                    .map(|o| serde_yaml::to_string(o).unwrap())
+                    .collect();
+                fs::write(&golden_path, docs.join("---\n")).unwrap();
+            }
+
+            let golden: Vec<serde_json::Value> =
+                serde_yaml::Deserializer::from_str(&fs::read_to_string(&golden_path).unwrap())
+                    .map(|doc| serde_json::Value::deserialize(doc).unwrap())
+                    .filter(|doc| !doc.is_null())
+                    .collect();
+            assert_eq!(rendered, golden, "golden file mismatch for {name}");
+            checked += 1;
+        }
+
+        assert!(checked > 0, "no fixtures found");
+    }
 }
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{
+    Api, Client, ResourceExt,
+    api::{Patch, PatchParams},
+    runtime::wait::{await_condition, conditions},
+};
+use tracing::info;
+
+use crate::{
+    controller::{self, REDIRECT_KUBE_SLUG},
+    types,
+};
+
+/// How long to wait for each CRD to be established.
+const ESTABLISH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether to install the CRDs on startup, from `INSTALL_CRDS`.
+///
+/// Off by default, CRDs are often managed with the rest of the cluster's manifests.
+pub fn enabled() -> bool {
+    std::env::var("INSTALL_CRDS").is_ok_and(|v| v == "true")
+}
+
+/// Server-side applies the operator's CRDs and waits until they are established.
+pub async fn install(client: Client) -> anyhow::Result<()> {
+    let api: Api<CustomResourceDefinition> = Api::all(client);
+    let crds = types::crds(
+        &controller::self_namespace(),
+        &controller::self_service_name(),
+    );
+    for crd in crds {
+        let name = crd.name_any();
+        info!("installing CRD {}", name);
+        api.patch(
+            &name,
+            &PatchParams::apply(REDIRECT_KUBE_SLUG).force(),
+            &Patch::Apply(&crd),
+        )
+        .await?;
+
+        let established = await_condition(api.clone(), &name, conditions::is_crd_established());
+        tokio::time::timeout(ESTABLISH_TIMEOUT, established)
+            .await
+            .map_err(|_| anyhow::anyhow!("CRD {name} was not established in time"))??;
+    }
+    Ok(())
+}
@@ -1,9 +1,14 @@
 mod types;
 
-use kube::CustomResourceExt;
+/// Prints the CRDs, usage: `crdgen [NAMESPACE [SERVICE]]`.
+///
+/// The conversion webhook is the Service `SERVICE` in `NAMESPACE`, both default to
+/// `redirect-operator`.
 fn main() {
-    print!(
-        "{}",
-        serde_yaml::to_string(&types::Redirect::crd()).unwrap()
-    )
+    let mut args = std::env::args().skip(1);
+    let namespace = args.next().unwrap_or("redirect-operator".to_string());
+    let service_name = args.next().unwrap_or("redirect-operator".to_string());
+    for crd in types::crds(&namespace, &service_name) {
+        print!("---\n{}", serde_yaml::to_string(&crd).unwrap())
+    }
 }
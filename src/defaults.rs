@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use anyhow::Context as _;
+use k8s_openapi::api::core::v1::Namespace;
+use kube::{Api, Client, ResourceExt};
+
+use crate::types::{Error, IngressOffload, RedirectIngress};
+
+/// Namespace annotation naming the cert-manager ClusterIssuer for Redirects in it.
+pub const NAMESPACE_TLS_ISSUER_ANNOTATION: &str = "redirect.kube.ibotty.net/default-tls-issuer";
+
+/// Namespace annotation with the ingress class for Redirects in it.
+pub const NAMESPACE_INGRESS_CLASS_ANNOTATION: &str =
+    "redirect.kube.ibotty.net/default-ingress-class";
+
+pub const CERT_MANAGER_CLUSTER_ISSUER_ANNOTATION: &str = "cert-manager.io/cluster-issuer";
+pub const CERT_MANAGER_ISSUER_ANNOTATION: &str = "cert-manager.io/issuer";
+
+/// Defaults of the operator for all Redirects, namespace defaults win over them.
+#[derive(Debug, Default, Clone)]
+pub struct OperatorDefaults {
+    pub ingress_class: Option<String>,
+    pub ingress_annotations: BTreeMap<String, String>,
+    pub ingress_labels: BTreeMap<String, String>,
+    pub ingress_offload: Option<IngressOffload>,
+    /// the copy of `WILDCARD_TLS_SECRET`, for Ingresses without their own certificate
+    pub tls_secret_name: Option<String>,
+}
+
+impl OperatorDefaults {
+    /// Reads `DEFAULT_INGRESS_CLASS`, `DEFAULT_INGRESS_ANNOTATIONS` and
+    /// `DEFAULT_INGRESS_LABELS` as YAML or JSON maps, and `INGRESS_OFFLOAD`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let map = |var: &str| -> anyhow::Result<BTreeMap<String, String>> {
+            match env::var(var) {
+                Ok(value) if !value.trim().is_empty() => {
+                    serde_yaml::from_str(&value).with_context(|| format!("invalid {var}"))
+                }
+                _ => Ok(BTreeMap::new()),
+            }
+        };
+        Ok(Self {
+            ingress_class: env::var("DEFAULT_INGRESS_CLASS")
+                .ok()
+                .filter(|c| !c.is_empty()),
+            ingress_annotations: map("DEFAULT_INGRESS_ANNOTATIONS")?,
+            ingress_labels: map("DEFAULT_INGRESS_LABELS")?,
+            ingress_offload: match env::var("INGRESS_OFFLOAD") {
+                Ok(offload) if !offload.is_empty() => {
+                    Some(serde_yaml::from_str(&offload).context("invalid INGRESS_OFFLOAD")?)
+                }
+                _ => None,
+            },
+            tls_secret_name: None,
+        })
+    }
+}
+
+/// Defaults cluster admins set on a namespace for the Redirects created there.
+#[derive(Debug, Default, Clone)]
+pub struct NamespaceDefaults {
+    pub tls_issuer: Option<String>,
+    pub ingress_class: Option<String>,
+    /// from the operator, added to the Redirects' own
+    pub ingress_annotations: BTreeMap<String, String>,
+    /// from the operator, added to the Redirects' own
+    pub ingress_labels: BTreeMap<String, String>,
+    /// from the operator
+    pub ingress_offload: Option<IngressOffload>,
+    /// from the operator
+    pub tls_secret_name: Option<String>,
+}
+
+impl NamespaceDefaults {
+    pub fn from_namespace(namespace: &Namespace) -> Self {
+        let annotation = |key: &str| {
+            namespace
+                .annotations()
+                .get(key)
+                .filter(|v| !v.is_empty())
+                .cloned()
+        };
+        Self {
+            tls_issuer: annotation(NAMESPACE_TLS_ISSUER_ANNOTATION),
+            ingress_class: annotation(NAMESPACE_INGRESS_CLASS_ANNOTATION),
+            ..Self::default()
+        }
+    }
+
+    /// Adds the operator's defaults below the namespace's.
+    pub fn with_operator_defaults(mut self, operator: &OperatorDefaults) -> Self {
+        if self.ingress_class.is_none() {
+            self.ingress_class = operator.ingress_class.clone();
+        }
+        self.ingress_annotations = operator.ingress_annotations.clone();
+        self.ingress_labels = operator.ingress_labels.clone();
+        self.ingress_offload = operator.ingress_offload.clone();
+        self.tls_secret_name = operator.tls_secret_name.clone();
+        self
+    }
+
+    /// Reads the defaults of namespace `ns`, none if it does not exist.
+    pub async fn fetch(client: Client, ns: &str) -> Result<Self, Error> {
+        let api: Api<Namespace> = Api::all(client);
+        let namespace = api.get_opt(ns).await.map_err(Error::NamespaceFetchFailed)?;
+        Ok(namespace
+            .as_ref()
+            .map(Self::from_namespace)
+            .unwrap_or_default())
+    }
+
+    /// The Ingress settings with the defaults filled in, explicit settings win.
+    pub fn apply(&self, ingress: &RedirectIngress) -> RedirectIngress {
+        let mut ingress = ingress.clone();
+        if ingress.ingress_class_name.is_none() {
+            ingress.ingress_class_name = self.ingress_class.clone();
+        }
+        if ingress.offload.is_none() {
+            ingress.offload = self.ingress_offload.clone();
+        }
+        for (key, value) in &self.ingress_annotations {
+            ingress
+                .annotations
+                .get_or_insert_default()
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        for (key, value) in &self.ingress_labels {
+            ingress
+                .labels
+                .get_or_insert_default()
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        // an issuer, of the Redirect or the namespace, wins over the shared certificate
+        let tls = &mut ingress.tls;
+        if let Some(secret_name) = self.tls_secret_name.as_ref().filter(|_| {
+            self.tls_issuer.is_none()
+                && tls.enabled
+                && tls.secret_name.is_none()
+                && tls.issuer_ref.is_none()
+                && tls.cluster_issuer.is_none()
+                && tls.issuer.is_none()
+        }) {
+            tls.secret_name = Some(secret_name.clone());
+        }
+        // a Certificate managed by the operator needs no ingress-shim, an explicit issuer wins
+        let tls = &ingress.tls;
+        if let Some(issuer) = self.tls_issuer.as_ref().filter(|_| {
+            tls.enabled
+                && tls.issuer_ref.is_none()
+                && tls.cluster_issuer.is_none()
+                && tls.issuer.is_none()
+        }) {
+            ingress
+                .annotations
+                .get_or_insert_default()
+                .entry(CERT_MANAGER_CLUSTER_ISSUER_ANNOTATION.to_string())
+                .or_insert_with(|| issuer.clone());
+        }
+        ingress
+    }
+}
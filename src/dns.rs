@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+use futures::future::BoxFuture;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::Client;
+use tracing::{info, warn};
+
+use crate::{
+    cloudflare::Cloudflare, controller::condition, dnsverify, gc, route53::Route53, types::Redirect,
+};
+
+/// Condition type reporting whether the hosts' DNS records exist and have propagated.
+pub const CONDITION_DNS_RECORDS_READY: &str = "DNSRecordsReady";
+
+/// A DNS record a host should have, type and content.
+pub type Record = (&'static str, String);
+
+/// A DNS service the operator can manage the hosts' address records with.
+pub trait DnsProvider: Send + Sync {
+    /// the provider's name, for logs and conditions
+    fn name(&self) -> &'static str;
+
+    /// Whether the records resolve to the provider instead of the load balancers.
+    fn proxied(&self) -> bool {
+        false
+    }
+
+    /// The zone `host` is in, none if the provider does not manage it.
+    fn zone<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Option<String>, String>>;
+
+    /// Makes the address records of `host` match `wanted`, deleting them if it is empty.
+    ///
+    /// The records are marked with `tag`, records marked otherwise or not at all are never
+    /// changed.
+    fn sync_host<'a>(
+        &'a self,
+        zone: &'a str,
+        host: &'a str,
+        tag: &'a str,
+        wanted: &'a [Record],
+    ) -> BoxFuture<'a, Result<(), String>>;
+}
+
+/// DNS records for the served hosts, pointing at the Ingresses' load balancers.
+pub struct DnsRecords {
+    provider: Box<dyn DnsProvider>,
+}
+
+impl DnsRecords {
+    /// The configured provider, see [`Cloudflare::from_env`] and [`Route53::from_env`].
+    pub async fn from_env(
+        client: Client,
+        namespace: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<Self>> {
+        let cloudflare = Cloudflare::from_env(client.clone(), namespace, dry_run)?;
+        let route53 = Route53::from_env(client, namespace, dry_run).await?;
+        let provider: Box<dyn DnsProvider> = match (cloudflare, route53) {
+            (Some(_), Some(_)) => anyhow::bail!("configure either Cloudflare or Route53"),
+            (Some(cloudflare), None) => Box::new(cloudflare),
+            (None, Some(route53)) => Box::new(route53),
+            (None, None) => return Ok(None),
+        };
+        info!("managing DNS records with {}", provider.name());
+        Ok(Some(Self { provider }))
+    }
+
+    /// Points the records of `hosts` at `addresses` and deletes those of the `previous` hosts
+    /// no longer served.
+    ///
+    /// Returns the hosts with records and the `DNSRecordsReady` condition, none if no host is
+    /// in a zone of the provider.
+    pub async fn sync(
+        &self,
+        redirect: &Redirect,
+        hosts: &BTreeSet<String>,
+        previous: &BTreeSet<String>,
+        addresses: &[String],
+    ) -> (Vec<String>, Option<Condition>) {
+        let tag = tag(redirect);
+        let wanted = records(addresses);
+
+        let mut managed = BTreeSet::new();
+        let mut errors = Vec::new();
+        for host in previous.difference(hosts) {
+            let result = match self.provider.zone(host).await {
+                Ok(Some(zone)) => self.provider.sync_host(&zone, host, &tag, &[]).await,
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                // retried with the next reconcile
+                managed.insert(host.clone());
+                errors.push(e);
+            }
+        }
+        let mut zoned = BTreeSet::new();
+        for host in hosts {
+            let zone = match self.provider.zone(host).await {
+                Ok(Some(zone)) => zone,
+                Ok(None) => continue,
+                Err(e) => {
+                    // it might have records
+                    if previous.contains(host) {
+                        managed.insert(host.clone());
+                    }
+                    errors.push(e);
+                    continue;
+                }
+            };
+            managed.insert(host.clone());
+            zoned.insert(host.clone());
+            // records pointing nowhere would be worse than old ones
+            if wanted.is_empty() {
+                continue;
+            }
+            if let Err(e) = self.provider.sync_host(&zone, host, &tag, &wanted).await {
+                errors.push(e);
+            }
+        }
+
+        let managed = managed.into_iter().collect();
+        if !errors.is_empty() {
+            warn!(
+                "syncing {} DNS records failed: {}",
+                self.provider.name(),
+                errors.join("; ")
+            );
+            return (
+                managed,
+                Some(condition(
+                    CONDITION_DNS_RECORDS_READY,
+                    false,
+                    "SyncFailed",
+                    errors.join("; "),
+                )),
+            );
+        }
+        if zoned.is_empty() {
+            return (managed, None);
+        }
+        if wanted.is_empty() {
+            return (
+                managed,
+                Some(condition(
+                    CONDITION_DNS_RECORDS_READY,
+                    false,
+                    "NoAddress",
+                    "the load balancers have no address yet",
+                )),
+            );
+        }
+        let condition = if self.provider.proxied() {
+            condition(
+                CONDITION_DNS_RECORDS_READY,
+                true,
+                "Proxied",
+                format!(
+                    "the records exist and are proxied by {}",
+                    self.provider.name()
+                ),
+            )
+        } else {
+            match dnsverify::verify(&zoned, addresses).await {
+                (_, 0) => condition(
+                    CONDITION_DNS_RECORDS_READY,
+                    true,
+                    "Propagated",
+                    format!("{} resolve to the load balancers", join(&zoned)),
+                ),
+                (resolved, _) => condition(
+                    CONDITION_DNS_RECORDS_READY,
+                    false,
+                    "Propagating",
+                    resolved.message,
+                ),
+            }
+        };
+        (managed, Some(condition))
+    }
+
+    /// Deletes the records of `hosts`, e.g. of a deleted Redirect.
+    pub async fn delete(
+        &self,
+        redirect: &Redirect,
+        hosts: &BTreeSet<String>,
+    ) -> Result<(), String> {
+        let tag = tag(redirect);
+        for host in hosts {
+            if let Some(zone) = self.provider.zone(host).await? {
+                self.provider.sync_host(&zone, host, &tag, &[]).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The mark of the Redirect's records.
+fn tag(redirect: &Redirect) -> String {
+    format!(
+        "managed by redirect-operator for {}",
+        gc::owner_label_value(redirect)
+    )
+}
+
+/// A/AAAA records for IP addresses, a CNAME for the first host name otherwise.
+fn records(addresses: &[String]) -> Vec<Record> {
+    let ips: Vec<Record> = addresses
+        .iter()
+        .filter_map(|a| a.parse::<IpAddr>().ok())
+        .map(|ip| (if ip.is_ipv4() { "A" } else { "AAAA" }, ip.to_string()))
+        .collect();
+    if !ips.is_empty() {
+        return ips;
+    }
+    addresses
+        .first()
+        .map(|name| vec![("CNAME", name.clone())])
+        .unwrap_or_default()
+}
+
+fn join(hosts: &BTreeSet<String>) -> String {
+    hosts.iter().cloned().collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,88 @@
+use std::{collections::BTreeSet, net::IpAddr};
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use tokio::net::lookup_host;
+use tracing::debug;
+
+use crate::controller::condition;
+
+/// Condition type reporting whether the hosts resolve to the Ingresses' load balancers.
+pub const CONDITION_DNS_CONFIGURED: &str = "DNSConfigured";
+
+/// Whether to resolve the served hosts on every reconcile, from `DNS_VERIFY`.
+pub fn enabled() -> bool {
+    std::env::var("DNS_VERIFY").is_ok_and(|v| v == "true")
+}
+
+/// The IPs `name` resolves to, literal IPs resolve to themselves.
+async fn resolve(name: &str) -> Result<BTreeSet<IpAddr>, String> {
+    if let Ok(ip) = name.parse() {
+        return Ok(BTreeSet::from([ip]));
+    }
+    // the port is only there to satisfy getaddrinfo
+    lookup_host((name, 80))
+        .await
+        .map(|addrs| addrs.map(|a| a.ip()).collect())
+        .map_err(|e| e.to_string())
+}
+
+/// Resolves `hosts` and compares them with the load balancer `addresses`.
+///
+/// Returns the `DNSConfigured` condition and how many hosts do not point at the load
+/// balancers. Wildcard hosts cannot be resolved and are skipped.
+pub async fn verify(hosts: &BTreeSet<String>, addresses: &[String]) -> (Condition, usize) {
+    if addresses.is_empty() {
+        return (
+            condition(
+                CONDITION_DNS_CONFIGURED,
+                false,
+                "NoAddress",
+                "the load balancers have no address yet",
+            ),
+            0,
+        );
+    }
+    let mut expected = BTreeSet::new();
+    for address in addresses {
+        match resolve(address).await {
+            Ok(ips) => expected.extend(ips),
+            Err(e) => debug!("cannot resolve load balancer {}: {}", address, e),
+        }
+    }
+
+    let mut mismatches = Vec::new();
+    for host in hosts.iter().filter(|h| !h.starts_with("*.")) {
+        match resolve(host).await {
+            Ok(ips) if !ips.is_empty() && ips.is_subset(&expected) => {}
+            Ok(ips) => mismatches.push(format!(
+                "{host} resolves to {}",
+                ips.iter()
+                    .map(IpAddr::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Err(e) => mismatches.push(format!("{host} does not resolve: {e}")),
+        }
+    }
+
+    let condition = if mismatches.is_empty() {
+        condition(
+            CONDITION_DNS_CONFIGURED,
+            true,
+            "Configured",
+            format!("all hosts resolve to {}", addresses.join(", ")),
+        )
+    } else {
+        condition(
+            CONDITION_DNS_CONFIGURED,
+            false,
+            "DNSMismatch",
+            format!(
+                "expected {}, but {}",
+                addresses.join(", "),
+                mismatches.join("; ")
+            ),
+        )
+    };
+    (condition, mismatches.len())
+}
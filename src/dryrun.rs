@@ -0,0 +1,109 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::info;
+
+/// Command line flag enabling dry-run mode, like `DRY_RUN=true`.
+pub const FLAG: &str = "--dry-run";
+
+/// Metadata the API server maintains, it differs between any two versions of an object.
+const VOLATILE_METADATA: [&str; 5] = [
+    "creationTimestamp",
+    "generation",
+    "managedFields",
+    "resourceVersion",
+    "uid",
+];
+
+/// Whether to compute and log changes instead of writing them, from `DRY_RUN` or `--dry-run`.
+pub fn enabled() -> bool {
+    std::env::var("DRY_RUN").is_ok_and(|v| v == "true") || std::env::args().any(|a| a == FLAG)
+}
+
+/// `object` without its status and the metadata the API server maintains.
+pub fn comparable(object: &impl Serialize) -> Value {
+    let mut value = serde_json::to_value(object).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        object.remove("status");
+        if let Some(metadata) = object.get_mut("metadata").and_then(Value::as_object_mut) {
+            for field in VOLATILE_METADATA {
+                metadata.remove(field);
+            }
+        }
+    }
+    value
+}
+
+/// Logs every field that differs between `before` and `after`, one line each.
+///
+/// `before` is `None` for objects that would be created.
+pub fn log_diff(kind: &str, namespace: &str, name: &str, before: Option<&Value>, after: &Value) {
+    let Some(before) = before else {
+        info!(
+            kind,
+            namespace,
+            name,
+            object = %after,
+            "dry run: would create {} {}/{}",
+            kind,
+            namespace,
+            name
+        );
+        return;
+    };
+    let mut changes = Vec::new();
+    diff(String::new(), Some(before), Some(after), &mut changes);
+    if changes.is_empty() {
+        info!(
+            kind,
+            namespace, name, "dry run: {} {}/{} is up to date", kind, namespace, name
+        );
+    }
+    for (field, before, after) in changes {
+        info!(
+            kind,
+            namespace,
+            name,
+            field = %field,
+            before = %before.unwrap_or(&Value::Null),
+            after = %after.unwrap_or(&Value::Null),
+            "dry run: would change {} {}/{}",
+            kind,
+            namespace,
+            name
+        );
+    }
+}
+
+/// Logs a delete that was not made.
+pub fn log_delete(kind: &str, namespace: &str, name: &str) {
+    info!(
+        kind,
+        namespace, name, "dry run: would delete {} {}/{}", kind, namespace, name
+    );
+}
+
+/// Collects the paths of differing fields, descending into objects but not into arrays.
+fn diff<'a>(
+    path: String,
+    before: Option<&'a Value>,
+    after: Option<&'a Value>,
+    changes: &mut Vec<(String, Option<&'a Value>, Option<&'a Value>)>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for key in keys {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff(field, before.get(key), after.get(key), changes);
+            }
+        }
+        (before, after) if before == after => {}
+        (before, after) => changes.push((path, before, after)),
+    }
+}
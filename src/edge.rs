@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use kube::{ResourceExt, runtime::reflector::Store};
+use serde::Serialize;
+
+use crate::{
+    host,
+    pathmap::PathMaps,
+    shortlink,
+    types::{Redirect, RedirectLink, RedirectMatch, RedirectMode, RedirectSplit},
+};
+
+/// The redirect logic of one Redirect, for edge workers to mirror.
+#[derive(Debug, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeRule {
+    /// namespace/name of the Redirect
+    pub redirect: String,
+    pub hosts: Vec<String>,
+    pub to: String,
+    /// redirect to this host keeping scheme, path and query, instead of `to`
+    pub canonical_host: Option<String>,
+    pub include_request_uri: bool,
+    /// query parameters added to the target
+    pub append_params: BTreeMap<String, String>,
+    /// forward requests to the target instead of redirecting
+    pub proxy: bool,
+    /// serve this page instead of redirecting
+    pub page: Option<EdgePage>,
+    pub status: u16,
+    /// short code → target
+    pub short_links: BTreeMap<String, String>,
+    /// exact request path → target, an empty target means the path is gone
+    pub paths: BTreeMap<String, String>,
+    /// host → target for hosts not using `to`
+    pub overrides: BTreeMap<String, EdgeTarget>,
+    /// request conditions, rules with conditions take precedence for their hosts
+    #[serde(rename = "match")]
+    pub match_: RedirectMatch,
+    /// weighted targets replacing `to`
+    pub split: Option<RedirectSplit>,
+    /// `Link` headers, links without URI refer to the target
+    pub links: Vec<RedirectLink>,
+}
+
+#[derive(Debug, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeTarget {
+    pub to: String,
+    pub include_request_uri: bool,
+    pub append_params: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgePage {
+    pub status: u16,
+    pub html: String,
+}
+
+#[derive(Debug, Serialize, Hash)]
+pub struct EdgeRules {
+    pub rules: Vec<EdgeRule>,
+}
+
+impl EdgeRules {
+    /// Collects the rules of all Redirects, or only the one serving `host`.
+    pub fn collect(store: &Store<Redirect>, path_maps: &PathMaps, host: Option<&str>) -> Self {
+        Self::from_redirects(store.state(), path_maps, host)
+    }
+
+    /// The rules of `redirects`, or only of the one serving `host`.
+    pub fn from_redirects(
+        mut redirects: Vec<Arc<Redirect>>,
+        path_maps: &PathMaps,
+        host: Option<&str>,
+    ) -> Self {
+        redirects.sort_by_key(|r| (r.namespace(), r.name_any()));
+
+        let rules = redirects
+            .iter()
+            .filter_map(|redirect| {
+                let (hosts, _) = host::served_hosts(&redirect.spec);
+                if host.is_some_and(|host| !hosts.contains(host)) {
+                    return None;
+                }
+
+                let short_links = shortlink::assign_codes(redirect)
+                    .into_iter()
+                    .map(|l| (l.code, l.to))
+                    .collect();
+                let paths = path_maps
+                    .table_for(redirect)
+                    .map(|table| table.iter().map(|(p, t)| (p.clone(), t.clone())).collect())
+                    .unwrap_or_default();
+
+                let overrides = redirect
+                    .spec
+                    .overrides
+                    .iter()
+                    .filter_map(|o| {
+                        let host = host::normalize(&o.host).ok()?;
+                        let target = EdgeTarget {
+                            to: o.to.uri.clone(),
+                            include_request_uri: o.to.include_request_uri,
+                            append_params: o.to.append_params.clone(),
+                        };
+                        hosts.contains(&host).then_some((host, target))
+                    })
+                    .collect();
+
+                Some(EdgeRule {
+                    redirect: format!(
+                        "{}/{}",
+                        redirect.namespace().unwrap_or_default(),
+                        redirect.name_any()
+                    ),
+                    hosts: hosts.into_iter().collect(),
+                    to: redirect.spec.to.uri.clone(),
+                    canonical_host: host::canonical_host(&redirect.spec),
+                    include_request_uri: redirect.spec.to.include_request_uri,
+                    append_params: redirect.spec.to.append_params.clone(),
+                    proxy: redirect.spec.mode == RedirectMode::Proxy,
+                    page: (redirect.spec.mode == RedirectMode::Page)
+                        .then(|| path_maps.page(redirect))
+                        .flatten()
+                        .map(|(status, html)| EdgePage {
+                            status: status.as_u16(),
+                            html,
+                        }),
+                    status: 308,
+                    short_links,
+                    paths,
+                    overrides,
+                    match_: redirect.spec.match_.clone(),
+                    split: redirect.spec.split.clone(),
+                    links: redirect.spec.links.clone(),
+                })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// A strong ETag derived from the rule set.
+    pub fn etag(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        format!("\"{:016x}\"", hasher.finish())
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::{
+    ResourceExt,
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    controller::{
+        NetworkingBackend, REDIRECT_SERVICE_PORT, RenderContext, condition,
+        ingress_name_for_redirect,
+    },
+    host,
+    offload::{self, EdgeTarget},
+    route,
+    types::{
+        Error, PathMatchType, Redirect, RedirectSpec, RedirectStatus, RedirectStatusHostObject,
+    },
+};
+
+/// Condition type reporting whether the Mappings are applied, and how they answer.
+pub const CONDITION_MAPPING_READY: &str = "MappingReady";
+
+/// Whether Redirects not saying get Mappings, from `EMISSARY_MAPPINGS`.
+static ENABLED_BY_DEFAULT: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("EMISSARY_MAPPINGS").is_ok_and(|v| v == "true"));
+
+/// The Emissary `getambassador.io/v3alpha1` Mapping API.
+pub fn api_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk(
+        "getambassador.io",
+        "v3alpha1",
+        "Mapping",
+    ))
+}
+
+/// The Mapping fields redirecting like a Redirect, or why there are none.
+///
+/// Emissary keeps the request's scheme, so only `https` targets qualify.
+fn native_redirect(spec: &RedirectSpec) -> Result<Value, String> {
+    Ok(match offload::edge_target(spec)? {
+        EdgeTarget::CanonicalHost(canonical) => json!({
+            "host_redirect": true,
+            "service": canonical,
+            "redirect_response_code": 308,
+        }),
+        EdgeTarget::Uri {
+            scheme,
+            authority,
+            path,
+        } => {
+            if scheme != "https" {
+                return Err(format!("Emissary cannot redirect to {scheme}"));
+            }
+            let mut redirect = json!({
+                "host_redirect": true,
+                "service": authority,
+                "redirect_response_code": 308,
+            });
+            if let Some(path) = path {
+                redirect["path_redirect"] = json!(path);
+            }
+            redirect
+        }
+    })
+}
+
+/// Mapping prefixes for `match.paths`, everything if there are none.
+fn prefixes(spec: &RedirectSpec) -> Vec<Value> {
+    if spec.match_.paths.is_empty() {
+        return vec![json!({ "prefix": "/" })];
+    }
+    spec.match_
+        .paths
+        .iter()
+        .map(|p| match p.path_type {
+            PathMatchType::Prefix => json!({ "prefix": p.path }),
+            PathMatchType::Exact => json!({ "prefix": p.path, "prefix_exact": true }),
+            PathMatchType::Regex => json!({ "prefix": p.path, "prefix_regex": true }),
+        })
+        .collect()
+}
+
+/// The Mappings serving a Redirect, one per host and path, with the condition describing them.
+///
+/// Hosts conflicting with older Redirects are left out. `allow_native` is false if the
+/// operator has to see requests regardless of the spec, e.g. to refuse loops.
+pub fn mappings_for_redirect(
+    rctx: &RenderContext,
+    redirect: &Redirect,
+) -> (Vec<DynamicObject>, Condition) {
+    let settings = &redirect.spec.mapping;
+    let route_to_operator = json!({
+        "service": format!("{}.{}:{REDIRECT_SERVICE_PORT}", rctx.service_name, rctx.namespace),
+        // the operator needs the full path
+        "rewrite": "",
+    });
+    let (target, ready) = match (settings.native, rctx.allow_native) {
+        (false, _) => (
+            route_to_operator,
+            condition(
+                CONDITION_MAPPING_READY,
+                true,
+                "RoutedToOperator",
+                "requests are routed to the operator",
+            ),
+        ),
+        (true, false) => (
+            route_to_operator,
+            condition(
+                CONDITION_MAPPING_READY,
+                true,
+                "RoutedToOperator",
+                "not redirecting natively while the Redirect is refused",
+            ),
+        ),
+        (true, true) => match native_redirect(&redirect.spec) {
+            Ok(target) => (
+                target,
+                condition(
+                    CONDITION_MAPPING_READY,
+                    true,
+                    "Native",
+                    "requests are redirected by Emissary",
+                ),
+            ),
+            Err(reason) => (
+                route_to_operator,
+                condition(
+                    CONDITION_MAPPING_READY,
+                    true,
+                    "RoutedToOperator",
+                    format!("cannot redirect natively, {reason}"),
+                ),
+            ),
+        },
+    };
+
+    let (hosts, _) = host::served_hosts(&redirect.spec);
+    let prefixes = prefixes(&redirect.spec);
+    let mut mappings = Vec::new();
+    for host in hosts
+        .iter()
+        .filter(|h| !rctx.conflicting_hosts.contains(*h))
+    {
+        let base = format!("{}.{}", ingress_name_for_redirect(redirect), host);
+        for (i, prefix) in prefixes.iter().enumerate() {
+            let name = match prefixes.len() {
+                1 => base.clone(),
+                _ => format!("{base}.{i}"),
+            };
+            let mut spec = Map::new();
+            spec.insert("hostname".to_string(), json!(host));
+            for fields in [prefix, &target].into_iter().filter_map(Value::as_object) {
+                spec.extend(fields.clone());
+            }
+            if !settings.ambassador_id.is_empty() {
+                spec.insert("ambassador_id".to_string(), json!(settings.ambassador_id));
+            }
+
+            let mut mapping = DynamicObject::new(&name, &api_resource())
+                .within(rctx.namespace)
+                .data(json!({ "spec": spec }));
+            mapping.metadata.annotations = settings.annotations.clone();
+            mapping.metadata.labels = settings.labels.clone();
+            mappings.push(mapping);
+        }
+    }
+    (mappings, ready)
+}
+
+/// Emissary Mappings, one per host and path.
+pub struct Mappings;
+
+impl NetworkingBackend for Mappings {
+    fn kind(&self) -> &'static str {
+        "Mapping"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        api_resource()
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        spec.wants_mapping(*ENABLED_BY_DEFAULT)
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let (mappings, ready) = mappings_for_redirect(rctx, redirect);
+        (mappings, vec![ready])
+    }
+
+    fn existing(&self, redirect: &Redirect, _self_namespace: &str) -> BTreeSet<(String, String)> {
+        redirect
+            .status
+            .iter()
+            .flat_map(|status| &status.mappings)
+            .map(|m| (m.namespace.clone(), m.name.clone()))
+            .collect()
+    }
+
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]) {
+        status.mappings = applied
+            .iter()
+            .map(|mapping| RedirectStatusHostObject {
+                name: mapping.name_any(),
+                namespace: namespace.to_string(),
+                host: route::object_host(mapping, "/spec/hostname"),
+            })
+            .collect();
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::MappingCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::MappingDeletionFailed(error)
+    }
+}
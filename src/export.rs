@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use crate::{
+    edge::{EdgeRule, EdgeRules},
+    params,
+};
+
+/// The configuration formats the redirect table can be exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Nginx,
+    Caddy,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nginx" => Ok(Self::Nginx),
+            "caddy" => Ok(Self::Caddy),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown format {s}, expected nginx, caddy or json")),
+        }
+    }
+}
+
+impl Format {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Nginx | Self::Caddy => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// Where requests not matching an exact path go.
+enum Fallback<'a> {
+    /// the same scheme, path and query on this host
+    CanonicalHost(&'a str),
+    Target {
+        to: &'a str,
+        include_request_uri: bool,
+    },
+}
+
+/// Hosts redirecting alike, a server block in nginx and a site block in Caddy.
+struct Site<'a> {
+    hosts: Vec<&'a str>,
+    /// exact request path → target, none if the path is gone
+    paths: BTreeMap<String, Option<&'a str>>,
+    fallback: Fallback<'a>,
+    append_params: &'a BTreeMap<String, String>,
+}
+
+/// Renders the redirect table as configuration for another web server.
+///
+/// nginx and Caddy only get plain redirects, rules proxying, serving pages, splitting traffic
+/// or matching on request conditions are left out with a comment.
+pub fn render(rules: &EdgeRules, format: Format) -> String {
+    if format == Format::Json {
+        let mut out = serde_json::to_string_pretty(rules).unwrap_or_default();
+        out.push('\n');
+        return out;
+    }
+
+    let mut out = String::new();
+    for rule in &rules.rules {
+        let origin = rule.redirect.as_str();
+        if let Some(reason) = unsupported(rule) {
+            let _ = writeln!(out, "# {origin}: not exported, {reason}\n");
+            continue;
+        }
+        if !rule.links.is_empty() {
+            let _ = writeln!(out, "# {origin}: Link headers are not exported");
+        }
+        for site in sites(rule) {
+            match format {
+                Format::Nginx => nginx(&mut out, origin, rule.status, &site),
+                Format::Caddy => caddy(&mut out, origin, rule.status, &site),
+                Format::Json => unreachable!(),
+            }
+        }
+    }
+    out
+}
+
+/// Why a rule cannot be written as plain redirects.
+fn unsupported(rule: &EdgeRule) -> Option<&'static str> {
+    if rule.proxy {
+        Some("it proxies requests")
+    } else if rule.page.is_some() {
+        Some("it serves a page")
+    } else if rule.split.is_some() {
+        Some("it splits traffic")
+    } else if !rule.match_.is_empty() {
+        Some("it matches on request conditions")
+    } else {
+        None
+    }
+}
+
+/// The sites of a rule, one for the hosts using `to` and one per overridden host.
+fn sites(rule: &EdgeRule) -> Vec<Site<'_>> {
+    let site = |hosts, to, include_request_uri, append_params| {
+        // short links win over the path map
+        let mut paths: BTreeMap<String, Option<&str>> = rule
+            .short_links
+            .iter()
+            .map(|(code, to)| (format!("/{code}"), Some(to.as_str())))
+            .collect();
+        for (path, to) in &rule.paths {
+            paths
+                .entry(path.clone())
+                .or_insert(Some(to.as_str()).filter(|to| !to.is_empty()));
+        }
+        // the canonical host wins over all paths
+        let (paths, fallback) = match &rule.canonical_host {
+            Some(canonical) => (BTreeMap::new(), Fallback::CanonicalHost(canonical)),
+            None => (
+                paths,
+                Fallback::Target {
+                    to,
+                    include_request_uri,
+                },
+            ),
+        };
+        Site {
+            hosts,
+            paths,
+            fallback,
+            append_params,
+        }
+    };
+
+    let mut sites = Vec::new();
+    let hosts: Vec<&str> = rule
+        .hosts
+        .iter()
+        .filter(|h| !rule.overrides.contains_key(*h))
+        .map(String::as_str)
+        .collect();
+    if !hosts.is_empty() {
+        sites.push(site(
+            hosts,
+            &rule.to,
+            rule.include_request_uri,
+            &rule.append_params,
+        ));
+    }
+    for (host, target) in &rule.overrides {
+        sites.push(site(
+            vec![host.as_str()],
+            &target.to,
+            target.include_request_uri,
+            &target.append_params,
+        ));
+    }
+    sites
+}
+
+fn nginx(out: &mut String, origin: &str, status: u16, site: &Site) {
+    // nginx would expand variables in targets
+    let escape = |uri: &str| uri.replace('$', "%24");
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+
+    let _ = writeln!(out, "# {origin}");
+    let _ = writeln!(out, "server {{");
+    let _ = writeln!(out, "    listen 80;");
+    let _ = writeln!(out, "    server_name {};", site.hosts.join(" "));
+    for (path, to) in &site.paths {
+        let _ = writeln!(out, "    location = {} {{", quote(path));
+        match to {
+            Some(to) => {
+                let to = params::append(&escape(to), site.append_params);
+                let _ = writeln!(out, "        return {status} {};", quote(&to));
+            }
+            None => {
+                let _ = writeln!(out, "        return 404;");
+            }
+        }
+        let _ = writeln!(out, "    }}");
+    }
+    let fallback = match site.fallback {
+        Fallback::CanonicalHost(canonical) => format!("$scheme://{canonical}$request_uri"),
+        Fallback::Target {
+            to,
+            include_request_uri: true,
+        } => format!("{}$uri", escape(to)),
+        Fallback::Target { to, .. } => escape(to),
+    };
+    let fallback = params::append(&fallback, site.append_params);
+    let _ = writeln!(out, "    location / {{");
+    let _ = writeln!(out, "        return {status} {};", quote(&fallback));
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "}}\n");
+}
+
+fn caddy(out: &mut String, origin: &str, status: u16, site: &Site) {
+    // Caddy would expand placeholders in targets
+    let escape = |uri: &str| uri.replace('{', "\\{").replace('}', "\\}");
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\\\""));
+
+    let _ = writeln!(out, "# {origin}");
+    let _ = writeln!(out, "{} {{", site.hosts.join(", "));
+    let _ = writeln!(out, "\troute {{");
+    for (path, to) in &site.paths {
+        match to {
+            Some(to) => {
+                let to = params::append(&escape(to), site.append_params);
+                let _ = writeln!(out, "\t\tredir {path} {} {status}", quote(&to));
+            }
+            None => {
+                let _ = writeln!(out, "\t\trespond {path} 404");
+            }
+        }
+    }
+    let fallback = match site.fallback {
+        Fallback::CanonicalHost(canonical) => format!("{{scheme}}://{canonical}{{uri}}"),
+        Fallback::Target {
+            to,
+            include_request_uri: true,
+        } => format!("{}{{path}}", escape(to)),
+        Fallback::Target { to, .. } => escape(to),
+    };
+    let fallback = params::append(&fallback, site.append_params);
+    let _ = writeln!(out, "\t\tredir {} {status}", quote(&fallback));
+    let _ = writeln!(out, "\t}}");
+    let _ = writeln!(out, "}}\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule() -> EdgeRule {
+        EdgeRule {
+            redirect: "web/old".to_string(),
+            hosts: vec!["old.example.com".to_string()],
+            to: "https://example.org/$home".to_string(),
+            canonical_host: None,
+            include_request_uri: false,
+            append_params: BTreeMap::new(),
+            proxy: false,
+            page: None,
+            status: 308,
+            short_links: BTreeMap::new(),
+            paths: BTreeMap::from([
+                ("/gone".to_string(), String::new()),
+                ("/shop".to_string(), "https://example.org/store".to_string()),
+            ]),
+            overrides: BTreeMap::new(),
+            match_: Default::default(),
+            split: None,
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!("caddy".parse::<Format>(), Ok(Format::Caddy));
+        assert!("apache".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn renders_nginx_server_blocks() {
+        let out = render(
+            &EdgeRules {
+                rules: vec![rule()],
+            },
+            Format::Nginx,
+        );
+        assert!(out.contains("    server_name old.example.com;\n"));
+        assert!(out.contains(
+            "    location = \"/shop\" {\n        return 308 \"https://example.org/store\";\n    }\n"
+        ));
+        assert!(out.contains("    location = \"/gone\" {\n        return 404;\n    }\n"));
+        // no nginx variables in targets
+        assert!(out.contains("        return 308 \"https://example.org/%24home\";\n"));
+    }
+
+    #[test]
+    fn renders_caddy_site_blocks() {
+        let mut rule = rule();
+        rule.include_request_uri = true;
+        rule.to = "https://example.org/{x}".to_string();
+        let out = render(&EdgeRules { rules: vec![rule] }, Format::Caddy);
+        assert!(out.starts_with("# web/old\nold.example.com {\n\troute {\n"));
+        assert!(out.contains("\t\trespond /gone 404\n"));
+        assert!(out.contains("\t\tredir \"https://example.org/\\{x\\}{path}\" 308\n"));
+    }
+
+    #[test]
+    fn leaves_out_rules_that_are_not_plain_redirects() {
+        let mut rule = rule();
+        rule.proxy = true;
+        let out = render(&EdgeRules { rules: vec![rule] }, Format::Nginx);
+        assert_eq!(out, "# web/old: not exported, it proxies requests\n\n");
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use anyhow::Context as _;
+use k8s_openapi::{api::networking::v1::Ingress, apimachinery::pkg::apis::meta::v1::Condition};
+use kube::api::DynamicObject;
+
+use crate::{controller::condition, types::RedirectExternalDns};
+
+pub const HOSTNAME_ANNOTATION: &str = "external-dns.alpha.kubernetes.io/hostname";
+pub const TTL_ANNOTATION: &str = "external-dns.alpha.kubernetes.io/ttl";
+pub const TARGET_ANNOTATION: &str = "external-dns.alpha.kubernetes.io/target";
+
+/// Condition type reporting whether external-dns can publish the records.
+pub const CONDITION_DNS_PUBLISHED: &str = "DnsPublished";
+
+/// Operator-wide external-dns settings, Redirects' own settings win.
+///
+/// Configured with `EXTERNAL_DNS_ENABLED`, `EXTERNAL_DNS_TTL` and `EXTERNAL_DNS_TARGET`.
+#[derive(Debug, Default, Clone)]
+pub struct ExternalDnsDefaults {
+    pub enabled: bool,
+    pub ttl: Option<u32>,
+    pub target: Option<String>,
+}
+
+impl ExternalDnsDefaults {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let ttl = match env::var("EXTERNAL_DNS_TTL") {
+            Ok(ttl) => Some(ttl.parse().context("invalid EXTERNAL_DNS_TTL")?),
+            Err(_) => None,
+        };
+        Ok(Self {
+            enabled: env::var("EXTERNAL_DNS_ENABLED").is_ok_and(|v| v == "true"),
+            ttl,
+            target: env::var("EXTERNAL_DNS_TARGET")
+                .ok()
+                .filter(|t| !t.is_empty()),
+        })
+    }
+
+    /// The settings for a Redirect, `None` if it is not published.
+    pub fn resolve(&self, settings: &RedirectExternalDns) -> Option<ExternalDnsDefaults> {
+        settings.enabled.unwrap_or(self.enabled).then(|| Self {
+            enabled: true,
+            ttl: settings.ttl.or(self.ttl),
+            target: settings.target.clone().or_else(|| self.target.clone()),
+        })
+    }
+
+    /// Adds the external-dns annotations for the hosts of `ingress`.
+    pub fn annotate(&self, ingress: &mut Ingress) {
+        let hosts: Vec<String> = ingress
+            .spec
+            .iter()
+            .flat_map(|spec| spec.rules.iter().flatten())
+            .filter_map(|rule| rule.host.clone())
+            .collect();
+        let annotations = ingress.metadata.annotations.get_or_insert_default();
+        annotations.insert(HOSTNAME_ANNOTATION.to_string(), hosts.join(","));
+        if let Some(ttl) = self.ttl {
+            annotations.insert(TTL_ANNOTATION.to_string(), ttl.to_string());
+        }
+        if let Some(target) = &self.target {
+            annotations.insert(TARGET_ANNOTATION.to_string(), target.clone());
+        }
+    }
+}
+
+/// Whether external-dns has an address to publish for the applied Ingresses.
+///
+/// `None` if none of them is annotated for external-dns.
+pub fn published_condition(ingresses: &[DynamicObject]) -> Option<Condition> {
+    let annotated: Vec<&DynamicObject> = ingresses
+        .iter()
+        .filter(|i| {
+            i.metadata
+                .annotations
+                .as_ref()
+                .is_some_and(|a| a.contains_key(HOSTNAME_ANNOTATION))
+        })
+        .collect();
+    if annotated.is_empty() {
+        return None;
+    }
+
+    let has_address = |ingress: &DynamicObject| {
+        let annotations: &BTreeMap<String, String> = ingress.metadata.annotations.as_ref()?;
+        if annotations.contains_key(TARGET_ANNOTATION) {
+            return Some(());
+        }
+        ingress
+            .data
+            .pointer("/status/loadBalancer/ingress")
+            .and_then(|lb| lb.as_array())
+            .filter(|lb| !lb.is_empty())
+            .map(|_| ())
+    };
+    let waiting: Vec<String> = annotated
+        .iter()
+        .filter(|i| has_address(i).is_none())
+        .filter_map(|i| i.metadata.name.clone())
+        .collect();
+    Some(if waiting.is_empty() {
+        condition(
+            CONDITION_DNS_PUBLISHED,
+            true,
+            "AddressAssigned",
+            "external-dns publishes the hosts",
+        )
+    } else {
+        condition(
+            CONDITION_DNS_PUBLISHED,
+            false,
+            "WaitingForAddress",
+            format!(
+                "no load balancer address on {} yet, external-dns has nothing to publish",
+                waiting.join(", ")
+            ),
+        )
+    })
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use kube::{
+    Api, Resource, ResourceExt,
+    api::{ApiResource, DynamicObject, ListParams},
+    runtime::reflector::ObjectRef,
+};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::{
+    controller::{BACKENDS, Context},
+    dryrun, hash,
+    types::{Error, Redirect},
+};
+
+/// Label on generated objects identifying their Redirect, hashed if `namespace.name` is too long.
+pub const OWNER_LABEL: &str = "redirect.kube.ibotty.net/owner";
+
+/// Annotation on generated objects with the `namespace/name` of their Redirect.
+pub const OWNER_ANNOTATION: &str = "redirect.kube.ibotty.net/owner";
+
+/// Longest label value Kubernetes accepts.
+const MAX_LABEL_VALUE: usize = 63;
+
+/// The owner label value for `redirect`.
+pub fn owner_label_value(redirect: &Redirect) -> String {
+    let value = format!(
+        "{}.{}",
+        redirect.namespace().unwrap_or_default(),
+        redirect.name_any()
+    );
+    if value.len() <= MAX_LABEL_VALUE {
+        value
+    } else {
+        format!("{:016x}", hash::fnv1a(&value))
+    }
+}
+
+/// Labels and annotates a generated object with its Redirect.
+pub fn mark(object: &mut DynamicObject, redirect: &Redirect) {
+    object
+        .labels_mut()
+        .insert(OWNER_LABEL.to_string(), owner_label_value(redirect));
+    object.annotations_mut().insert(
+        OWNER_ANNOTATION.to_string(),
+        format!(
+            "{}/{}",
+            redirect.namespace().unwrap_or_default(),
+            redirect.name_any()
+        ),
+    );
+}
+
+/// The Redirect a generated object belongs to, per its owner annotation.
+pub fn owner<K: Resource>(object: &K) -> Option<ObjectRef<Redirect>> {
+    let (ns, name) = object
+        .annotations()
+        .get(OWNER_ANNOTATION)?
+        .split_once('/')?;
+    Some(ObjectRef::new(name).within(ns))
+}
+
+/// How often to sweep, from `GC_INTERVAL_SECONDS`.
+fn interval() -> anyhow::Result<Duration> {
+    Ok(match std::env::var("GC_INTERVAL_SECONDS") {
+        Ok(seconds) => Duration::from_secs(seconds.parse().context("invalid GC_INTERVAL_SECONDS")?),
+        Err(_) => Duration::from_secs(600),
+    })
+}
+
+/// APIs for `resource` everywhere the operator may have created objects.
+fn apis(ctx: &Context, resource: &ApiResource) -> Vec<Api<DynamicObject>> {
+    if ctx.ingress_namespaces.contains("*")
+        || (ctx.ingress_same_namespace && ctx.watch_namespaces.is_empty())
+    {
+        return vec![Api::all_with(ctx.client.clone(), resource)];
+    }
+    let mut namespaces: BTreeSet<&str> = std::iter::once(ctx.self_namespace.as_str())
+        .chain(ctx.ingress_namespaces.iter().map(String::as_str))
+        .collect();
+    if ctx.ingress_same_namespace {
+        namespaces.extend(ctx.watch_namespaces.iter().map(String::as_str));
+    }
+    namespaces
+        .into_iter()
+        .map(|ns| Api::namespaced_with(ctx.client.clone(), ns, resource))
+        .collect()
+}
+
+/// Deletes generated objects whose Redirect no longer exists, returning how many.
+///
+/// Catches objects left behind when the operator was down while a Redirect was deleted, and
+/// all objects of deleted Redirects when running without finalizers.
+pub async fn sweep(ctx: &Context) -> Result<usize, Error> {
+    let mut pruned = 0;
+    for backend in BACKENDS
+        .iter()
+        .filter(|b| !ctx.unavailable_kinds.contains(b.kind()))
+    {
+        let kind = backend.kind();
+        for api in apis(ctx, &backend.api_resource()) {
+            let objects = api
+                .list_metadata(&ListParams::default().labels(OWNER_LABEL))
+                .await
+                .map_err(Error::ObjectListFailed)?;
+            for object in objects.items {
+                let Some(owner) = owner(&object) else {
+                    continue;
+                };
+                if ctx.redirects.get(&owner).is_some() {
+                    continue;
+                }
+                // the store misses Redirects of other instances with a different label selector
+                if ctx.redirect_selector.is_some() {
+                    let api: Api<Redirect> = Api::namespaced(
+                        ctx.client.clone(),
+                        owner.namespace.as_deref().unwrap_or_default(),
+                    );
+                    match api.get_metadata_opt(&owner.name).await {
+                        Ok(None) => {}
+                        Ok(Some(_)) => continue,
+                        Err(e) => return Err(Error::RedirectFetchFailed(e)),
+                    }
+                }
+
+                // without finalizers, the sweep is the first to notice the deletion
+                ctx.metrics
+                    .reconcile
+                    .forget(owner.namespace.as_deref().unwrap_or_default(), &owner.name);
+
+                let object_ns = object.namespace().unwrap_or_default();
+                info!(
+                    "pruning {} {}/{} of deleted Redirect {}/{}",
+                    kind,
+                    object_ns,
+                    object.name_any(),
+                    owner.namespace.as_deref().unwrap_or_default(),
+                    owner.name
+                );
+                let api: Api<DynamicObject> =
+                    Api::namespaced_with(ctx.client.clone(), &object_ns, &backend.api_resource());
+                if ctx.dry_run {
+                    dryrun::log_delete(kind, &object_ns, &object.name_any());
+                }
+                match api.delete(&object.name_any(), &ctx.delete_params()).await {
+                    Err(kube::Error::Api(response)) if response.code == 404 => {}
+                    Err(e) => return Err(backend.delete_failed(e)),
+                    Ok(_) => {
+                        ctx.metrics.reconcile.set_pruned(kind);
+                        pruned += 1;
+                    }
+                }
+            }
+        }
+    }
+    Ok(pruned)
+}
+
+/// Sweeps periodically while leading, until `shutdown` fires.
+pub async fn run(ctx: Arc<Context>, mut shutdown: oneshot::Receiver<()>) {
+    let interval = match interval() {
+        Ok(interval) => interval,
+        Err(e) => {
+            warn!("not collecting orphaned objects: {:?}", e);
+            return;
+        }
+    };
+    // an empty store would make every object look orphaned
+    tokio::select! {
+        res = ctx.redirects.wait_until_ready() => if res.is_err() { return },
+        _ = &mut shutdown => return,
+    }
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = &mut shutdown => return,
+        }
+        if !ctx.leads_cluster() {
+            continue;
+        }
+        match sweep(&ctx).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("pruned {} orphaned objects", pruned),
+            Err(e) => warn!("collecting orphaned objects failed: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Arc;
+
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{
+    Api, Resource, ResourceExt,
+    api::{ListParams, Patch, PatchParams},
+    runtime::{WatchStreamExt, controller::Action, reflector::ObjectRef, watcher},
+};
+use serde_json::json;
+use tokio::sync::oneshot;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    controller::{Context, REDIRECT_KUBE_SLUG, apply_status, condition, leadership_acquired},
+    dryrun, host,
+    types::*,
+};
+
+/// Label on generated Redirects naming their RedirectGenerator.
+pub const REDIRECT_GENERATOR_LABEL: &str = "redirect.kube.ibotty.net/generator";
+
+/// Condition type reporting whether the generator's items could be loaded.
+pub const CONDITION_SOURCE_VALID: &str = "SourceValid";
+
+/// Parses `host,target` lines, skipping empty lines and `#` comments.
+fn parse_items(data: &str) -> Result<Vec<RedirectGeneratorItem>, String> {
+    data.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| match line.split_once(',') {
+            Some((host, to)) if !host.trim().is_empty() && !to.trim().is_empty() => {
+                Ok(RedirectGeneratorItem {
+                    host: host.trim().to_string(),
+                    to: to.trim().to_string(),
+                })
+            }
+            _ => Err(format!("line {}: expected `host,target`", i + 1)),
+        })
+        .collect()
+}
+
+/// Collects the inline items and the ones from the referenced ConfigMap.
+///
+/// Returns `Ok(Err(_))` when the source is unusable, so nothing gets pruned.
+async fn items(
+    generator: &RedirectGenerator,
+    ctx: &Context,
+) -> Result<Result<Vec<RedirectGeneratorItem>, String>, Error> {
+    let mut items = generator.spec.items.clone();
+
+    if let Some(source) = &generator.spec.config_map_ref {
+        let ns = generator.namespace().unwrap();
+        let api: Api<ConfigMap> = Api::namespaced(ctx.client.clone(), &ns);
+        let Some(config_map) = api
+            .get_opt(&source.name)
+            .await
+            .map_err(Error::ConfigMapFetchFailed)?
+        else {
+            return Ok(Err(format!("ConfigMap {} does not exist", source.name)));
+        };
+        let Some(data) = config_map.data.as_ref().and_then(|d| d.get(&source.key)) else {
+            return Ok(Err(format!(
+                "ConfigMap {} has no key {}",
+                source.name, source.key
+            )));
+        };
+        match parse_items(data) {
+            Ok(parsed) => items.extend(parsed),
+            Err(e) => return Ok(Err(format!("ConfigMap {}: {}", source.name, e))),
+        }
+    }
+
+    Ok(Ok(items))
+}
+
+fn redirect_for_item(
+    generator: &RedirectGenerator,
+    item: &RedirectGeneratorItem,
+) -> Result<Redirect, idna::Errors> {
+    let name = format!("{}-{}", generator.name_any(), host::normalize(&item.host)?);
+    let template = &generator.spec.template;
+
+    let mut redirect = Redirect::new(
+        &name,
+        RedirectSpec {
+            hosts: HashSet::from([item.host.clone()]),
+            to: RedirectTo {
+                uri: item.to.clone(),
+                include_request_uri: template.include_request_uri,
+                ..Default::default()
+            },
+            ingress: template.ingress.clone(),
+            ..Default::default()
+        },
+    );
+    redirect.metadata.namespace = generator.namespace();
+    redirect.metadata.labels = Some(BTreeMap::from([(
+        REDIRECT_GENERATOR_LABEL.to_string(),
+        generator.name_any(),
+    )]));
+    redirect.metadata.owner_references = generator.controller_owner_ref(&()).map(|o| vec![o]);
+    Ok(redirect)
+}
+
+#[instrument(skip(ctx), fields(trace_id))]
+pub async fn reconcile(
+    generator: Arc<RedirectGenerator>,
+    ctx: Arc<Context>,
+) -> Result<Action, Error> {
+    if !ctx.leads_cluster() {
+        info!("not acting because we are not leader");
+        return Ok(Action::await_change());
+    }
+
+    let _timer = ctx.metrics.reconcile.count_and_measure();
+
+    let ns = generator.namespace().unwrap();
+    let generator_name = generator.name_any();
+    info!(
+        "Reconciling RedirectGenerator \"{}\" in {}",
+        generator_name, ns
+    );
+
+    let generator_api: Api<RedirectGenerator> = Api::namespaced(ctx.client.clone(), &ns);
+    let redirect_api: Api<Redirect> = Api::namespaced(ctx.client.clone(), &ns);
+
+    let mut status = RedirectGeneratorStatus::default();
+    match items(&generator, &ctx).await? {
+        Err(message) => {
+            warn!(
+                "RedirectGenerator {}/{} has an invalid source: {}",
+                ns, generator_name, message
+            );
+            status.conditions.push(condition(
+                CONDITION_SOURCE_VALID,
+                false,
+                "InvalidSource",
+                message,
+            ));
+        }
+        Ok(items) => {
+            let mut invalid_hosts = Vec::new();
+            let mut desired = BTreeSet::new();
+            for item in &items {
+                let Ok(redirect) = redirect_for_item(&generator, item) else {
+                    invalid_hosts.push(item.host.clone());
+                    continue;
+                };
+                let redirect_name = redirect.name_any();
+                redirect_api
+                    .patch(
+                        &redirect_name,
+                        &ctx.patch_params(PatchParams::apply(REDIRECT_KUBE_SLUG).force()),
+                        &Patch::Apply(redirect),
+                    )
+                    .await
+                    .map_err(Error::RedirectApplyFailed)?;
+                desired.insert(redirect_name);
+            }
+
+            // prune Redirects whose entries were removed
+            let selector = format!("{REDIRECT_GENERATOR_LABEL}={generator_name}");
+            let existing = redirect_api
+                .list_metadata(&ListParams::default().labels(&selector))
+                .await
+                .map_err(Error::RedirectListFailed)?;
+            for stale in existing
+                .items
+                .iter()
+                .map(|r| r.name_any())
+                .filter(|name| !desired.contains(name))
+            {
+                info!("pruning generated Redirect {}/{}", ns, stale);
+                if ctx.dry_run {
+                    dryrun::log_delete("Redirect", &ns, &stale);
+                }
+                redirect_api
+                    .delete(&stale, &ctx.delete_params())
+                    .await
+                    .map_err(Error::RedirectDeletionFailed)?;
+            }
+
+            status.conditions.push(if invalid_hosts.is_empty() {
+                condition(
+                    CONDITION_SOURCE_VALID,
+                    true,
+                    "SourceValid",
+                    "all items are valid",
+                )
+            } else {
+                condition(
+                    CONDITION_SOURCE_VALID,
+                    false,
+                    "InvalidHosts",
+                    format!("ignoring invalid hosts: {}", invalid_hosts.join(", ")),
+                )
+            });
+            status.redirects = desired.into_iter().collect();
+        }
+    }
+
+    if ctx.dry_run {
+        dryrun::log_diff(
+            "RedirectGenerator status",
+            &ns,
+            &generator_name,
+            Some(&json!({ "status": generator.status })),
+            &json!({ "status": status }),
+        );
+    } else {
+        apply_status(&generator_api, &generator_name, &status).await?;
+    }
+
+    Ok(Action::requeue(ctx.requeue.resync(&*generator)))
+}
+
+/// Runs the RedirectGenerator controller until `shutdown` fires.
+pub async fn run(ctx: Arc<Context>, shutdown: oneshot::Receiver<()>) {
+    let controller = ctx.controller::<RedirectGenerator>(watcher::Config::default());
+    let store = controller.store();
+
+    controller
+        .owns_stream(
+            ctx.watch::<Redirect>(watcher::Config::default().labels(REDIRECT_GENERATOR_LABEL))
+                .touched_objects(),
+        )
+        .watches_stream(
+            ctx.watch::<ConfigMap>(watcher::Config::default())
+                .touched_objects(),
+            move |config_map| {
+                let ns = config_map.namespace();
+                let name = config_map.name_any();
+                store
+                    .state()
+                    .into_iter()
+                    .filter(|g| {
+                        g.namespace() == ns
+                            && g.spec
+                                .config_map_ref
+                                .as_ref()
+                                .is_some_and(|r| r.name == name)
+                    })
+                    .map(|g| ObjectRef::from_obj(&*g))
+                    .collect::<Vec<_>>()
+            },
+        )
+        .with_config(ctx.controller_config.clone())
+        .reconcile_all_on(leadership_acquired(ctx.leader_state.clone()))
+        .reconcile_all_on(ctx.reconcile_all.subscribe())
+        .graceful_shutdown_on(async move {
+            let _ = shutdown.await;
+        })
+        .run(reconcile, error_policy, ctx)
+        .for_each(|res| async move {
+            match res {
+                Ok(o) => info!("reconciled {:?}", o),
+                Err(e) => warn!("reconcile failed: {:?}", e),
+            }
+        })
+        .await;
+}
+
+fn error_policy(generator: Arc<RedirectGenerator>, error: &Error, ctx: Arc<Context>) -> Action {
+    ctx.metrics
+        .reconcile
+        .set_error(&generator.name_any(), error);
+
+    Action::requeue(ctx.requeue.backoff(&*generator))
+}
@@ -0,0 +1,428 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Context as _;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use kube::{ResourceExt, runtime::watcher};
+
+use crate::types::{Redirect, RedirectMode, RedirectSpec, RedirectTo};
+
+/// Condition type reporting hosts a RedirectHostPolicy keeps the Redirect's namespace from claiming.
+pub const CONDITION_HOST_DENIED: &str = "HostDenied";
+
+/// Condition type reporting hosts beyond the namespace's host quota.
+pub const CONDITION_QUOTA_EXCEEDED: &str = "QuotaExceeded";
+
+/// How many hosts the Redirects of a namespace may claim, from `NAMESPACE_HOST_QUOTA`.
+pub fn quota_from_env() -> anyhow::Result<Option<usize>> {
+    std::env::var("NAMESPACE_HOST_QUOTA")
+        .ok()
+        .map(|quota| quota.parse().context("invalid NAMESPACE_HOST_QUOTA"))
+        .transpose()
+}
+
+/// Whether the controller found hosts the Redirect may not claim.
+///
+/// Such Redirects are not served at all and never win a host over another Redirect.
+pub fn is_claim_denied(redirect: &Redirect) -> bool {
+    redirect.status.as_ref().is_some_and(|status| {
+        status
+            .conditions
+            .iter()
+            .any(|c| c.type_ == CONDITION_HOST_DENIED && c.status == "True")
+    })
+}
+
+/// Converts a host name to its canonical ASCII (punycode) form.
+///
+/// Unicode and `xn--` spellings of the same domain normalize to the same
+/// lowercase name, a trailing dot is ignored.
+pub fn normalize(host: &str) -> Result<String, idna::Errors> {
+    idna::domain_to_ascii(host.trim_end_matches('.'))
+}
+
+/// Normalizes all hosts, returning the valid ones and the ones that are not valid domain names.
+pub fn normalize_all<'a>(
+    hosts: impl IntoIterator<Item = &'a String>,
+) -> (BTreeSet<String>, Vec<String>) {
+    let mut valid = BTreeSet::new();
+    let mut invalid = Vec::new();
+    for host in hosts {
+        match normalize(host) {
+            Ok(host) => {
+                valid.insert(host);
+            }
+            Err(_) => invalid.push(host.clone()),
+        }
+    }
+    (valid, invalid)
+}
+
+/// The override for a normalized `host`, if there is one.
+pub fn override_for<'a>(spec: &'a RedirectSpec, host: &str) -> Option<&'a RedirectTo> {
+    spec.overrides
+        .iter()
+        .find(|o| normalize(&o.host).is_ok_and(|h| h == host))
+        .map(|o| &o.to)
+}
+
+/// The target for a normalized `host`, honoring per-host overrides.
+pub fn target_for<'a>(spec: &'a RedirectSpec, host: &str) -> &'a RedirectTo {
+    override_for(spec, host).unwrap_or(&spec.to)
+}
+
+/// Override hosts that are not listed in `hosts`, and are therefore never served.
+pub fn unmatched_overrides(spec: &RedirectSpec) -> Vec<String> {
+    let (hosts, _) = normalize_all(&spec.hosts);
+    spec.overrides
+        .iter()
+        .filter(|o| !normalize(&o.host).is_ok_and(|h| hosts.contains(&h)))
+        .map(|o| o.host.clone())
+        .collect()
+}
+
+/// The normalized canonical host in `canonicalHost` mode.
+pub fn canonical_host(spec: &RedirectSpec) -> Option<String> {
+    if spec.mode != RedirectMode::CanonicalHost {
+        return None;
+    }
+    normalize(spec.canonical_host.as_deref()?).ok()
+}
+
+/// The normalized hosts a Redirect answers for, and the invalid ones.
+///
+/// The canonical host is never served, it would redirect to itself.
+pub fn served_hosts(spec: &RedirectSpec) -> (BTreeSet<String>, Vec<String>) {
+    let (mut hosts, invalid) = normalize_all(&spec.hosts);
+    if let Some(canonical) = canonical_host(spec) {
+        hosts.remove(&canonical);
+    }
+    (hosts, invalid)
+}
+
+/// Whether a Redirect answers for the normalized `host`.
+pub fn serves(spec: &RedirectSpec, host: &str) -> bool {
+    spec.hosts
+        .iter()
+        .any(|h| normalize(h).is_ok_and(|h| h == host))
+        && canonical_host(spec).is_none_or(|canonical| canonical != host)
+}
+
+/// Where a Redirect goes among the Redirects serving a host: those with request conditions
+/// first, then the oldest, then by namespace and name.
+fn rank(redirect: &Redirect) -> (bool, Option<Time>, Option<String>, String) {
+    (
+        redirect.spec.match_.is_empty(),
+        redirect.creation_timestamp(),
+        redirect.namespace(),
+        redirect.name_any(),
+    )
+}
+
+#[derive(Default)]
+struct Index {
+    /// Redirects by namespace and name
+    namespaces: HashMap<String, HashMap<String, Arc<Redirect>>>,
+    /// the hosts each Redirect claimed, by uid
+    claimed: HashMap<String, BTreeSet<String>>,
+    hosts: HashMap<String, Vec<Arc<Redirect>>>,
+    /// hosts beyond the namespace's quota, by Redirect uid
+    over_quota: HashMap<String, BTreeSet<String>>,
+    /// the Redirects listed so far while the watcher relists
+    relisted: Option<Vec<Redirect>>,
+}
+
+impl Index {
+    fn apply(&mut self, redirect: Redirect, quota: Option<usize>) {
+        let ns = redirect.namespace().unwrap_or_default();
+        let redirect = Arc::new(redirect);
+        let previous = self
+            .namespaces
+            .entry(ns.clone())
+            .or_default()
+            .insert(redirect.name_any(), redirect.clone());
+        if let Some(previous) = previous {
+            self.unclaim(&previous);
+        }
+        if quota.is_some() {
+            // younger Redirects of the namespace may gain or lose hosts
+            self.reclaim(&ns, quota);
+            return;
+        }
+        self.claim(&redirect, &mut BTreeSet::new(), None);
+    }
+
+    fn delete(&mut self, redirect: &Redirect, quota: Option<usize>) {
+        let ns = redirect.namespace().unwrap_or_default();
+        let Some(redirects) = self.namespaces.get_mut(&ns) else {
+            return;
+        };
+        let Some(previous) = redirects.remove(&redirect.name_any()) else {
+            return;
+        };
+        if redirects.is_empty() {
+            self.namespaces.remove(&ns);
+        }
+        self.unclaim(&previous);
+        if quota.is_some() {
+            self.reclaim(&ns, quota);
+        }
+    }
+
+    /// Replaces all Redirects after a relist.
+    fn replace(&mut self, redirects: Vec<Redirect>, quota: Option<usize>) {
+        *self = Self::default();
+        for redirect in redirects {
+            self.namespaces
+                .entry(redirect.namespace().unwrap_or_default())
+                .or_default()
+                .insert(redirect.name_any(), Arc::new(redirect));
+        }
+        let namespaces: Vec<String> = self.namespaces.keys().cloned().collect();
+        for ns in namespaces {
+            self.reclaim(&ns, quota);
+        }
+    }
+
+    /// Claims the hosts of the Redirects in `ns` again, the oldest Redirects' hosts first.
+    fn reclaim(&mut self, ns: &str, quota: Option<usize>) {
+        let mut redirects: Vec<Arc<Redirect>> = self
+            .namespaces
+            .get(ns)
+            .map(|redirects| redirects.values().cloned().collect())
+            .unwrap_or_default();
+        for redirect in &redirects {
+            self.unclaim(redirect);
+        }
+        redirects.sort_by_key(|r| (r.creation_timestamp(), r.name_any()));
+        let mut claimed = BTreeSet::new();
+        for redirect in &redirects {
+            self.claim(redirect, &mut claimed, quota);
+        }
+    }
+
+    /// Adds the served hosts of `redirect`, `claimed` are the hosts of its namespace so far.
+    fn claim(
+        &mut self,
+        redirect: &Arc<Redirect>,
+        claimed: &mut BTreeSet<String>,
+        quota: Option<usize>,
+    ) {
+        if is_claim_denied(redirect) {
+            return;
+        }
+        let uid = redirect.uid().unwrap_or_default();
+        let (hosts, _) = served_hosts(&redirect.spec);
+        for host in hosts {
+            if !claimed.contains(&host) && quota.is_some_and(|q| claimed.len() >= q) {
+                self.over_quota.entry(uid.clone()).or_default().insert(host);
+                continue;
+            }
+            claimed.insert(host.clone());
+            let candidates = self.hosts.entry(host.clone()).or_default();
+            let position = rank(redirect);
+            let at = candidates.partition_point(|r| rank(r) < position);
+            candidates.insert(at, redirect.clone());
+            self.claimed.entry(uid.clone()).or_default().insert(host);
+        }
+    }
+
+    /// Removes the hosts `redirect` claimed.
+    fn unclaim(&mut self, redirect: &Redirect) {
+        let uid = redirect.uid().unwrap_or_default();
+        self.over_quota.remove(&uid);
+        for host in self.claimed.remove(&uid).unwrap_or_default() {
+            if let Some(candidates) = self.hosts.get_mut(&host) {
+                candidates.retain(|r| r.uid().unwrap_or_default() != uid);
+                if candidates.is_empty() {
+                    self.hosts.remove(&host);
+                }
+            }
+        }
+    }
+}
+
+/// Normalized host → serving Redirects, kept up to date from the Redirect watcher.
+///
+/// Avoids normalizing every host of every Redirect on each lookup.
+#[derive(Clone, Default)]
+pub struct HostIndex {
+    index: Arc<RwLock<Index>>,
+    /// how many hosts the Redirects of a namespace may claim
+    quota: Option<usize>,
+}
+
+impl HostIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the hosts per namespace, the oldest Redirects' hosts count first.
+    ///
+    /// Hosts beyond the quota are not served.
+    pub fn with_quota(mut self, quota: Option<usize>) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Updates the index like a reflector updates its store.
+    pub fn apply_watcher_event(&self, event: &watcher::Event<Redirect>) {
+        let mut index = self.index.write().unwrap();
+        match event {
+            watcher::Event::Apply(redirect) => index.apply(redirect.clone(), self.quota),
+            watcher::Event::Delete(redirect) => index.delete(redirect, self.quota),
+            watcher::Event::Init => index.relisted = Some(Vec::new()),
+            watcher::Event::InitApply(redirect) => {
+                index
+                    .relisted
+                    .get_or_insert_default()
+                    .push(redirect.clone());
+            }
+            watcher::Event::InitDone => {
+                let redirects = index.relisted.take().unwrap_or_default();
+                index.replace(redirects, self.quota);
+            }
+        }
+    }
+
+    /// The Redirects serving the normalized `host`.
+    ///
+    /// Redirects with request conditions come first, then the oldest, then by namespace and name.
+    pub fn find(&self, host: &str) -> Vec<Arc<Redirect>> {
+        let index = self.index.read().unwrap();
+        index.hosts.get(host).cloned().unwrap_or_default()
+    }
+
+    /// The hosts of `redirect` beyond its namespace's quota.
+    pub fn over_quota(&self, redirect: &Redirect) -> BTreeSet<String> {
+        let Some(uid) = redirect.uid() else {
+            return BTreeSet::new();
+        };
+        let index = self.index.read().unwrap();
+        index.over_quota.get(&uid).cloned().unwrap_or_default()
+    }
+
+    /// The hosts of `redirect` an older Redirect answers for as well, with that Redirect.
+    ///
+    /// Only Redirects without request conditions conflict, they would compete for
+    /// every request to the host.
+    pub fn conflicts(&self, redirect: &Redirect) -> BTreeMap<String, Arc<Redirect>> {
+        if !redirect.spec.match_.is_empty() {
+            return BTreeMap::new();
+        }
+        let (hosts, _) = served_hosts(&redirect.spec);
+        let index = self.index.read().unwrap();
+        hosts
+            .into_iter()
+            .filter_map(|host| {
+                let winner = index
+                    .hosts
+                    .get(&host)?
+                    .iter()
+                    .find(|r| r.spec.match_.is_empty())?;
+                (winner.uid() != redirect.uid()).then(|| (host, winner.clone()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::redirect_from_json;
+
+    fn serving(ns: &str, name: &str, created: &str, hosts: &[&str]) -> Redirect {
+        redirect_from_json(json!({
+            "metadata": {
+                "name": name,
+                "namespace": ns,
+                "uid": format!("{ns}/{name}"),
+                "creationTimestamp": created,
+            },
+            "spec": { "hosts": hosts, "to": { "uri": "https://example.org" }, "ingress": {} },
+        }))
+    }
+
+    fn names(redirects: Vec<Arc<Redirect>>) -> Vec<String> {
+        redirects.iter().map(|r| r.name_any()).collect()
+    }
+
+    #[test]
+    fn normalizes_to_lowercase_punycode() {
+        assert_eq!(
+            normalize("Bücher.Example.").unwrap(),
+            "xn--bcher-kva.example"
+        );
+        assert_eq!(
+            normalize("xn--bcher-kva.example").unwrap(),
+            "xn--bcher-kva.example"
+        );
+        assert_eq!(normalize("*.Example.com").unwrap(), "*.example.com");
+        assert!(normalize("exa mple.com").is_err());
+    }
+
+    #[test]
+    fn follows_applied_and_deleted_redirects() {
+        let index = HostIndex::new();
+        let old = serving("web", "old", "2024-01-01T00:00:00Z", &["a.example.com"]);
+        let new = serving("web", "new", "2024-02-01T00:00:00Z", &["A.example.com."]);
+        index.apply_watcher_event(&watcher::Event::Apply(new.clone()));
+        index.apply_watcher_event(&watcher::Event::Apply(old.clone()));
+        assert_eq!(names(index.find("a.example.com")), ["old", "new"]);
+        assert_eq!(
+            index.conflicts(&new).keys().collect::<Vec<_>>(),
+            ["a.example.com"]
+        );
+        assert!(index.conflicts(&old).is_empty());
+
+        let moved = serving("web", "old", "2024-01-01T00:00:00Z", &["b.example.com"]);
+        index.apply_watcher_event(&watcher::Event::Apply(moved));
+        assert_eq!(names(index.find("a.example.com")), ["new"]);
+        assert_eq!(names(index.find("b.example.com")), ["old"]);
+
+        index.apply_watcher_event(&watcher::Event::Delete(new));
+        assert!(index.find("a.example.com").is_empty());
+    }
+
+    #[test]
+    fn relists_replace_all_redirects() {
+        let index = HostIndex::new();
+        let gone = serving("web", "gone", "2024-01-01T00:00:00Z", &["a.example.com"]);
+        index.apply_watcher_event(&watcher::Event::Apply(gone));
+        index.apply_watcher_event(&watcher::Event::Init);
+        index.apply_watcher_event(&watcher::Event::InitApply(serving(
+            "web",
+            "kept",
+            "2024-01-01T00:00:00Z",
+            &["bücher.example"],
+        )));
+        // the previous state is served until the relist is done
+        assert_eq!(names(index.find("a.example.com")), ["gone"]);
+        index.apply_watcher_event(&watcher::Event::InitDone);
+        assert!(index.find("a.example.com").is_empty());
+        assert_eq!(names(index.find("xn--bcher-kva.example")), ["kept"]);
+    }
+
+    #[test]
+    fn quota_counts_the_oldest_hosts_first() {
+        let index = HostIndex::new().with_quota(Some(1));
+        let young = serving("web", "young", "2024-02-01T00:00:00Z", &["b.example.com"]);
+        index.apply_watcher_event(&watcher::Event::Apply(young.clone()));
+        assert_eq!(names(index.find("b.example.com")), ["young"]);
+
+        let old = serving("web", "old", "2024-01-01T00:00:00Z", &["a.example.com"]);
+        index.apply_watcher_event(&watcher::Event::Apply(old.clone()));
+        assert_eq!(names(index.find("a.example.com")), ["old"]);
+        assert!(index.find("b.example.com").is_empty());
+        assert_eq!(
+            index.over_quota(&young),
+            BTreeSet::from(["b.example.com".to_string()])
+        );
+
+        index.apply_watcher_event(&watcher::Event::Delete(old));
+        assert_eq!(names(index.find("b.example.com")), ["young"]);
+        assert!(index.over_quota(&young).is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use futures::StreamExt;
+use kube::{
+    Api, Client, ResourceExt,
+    runtime::{
+        WatchStreamExt,
+        reflector::{self, Store},
+        watcher::{self, Event, watcher},
+    },
+};
+use regex::Regex;
+use tracing::warn;
+
+use crate::{
+    host, resync,
+    types::{RedirectHostPolicy, RedirectHostPolicyRule},
+};
+
+/// The cluster's RedirectHostPolicies, kept up to date by a watch.
+#[derive(Clone)]
+pub struct HostPolicies {
+    store: Store<RedirectHostPolicy>,
+}
+
+/// Whether `rule` matches the normalized `host`.
+fn matches(rule: &RedirectHostPolicyRule, host: &str) -> bool {
+    let suffix = rule.suffix.as_deref().and_then(|suffix| {
+        host::normalize(suffix.trim_start_matches("*.").trim_start_matches('.')).ok()
+    });
+    let by_suffix = suffix.is_some_and(|suffix| {
+        host == suffix
+            || host
+                .strip_suffix(&suffix)
+                .is_some_and(|sub| sub.ends_with('.'))
+    });
+    let by_regex =
+        rule.regex
+            .as_deref()
+            .is_some_and(|regex| match Regex::new(&format!("^(?:{regex})$")) {
+                Ok(regex) => regex.is_match(host),
+                Err(e) => {
+                    warn!("ignoring invalid RedirectHostPolicy regex {}: {}", regex, e);
+                    false
+                }
+            });
+    by_suffix || by_regex
+}
+
+impl HostPolicies {
+    /// Watches RedirectHostPolicies, reconciling everything whenever one changes.
+    pub fn spawn(client: Client, changed: resync::ReconcileAll) -> Self {
+        let (store, writer) = reflector::store();
+        let api: Api<RedirectHostPolicy> = Api::all(client);
+        tokio::spawn(
+            reflector::reflector(writer, watcher(api, watcher::Config::default()))
+                .default_backoff()
+                .for_each(move |event| {
+                    match event {
+                        // relists do not tell what changed, better safe than sorry
+                        Ok(Event::Apply(_) | Event::Delete(_) | Event::InitDone) => {
+                            changed.trigger()
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("watching RedirectHostPolicies failed: {:?}", e),
+                    }
+                    futures::future::ready(())
+                }),
+        );
+        Self { store }
+    }
+
+    /// No RedirectHostPolicies, for operators not allowed to watch them.
+    pub fn none() -> Self {
+        Self {
+            store: reflector::store().0,
+        }
+    }
+
+    /// The `hosts` `namespace` may not claim, with the policies reserving them.
+    pub fn denied(
+        &self,
+        namespace: &str,
+        hosts: &BTreeSet<String>,
+    ) -> BTreeMap<String, Vec<String>> {
+        let policies = self.store.state();
+        hosts
+            .iter()
+            .filter_map(|host| {
+                let mut reserved_by = Vec::new();
+                for policy in &policies {
+                    for rule in policy.spec.rules.iter().filter(|r| matches(r, host)) {
+                        if rule.namespaces.iter().any(|ns| ns == namespace) {
+                            return None;
+                        }
+                        reserved_by.push(policy.name_any());
+                    }
+                }
+                reserved_by.dedup();
+                (!reserved_by.is_empty()).then(|| (host.clone(), reserved_by))
+            })
+            .collect()
+    }
+}
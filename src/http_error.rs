@@ -0,0 +1,226 @@
+use axum::{
+    Json,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::interstitial::escape_html;
+
+/// Why a request was not redirected.
+#[derive(Debug)]
+pub enum HttpError {
+    /// no Redirect for the request, or a path that is gone, with a Redirect's custom page
+    NotFound {
+        status: StatusCode,
+        page: Option<String>,
+    },
+    /// a Redirect refuses to answer, e.g. while paused or looping
+    PolicyDenied {
+        status: StatusCode,
+        reason: String,
+    },
+    RateLimited,
+    /// the Redirects are not loaded yet after startup
+    NotReady,
+    /// the `Host` header is not a valid domain name
+    BadHost(String),
+    InternalError(String),
+}
+
+impl HttpError {
+    pub fn not_found() -> Self {
+        Self::NotFound {
+            status: StatusCode::NOT_FOUND,
+            page: None,
+        }
+    }
+
+    /// Fills in the operator's 404 page where no Redirect brought its own.
+    ///
+    /// `{{host}}` and `{{status}}` in the template are replaced.
+    pub fn with_default_page(self, template: Option<&str>, host: &str) -> Self {
+        match (self, template) {
+            (Self::NotFound { status, page: None }, Some(template)) => Self::NotFound {
+                status,
+                page: Some(
+                    template
+                        .replace("{{host}}", &escape_html(host))
+                        .replace("{{status}}", status.as_str()),
+                ),
+            },
+            (error, _) => error,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotFound { status, .. } | Self::PolicyDenied { status, .. } => *status,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::NotReady => StatusCode::SERVICE_UNAVAILABLE,
+            Self::BadHost(_) => StatusCode::BAD_REQUEST,
+            Self::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Label for metrics and the `error` field of JSON responses.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "not_found",
+            Self::PolicyDenied { .. } => "policy_denied",
+            Self::RateLimited => "rate_limited",
+            Self::NotReady => "not_ready",
+            Self::BadHost(_) => "bad_host",
+            Self::InternalError(_) => "internal_error",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::NotFound { .. } => "no redirect for this request".to_string(),
+            Self::PolicyDenied { reason, .. } => reason.clone(),
+            Self::RateLimited => "too many requests".to_string(),
+            Self::NotReady => "starting up, try again shortly".to_string(),
+            Self::BadHost(host) => format!("invalid host {host}"),
+            Self::InternalError(_) => "internal error".to_string(),
+        }
+    }
+
+    /// Answers in the format the client accepts: JSON, HTML or plain text.
+    ///
+    /// A Redirect's custom 404 page is always served as HTML.
+    pub fn respond(self, headers: &HeaderMap) -> Response {
+        if matches!(self, Self::NotReady) {
+            let mut response = self.respond_body(headers);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("5"));
+            return response;
+        }
+        self.respond_body(headers)
+    }
+
+    fn respond_body(self, headers: &HeaderMap) -> Response {
+        let status = self.status();
+        if let Self::NotFound {
+            page: Some(page), ..
+        } = self
+        {
+            return (status, Html(page)).into_response();
+        }
+
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains("application/json") {
+            let body = json!({"error": self.kind(), "message": self.message()});
+            (status, Json(body)).into_response()
+        } else if accept.contains("text/html") {
+            let message = escape_html(&self.message());
+            let html = format!(
+                "<!DOCTYPE html>\n<html><head><title>{} {message}</title></head>\
+                 <body><h1>{message}</h1></body></html>\n",
+                status.as_u16()
+            );
+            (status, Html(html)).into_response()
+        } else {
+            (status, format!("{}\n", self.message())).into_response()
+        }
+    }
+}
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        self.respond(&HeaderMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepting(accept: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+        headers
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn maps_errors_to_statuses() {
+        assert_eq!(HttpError::not_found().status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            HttpError::RateLimited.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            HttpError::BadHost("a b".to_string()).status(),
+            StatusCode::BAD_REQUEST
+        );
+        let denied = HttpError::PolicyDenied {
+            status: StatusCode::FORBIDDEN,
+            reason: "target not allowed".to_string(),
+        };
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+        assert_eq!(denied.kind(), "policy_denied");
+        assert_eq!(denied.message(), "target not allowed");
+    }
+
+    #[tokio::test]
+    async fn answers_in_the_accepted_format() {
+        let json = HttpError::not_found().respond(&accepting("application/json"));
+        assert_eq!(json.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(
+            body(json).await,
+            r#"{"error":"not_found","message":"no redirect for this request"}"#
+        );
+
+        let html = HttpError::not_found().respond(&accepting("text/html,*/*"));
+        assert_eq!(html.status(), StatusCode::NOT_FOUND);
+        assert!(
+            body(html)
+                .await
+                .contains("<h1>no redirect for this request</h1>")
+        );
+
+        let text = HttpError::NotReady.respond(&HeaderMap::new());
+        assert_eq!(text.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(text.headers()[header::RETRY_AFTER], "5");
+        assert_eq!(body(text).await, "starting up, try again shortly\n");
+    }
+
+    #[tokio::test]
+    async fn custom_pages_are_always_html() {
+        let error = HttpError::NotFound {
+            status: StatusCode::GONE,
+            page: Some("<p>gone</p>".to_string()),
+        };
+        let response = error.respond(&accepting("application/json"));
+        assert_eq!(response.status(), StatusCode::GONE);
+        assert_eq!(body(response).await, "<p>gone</p>");
+    }
+
+    #[tokio::test]
+    async fn fills_in_the_default_page() {
+        let error = HttpError::not_found()
+            .with_default_page(Some("<p>{{host}}: {{status}}</p>"), "<script>.example");
+        let response = error.respond(&HeaderMap::new());
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body(response).await, "<p>&lt;script&gt;.example: 404</p>");
+
+        // a Redirect's own page wins
+        let own = HttpError::NotFound {
+            status: StatusCode::NOT_FOUND,
+            page: Some("own".to_string()),
+        }
+        .with_default_page(Some("default"), "example.com");
+        assert_eq!(body(own.respond(&HeaderMap::new())).await, "own");
+    }
+}
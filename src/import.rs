@@ -0,0 +1,357 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use kube::Resource;
+use serde_json::{Value, json};
+
+use crate::{host, pathmap::CONFIG_MAP_LABEL, types::Redirect};
+
+/// Key of the path map in generated ConfigMaps.
+const PATH_MAP_KEY: &str = "paths.csv";
+
+/// The formats legacy redirects can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `host[/path],target` lines
+    Csv,
+    /// the entries of an nginx `map` block, `host[/path] target;`
+    Nginx,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "nginx" => Ok(Self::Nginx),
+            _ => Err(format!("unknown format {s}, expected csv or nginx")),
+        }
+    }
+}
+
+/// How entries are grouped into Redirects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// one Redirect per host
+    Host,
+    /// one Redirect for all hosts with the same target and paths
+    Target,
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "host" => Ok(Self::Host),
+            "target" => Ok(Self::Target),
+            _ => Err(format!("unknown grouping {s}, expected host or target")),
+        }
+    }
+}
+
+/// One legacy redirect, an empty target marks a gone path.
+#[derive(Debug)]
+pub struct Entry {
+    pub host: String,
+    pub path: Option<String>,
+    pub to: String,
+}
+
+/// Parses `input`, sources starting with `/` are paths of `default_host`.
+pub fn parse(
+    input: &str,
+    format: Format,
+    default_host: Option<&str>,
+) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let pair = match format {
+            Format::Csv => line
+                .split_once(',')
+                .map(|(source, to)| (source.trim(), to.trim()))
+                .ok_or("expected `host[/path],target`".to_string()),
+            Format::Nginx => nginx_entry(line),
+        };
+        let parsed = pair
+            .and_then(|pair| match pair {
+                // the map's `{`, `}` and parameters
+                ("", _) => Ok(None),
+                (source, to) => entry(source, to, default_host).map(Some),
+            })
+            .map_err(|e| format!("line {}: {e}", i + 1))?;
+        entries.extend(parsed);
+    }
+    Ok(entries)
+}
+
+/// The source and target of a line in an nginx `map` block, an empty source if there are none.
+fn nginx_entry(line: &str) -> Result<(&str, &str), String> {
+    let line = line
+        .split_once(" #")
+        .map_or(line, |(line, _)| line)
+        .trim()
+        .trim_end_matches(';');
+    if line.starts_with("map ") || line.ends_with('{') || line == "}" {
+        return Ok(("", ""));
+    }
+    let tokens: Vec<&str> = line
+        .split_whitespace()
+        .map(|t| t.trim_matches(|c| c == '"' || c == '\''))
+        .collect();
+    match tokens[..] {
+        ["default", _] | ["hostnames"] | ["volatile"] => Ok(("", "")),
+        ["include", _] => Err("includes are not supported".to_string()),
+        [source, _] if source.starts_with('~') => {
+            Err("regular expressions are not supported".to_string())
+        }
+        [source, to] => Ok((source, to)),
+        _ => Err("expected `host[/path] target;`".to_string()),
+    }
+}
+
+fn entry(source: &str, to: &str, default_host: Option<&str>) -> Result<Entry, String> {
+    let source = source
+        .strip_prefix("https://")
+        .or_else(|| source.strip_prefix("http://"))
+        .unwrap_or(source);
+    if source.contains('?') {
+        return Err(format!("{source}: queries are not supported"));
+    }
+    let (host, path) = match source.find('/') {
+        Some(0) => (
+            default_host.ok_or(format!("{source} has no host, pass --host"))?,
+            Some(source),
+        ),
+        Some(i) => (&source[..i], Some(&source[i..])),
+        None => (source, None),
+    };
+    let host = host::normalize(host).map_err(|_| format!("{host} is no valid host"))?;
+    // the host itself
+    let path = path.filter(|p| *p != "/");
+    if path.is_none() && to.is_empty() {
+        return Err(format!("{host} has no target"));
+    }
+    Ok(Entry {
+        host,
+        path: path.map(str::to_string),
+        to: to.to_string(),
+    })
+}
+
+/// Hosts redirecting alike, imported as one Redirect and its path map.
+#[derive(Debug)]
+pub struct Group {
+    pub name: String,
+    pub hosts: BTreeSet<String>,
+    pub to: String,
+    pub paths: BTreeMap<String, String>,
+}
+
+/// Groups `entries` into Redirects, every host needs an entry without path for its target.
+pub fn group(entries: &[Entry], grouping: Grouping) -> Result<Vec<Group>, String> {
+    let mut targets: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut paths: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+    for entry in entries {
+        let conflicting = match &entry.path {
+            None => targets
+                .insert(&entry.host, &entry.to)
+                .is_some_and(|previous| previous != entry.to),
+            Some(path) => paths
+                .entry(&entry.host)
+                .or_default()
+                .insert(path.clone(), entry.to.clone())
+                .is_some_and(|previous| previous != entry.to),
+        };
+        if conflicting {
+            return Err(format!(
+                "{}{} has conflicting targets",
+                entry.host,
+                entry.path.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+    if let Some(host) = paths.keys().find(|host| !targets.contains_key(*host)) {
+        return Err(format!(
+            "{host} only has entries with paths, add one without path for its other paths"
+        ));
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    for (host, to) in targets {
+        let host_paths = paths.remove(host).unwrap_or_default();
+        let existing = groups
+            .iter_mut()
+            .find(|g| grouping == Grouping::Target && g.to == to && g.paths == host_paths);
+        match existing {
+            Some(group) => {
+                group.hosts.insert(host.to_string());
+            }
+            None => groups.push(Group {
+                name: name_for(host),
+                hosts: BTreeSet::from([host.to_string()]),
+                to: to.to_string(),
+                paths: host_paths,
+            }),
+        }
+    }
+    Ok(groups)
+}
+
+/// An object name for `host`, wildcards are not allowed in names.
+fn name_for(host: &str) -> String {
+    match host.strip_prefix("*.") {
+        Some(domain) => format!("wildcard.{domain}"),
+        None => host.to_string(),
+    }
+}
+
+impl Group {
+    /// The Redirect to apply, only with the fields the import manages.
+    pub fn redirect(&self, namespace: &str, include_request_uri: bool) -> Value {
+        let mut redirect = json!({
+            "apiVersion": Redirect::api_version(&()),
+            "kind": Redirect::kind(&()),
+            "metadata": { "name": self.name, "namespace": namespace },
+            "spec": {
+                "hosts": self.hosts,
+                "to": { "uri": self.to, "includeRequestUri": include_request_uri },
+            },
+        });
+        if !self.paths.is_empty() {
+            redirect["spec"]["pathMap"] = json!({
+                "configMapRef": { "name": self.name, "key": PATH_MAP_KEY },
+                "format": "csv",
+            });
+        }
+        redirect
+    }
+
+    /// The ConfigMap with the path map, none without paths.
+    pub fn config_map(&self, namespace: &str) -> Option<Value> {
+        if self.paths.is_empty() {
+            return None;
+        }
+        let csv: String = self
+            .paths
+            .iter()
+            .map(|(path, to)| format!("{path},{to}\n"))
+            .collect();
+        Some(json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": {
+                "name": self.name,
+                "namespace": namespace,
+                "labels": { CONFIG_MAP_LABEL: "true" },
+            },
+            "data": { PATH_MAP_KEY: csv },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_csv_entries() {
+        let entries = parse(
+            "# legacy\nold.example.com,https://example.org\nhttps://old.example.com/shop,https://example.org/store\n/gone,\n",
+            Format::Csv,
+            Some("Other.Example.com"),
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].host, "old.example.com");
+        assert_eq!(entries[0].path, None);
+        assert_eq!(entries[1].path.as_deref(), Some("/shop"));
+        assert_eq!(entries[2].host, "other.example.com");
+        assert_eq!(entries[2].to, "");
+    }
+
+    #[test]
+    fn parses_nginx_map_blocks() {
+        let entries = parse(
+            "map $host$uri $target {\n    hostnames;\n    default \"\";\n    old.example.com https://example.org; # home\n    \"old.example.com/shop\" https://example.org/store;\n}\n",
+            Format::Nginx,
+            None,
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].path.as_deref(), Some("/shop"));
+        assert_eq!(entries[1].to, "https://example.org/store");
+    }
+
+    #[test]
+    fn reports_unsupported_lines() {
+        let error = |input, format| parse(input, format, None).unwrap_err();
+        assert_eq!(
+            error("/shop,https://example.org", Format::Csv),
+            "line 1: /shop has no host, pass --host"
+        );
+        assert_eq!(
+            error("a.example.com?x=1,https://example.org", Format::Csv),
+            "line 1: a.example.com?x=1: queries are not supported"
+        );
+        assert_eq!(
+            error("~^/a https://example.org;", Format::Nginx),
+            "line 1: regular expressions are not supported"
+        );
+        assert_eq!(
+            error("include more.map;", Format::Nginx),
+            "line 1: includes are not supported"
+        );
+        assert_eq!(
+            error("a.example.com,", Format::Csv),
+            "line 1: a.example.com has no target"
+        );
+    }
+
+    #[test]
+    fn groups_hosts_by_target() {
+        let entries = parse(
+            "a.example.com,https://example.org\nb.example.com,https://example.org\nc.example.com,https://example.net\n",
+            Format::Csv,
+            None,
+        )
+        .unwrap();
+        let groups = group(&entries, Grouping::Target).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "a.example.com");
+        assert_eq!(groups[0].hosts.len(), 2);
+        assert_eq!(group(&entries, Grouping::Host).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn refuses_conflicting_and_incomplete_entries() {
+        let conflicting = parse(
+            "a.example.com,https://example.org\na.example.com,https://example.net\n",
+            Format::Csv,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            group(&conflicting, Grouping::Host).unwrap_err(),
+            "a.example.com has conflicting targets"
+        );
+        let paths_only = parse(
+            "a.example.com/shop,https://example.org\n",
+            Format::Csv,
+            None,
+        )
+        .unwrap();
+        assert!(group(&paths_only, Grouping::Host).is_err());
+    }
+
+    #[test]
+    fn wildcard_groups_get_valid_names() {
+        assert_eq!(name_for("*.example.com"), "wildcard.example.com");
+        assert_eq!(name_for("example.com"), "example.com");
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use futures::StreamExt;
+use k8s_openapi::api::networking::v1::IngressClass;
+use kube::{
+    Api, Client,
+    runtime::{
+        WatchStreamExt,
+        reflector::{self, ObjectRef, Store},
+        watcher::{self, Event, watcher},
+    },
+};
+use tracing::warn;
+
+use crate::resync;
+
+/// Condition type reporting whether `spec.ingress.ingressClassName` names an IngressClass.
+pub const CONDITION_INGRESS_CLASS_MISSING: &str = "IngressClassMissing";
+
+/// The cluster's IngressClasses, kept up to date by a watch.
+#[derive(Clone)]
+pub struct IngressClasses {
+    store: Store<IngressClass>,
+    listed: Arc<AtomicBool>,
+}
+
+impl IngressClasses {
+    /// Watches IngressClasses, reconciling everything whenever one is added or removed.
+    pub fn spawn(client: Client, changed: resync::ReconcileAll) -> Self {
+        let (store, writer) = reflector::store();
+        let listed = Arc::new(AtomicBool::new(false));
+        let api: Api<IngressClass> = Api::all(client);
+        let listed_ = listed.clone();
+        tokio::spawn(
+            reflector::reflector(writer, watcher(api, watcher::Config::default()))
+                .default_backoff()
+                .for_each(move |event| {
+                    match event {
+                        Ok(Event::InitDone) => {
+                            listed_.store(true, Ordering::Relaxed);
+                            changed.trigger()
+                        }
+                        Ok(Event::Apply(_) | Event::Delete(_)) => changed.trigger(),
+                        Ok(_) => {}
+                        Err(e) => warn!("watching IngressClasses failed: {:?}", e),
+                    }
+                    futures::future::ready(())
+                }),
+        );
+        Self { store, listed }
+    }
+
+    /// No IngressClasses, for operators not allowed to watch them; none is ever missing.
+    pub fn none() -> Self {
+        Self {
+            store: reflector::store().0,
+            listed: Arc::default(),
+        }
+    }
+
+    /// Whether IngressClass `name` is known to be absent.
+    ///
+    /// Nothing is missing until the IngressClasses have been listed once.
+    pub fn missing(&self, name: &str) -> bool {
+        self.listed.load(Ordering::Relaxed) && self.store.get(&ObjectRef::new(name)).is_none()
+    }
+}
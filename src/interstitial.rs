@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+/// Used when a Redirect enables interstitials without its own templates.
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="5; url={{target}}">
+<title>{{host}} has moved</title>
+</head>
+<body>
+<p>{{host}} has moved to <a href="{{target}}">{{target}}</a>.</p>
+</body>
+</html>
+"#;
+
+/// Language tags from an `Accept-Language` header, most preferred first.
+pub fn accepted_languages(header: &str) -> Vec<String> {
+    let mut languages: Vec<(f32, usize, String)> = header
+        .split(',')
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            if tag.is_empty() || tag == "*" {
+                return None;
+            }
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then(|| (quality, i, tag.to_lowercase()))
+        })
+        .collect();
+    languages.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    languages.into_iter().map(|(_, _, tag)| tag).collect()
+}
+
+/// Picks `template.<lang>.html` for the first accepted language (or its primary subtag),
+/// falling back to `template.html`.
+pub fn select_template<'a>(
+    templates: &'a BTreeMap<String, String>,
+    languages: &[String],
+) -> Option<&'a str> {
+    let lookup = |lang: &str| {
+        templates
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&format!("template.{lang}.html")))
+            .map(|(_, template)| template.as_str())
+    };
+
+    languages
+        .iter()
+        .find_map(|lang| {
+            lookup(lang).or_else(|| {
+                lang.split_once('-')
+                    .and_then(|(primary, _)| lookup(primary))
+            })
+        })
+        .or_else(|| templates.get("template.html").map(String::as_str))
+}
+
+/// Renders the interstitial, substituting `{{target}}` and `{{host}}`.
+pub fn render(template: Option<&str>, target: &str, host: &str) -> String {
+    template
+        .unwrap_or(DEFAULT_TEMPLATE)
+        .replace("{{target}}", &escape_html(target))
+        .replace("{{host}}", &escape_html(host))
+}
+
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,215 @@
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use std::collections::BTreeSet;
+
+use kube::{
+    ResourceExt,
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+};
+use serde_json::{Value, json};
+
+use crate::{
+    controller::{
+        NetworkingBackend, REDIRECT_SERVICE_PORT, RenderContext, condition,
+        ingress_name_for_redirect,
+    },
+    host,
+    offload::{self, EdgeTarget},
+    types::{Error, PathMatchType, Redirect, RedirectSpec, RedirectStatus},
+};
+
+/// Condition type reporting whether the VirtualService is applied, and how it answers.
+pub const CONDITION_VIRTUAL_SERVICE_READY: &str = "VirtualServiceReady";
+
+/// The Istio `networking.istio.io/v1` VirtualService API.
+pub fn api_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk(
+        "networking.istio.io",
+        "v1",
+        "VirtualService",
+    ))
+}
+
+/// The Istio `redirect` stanza equivalent to a Redirect, or why there is none.
+///
+/// Such Redirects are answered by the gateway without reaching the operator.
+fn native_redirect(spec: &RedirectSpec) -> Result<Value, String> {
+    Ok(match offload::edge_target(spec)? {
+        EdgeTarget::CanonicalHost(canonical) => {
+            json!({ "authority": canonical, "redirectCode": 308 })
+        }
+        EdgeTarget::Uri {
+            scheme,
+            authority,
+            path,
+        } => {
+            let mut redirect = json!({
+                "scheme": scheme,
+                "authority": authority,
+                "redirectCode": 308,
+            });
+            if let Some(path) = path {
+                redirect["uri"] = json!(path);
+            }
+            redirect
+        }
+    })
+}
+
+/// Istio request matches for `match.paths`, everything if there are none.
+fn http_matches(spec: &RedirectSpec) -> Option<Value> {
+    if spec.match_.paths.is_empty() {
+        return None;
+    }
+    let matches = spec
+        .match_
+        .paths
+        .iter()
+        .map(|p| {
+            let kind = match p.path_type {
+                PathMatchType::Prefix => "prefix",
+                PathMatchType::Exact => "exact",
+                PathMatchType::Regex => "regex",
+            };
+            json!({ "uri": { kind: p.path } })
+        })
+        .collect();
+    Some(Value::Array(matches))
+}
+
+/// The VirtualService serving a Redirect, with the condition describing it.
+///
+/// `allow_native` is false if the operator has to see requests regardless of
+/// the spec, e.g. to refuse loops.
+pub fn virtual_service_for_redirect(
+    namespace: &str,
+    service_name: &str,
+    redirect: &Redirect,
+    allow_native: bool,
+) -> (DynamicObject, Condition) {
+    let settings = &redirect.spec.virtual_service;
+    let (hosts, _) = host::served_hosts(&redirect.spec);
+
+    let route_to_operator = json!({
+        "route": [{
+            "destination": {
+                "host": format!("{service_name}.{namespace}.svc.cluster.local"),
+                "port": { "number": REDIRECT_SERVICE_PORT },
+            },
+        }],
+    });
+    let (mut http, ready) = match (settings.native, allow_native) {
+        (false, _) => (
+            route_to_operator,
+            condition(
+                CONDITION_VIRTUAL_SERVICE_READY,
+                true,
+                "RoutedToOperator",
+                "requests are routed to the operator",
+            ),
+        ),
+        (true, false) => (
+            route_to_operator,
+            condition(
+                CONDITION_VIRTUAL_SERVICE_READY,
+                true,
+                "RoutedToOperator",
+                "not redirecting natively while the Redirect is refused",
+            ),
+        ),
+        (true, true) => match native_redirect(&redirect.spec) {
+            Ok(stanza) => (
+                json!({ "redirect": stanza }),
+                condition(
+                    CONDITION_VIRTUAL_SERVICE_READY,
+                    true,
+                    "Native",
+                    "requests are redirected by Istio",
+                ),
+            ),
+            Err(reason) => (
+                route_to_operator,
+                condition(
+                    CONDITION_VIRTUAL_SERVICE_READY,
+                    true,
+                    "RoutedToOperator",
+                    format!("cannot redirect natively, {reason}"),
+                ),
+            ),
+        },
+    };
+    if let Some(matches) = http_matches(&redirect.spec) {
+        http["match"] = matches;
+    }
+
+    let mut spec = json!({
+        "hosts": hosts,
+        "http": [http],
+    });
+    if !settings.gateways.is_empty() {
+        spec["gateways"] = json!(settings.gateways);
+    }
+
+    let mut virtual_service =
+        DynamicObject::new(&ingress_name_for_redirect(redirect), &api_resource())
+            .within(namespace)
+            .data(json!({ "spec": spec }));
+    virtual_service.metadata.annotations = settings.annotations.clone();
+    virtual_service.metadata.labels = settings.labels.clone();
+    (virtual_service, ready)
+}
+
+/// Istio VirtualServices, one per Redirect.
+pub struct VirtualServices;
+
+impl NetworkingBackend for VirtualServices {
+    fn kind(&self) -> &'static str {
+        "VirtualService"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        api_resource()
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        spec.wants_virtual_service()
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let (virtual_service, ready) = virtual_service_for_redirect(
+            rctx.namespace,
+            rctx.service_name,
+            redirect,
+            rctx.allow_native,
+        );
+        (vec![virtual_service], vec![ready])
+    }
+
+    fn existing(&self, redirect: &Redirect, self_namespace: &str) -> BTreeSet<(String, String)> {
+        redirect
+            .status
+            .iter()
+            .filter_map(|status| status.virtual_service.clone())
+            .map(|name| (self_namespace.to_string(), name))
+            .collect()
+    }
+
+    fn record(&self, status: &mut RedirectStatus, _namespace: &str, applied: &[DynamicObject]) {
+        status.virtual_service = applied.first().map(|vs| vs.name_any());
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::VirtualServiceCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::VirtualServiceDeletionFailed(error)
+    }
+}
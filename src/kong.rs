@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::sync::LazyLock;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::{
+    ResourceExt,
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+};
+use serde_json::{Value, json};
+
+use crate::{
+    controller::{Ingresses, NetworkingBackend, RenderContext, ingress_name_for_redirect},
+    offload::{self, EdgeTarget},
+    types::{Error, IngressOffload, Redirect, RedirectSpec, RedirectStatus, RedirectStatusObject},
+};
+
+/// Whether Redirects not choosing an offload are offloaded to Kong, from `INGRESS_OFFLOAD`.
+static KONG_BY_DEFAULT: LazyLock<bool> =
+    LazyLock::new(|| env::var("INGRESS_OFFLOAD").is_ok_and(|v| v.trim() == "kong"));
+
+/// The Kong `configuration.konghq.com/v1` KongPlugin API.
+pub fn api_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk(
+        "configuration.konghq.com",
+        "v1",
+        "KongPlugin",
+    ))
+}
+
+/// The `redirect` plugin's config answering with `target`.
+///
+/// Canonical hosts are redirected to over https, like with ingress-nginx.
+fn redirect_config(target: EdgeTarget) -> Value {
+    let (location, keep_incoming_path) = match target {
+        EdgeTarget::CanonicalHost(host) => (format!("https://{host}"), true),
+        EdgeTarget::Uri {
+            scheme,
+            authority,
+            path,
+        } => match path {
+            Some(path) => (format!("{scheme}://{authority}{path}"), false),
+            None => (format!("{scheme}://{authority}"), true),
+        },
+    };
+    json!({
+        "status_code": 308,
+        "location": location,
+        "keep_incoming_path": keep_incoming_path,
+    })
+}
+
+/// The KongPlugin the Ingresses of an offloaded Redirect reference, next to them.
+///
+/// There is none if Kong cannot answer for the Redirect; the Ingresses' `Offloaded` condition
+/// tells why.
+pub struct KongPlugins;
+
+impl NetworkingBackend for KongPlugins {
+    fn kind(&self) -> &'static str {
+        "KongPlugin"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        api_resource()
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        Ingresses.wanted(spec)
+            && spec
+                .ingress
+                .offload
+                .as_ref()
+                .map_or(*KONG_BY_DEFAULT, |o| *o == IngressOffload::Kong)
+    }
+
+    fn namespace<'a>(&self, redirect: &'a Redirect, same_namespace: bool) -> Option<&'a str> {
+        Ingresses.namespace(redirect, same_namespace)
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let offload = rctx.defaults.apply(&redirect.spec.ingress).offload;
+        if offload != Some(IngressOffload::Kong) || !rctx.allow_native {
+            return (Vec::new(), Vec::new());
+        }
+        let Ok(target) = offload::edge_target(&redirect.spec) else {
+            return (Vec::new(), Vec::new());
+        };
+        let plugin = DynamicObject::new(&ingress_name_for_redirect(redirect), &api_resource())
+            .within(rctx.namespace)
+            .data(json!({
+                "plugin": "redirect",
+                "config": redirect_config(target),
+            }));
+        (vec![plugin], Vec::new())
+    }
+
+    fn existing(&self, redirect: &Redirect, _self_namespace: &str) -> BTreeSet<(String, String)> {
+        redirect
+            .status
+            .iter()
+            .filter_map(|status| status.kong_plugin.as_ref())
+            .map(|p| (p.namespace.clone(), p.name.clone()))
+            .collect()
+    }
+
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]) {
+        status.kong_plugin = applied.first().map(|plugin| RedirectStatusObject {
+            name: plugin.name_any(),
+            namespace: namespace.to_string(),
+        });
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::KongPluginCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::KongPluginDeletionFailed(error)
+    }
+}
@@ -0,0 +1,8 @@
+//! Redirect types and checks, for tooling that validates Redirect manifests.
+
+pub mod host;
+pub mod lint;
+pub mod pattern;
+pub mod target;
+pub mod ttl;
+pub mod types;
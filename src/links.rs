@@ -0,0 +1,21 @@
+use axum::http::HeaderValue;
+
+use crate::types::RedirectLink;
+
+/// The `Link` header value for `links`, unset URIs refer to `target`.
+///
+/// `None` without links or if the value is not a valid header.
+pub fn header_value(links: &[RedirectLink], target: &str) -> Option<HeaderValue> {
+    if links.is_empty() {
+        return None;
+    }
+    let value = links
+        .iter()
+        .map(|link| {
+            let uri = link.uri.as_deref().unwrap_or(target);
+            format!("<{}>; rel=\"{}\"", uri, link.rel.replace('"', ""))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::from_str(&value).ok()
+}
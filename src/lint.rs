@@ -0,0 +1,341 @@
+use std::fmt;
+
+use crate::{
+    host, pattern, target, ttl,
+    types::{IngressOffload, RedirectMode, RedirectSpec},
+};
+
+/// A likely mistake in a Redirect spec that is not invalid per se.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    /// path of the offending field, e.g. `spec.to.uri`
+    pub field: String,
+    pub message: String,
+}
+
+impl LintWarning {
+    fn new(field: impl ToString, message: impl ToString) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Checks a Redirect spec for unused fields and questionable combinations.
+pub fn lint(spec: &RedirectSpec) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+
+    let (hosts, invalid_hosts) = host::served_hosts(spec);
+    if hosts.is_empty() {
+        warnings.push(LintWarning::new("spec.hosts", "no host would be served"));
+    }
+    for invalid in invalid_hosts {
+        warnings.push(LintWarning::new(
+            "spec.hosts",
+            format!("{invalid} is not a valid host name and is ignored"),
+        ));
+    }
+    for unmatched in host::unmatched_overrides(spec) {
+        warnings.push(LintWarning::new(
+            "spec.overrides",
+            format!("{unmatched} is not listed in spec.hosts, the override is unused"),
+        ));
+    }
+
+    match spec.mode {
+        RedirectMode::Redirect => {
+            if spec.canonical_host.is_some() {
+                warnings.push(LintWarning::new(
+                    "spec.canonicalHost",
+                    "only used in canonicalHost mode",
+                ));
+            }
+            for (field, to) in target::targets(spec) {
+                if to.uri.starts_with("http://") {
+                    warnings.push(LintWarning::new(
+                        field,
+                        format!(
+                            "{} uses plain http, clients with HSTS will be upgraded or refuse it",
+                            to.uri
+                        ),
+                    ));
+                }
+                if to.allow_non_http_scheme && to.include_request_uri && !target::is_http(&to.uri) {
+                    warnings.push(LintWarning::new(
+                        field,
+                        format!(
+                            "the request path is appended to {}, unset includeRequestUri",
+                            to.uri
+                        ),
+                    ));
+                }
+            }
+            if let Some(split) = &spec.split {
+                if split.targets.iter().all(|t| t.weight == 0) {
+                    warnings.push(LintWarning::new(
+                        "spec.split.targets",
+                        "no target has a weight, requests go to spec.to",
+                    ));
+                }
+                if split.sticky.is_none() && split.targets.len() > 1 {
+                    warnings.push(LintWarning::new(
+                        "spec.split.sticky",
+                        "unset, clients may land on a different target on every request",
+                    ));
+                }
+            }
+            if !spec.to.include_request_uri && spec.path_map.is_some() {
+                warnings.push(LintWarning::new(
+                    "spec.to.includeRequestUri",
+                    "paths missing from the path map are permanently redirected to the bare target",
+                ));
+            }
+        }
+        RedirectMode::Page => {
+            if !spec.to.uri.is_empty() || spec.split.is_some() || !spec.overrides.is_empty() {
+                warnings.push(LintWarning::new(
+                    "spec.to",
+                    "targets are ignored in page mode",
+                ));
+            }
+            if spec.page.is_none() {
+                warnings.push(LintWarning::new(
+                    "spec.page",
+                    "page mode without a page answers 503",
+                ));
+            }
+            if !spec.links.is_empty() {
+                warnings.push(LintWarning::new("spec.links", "ignored in page mode"));
+            }
+        }
+        RedirectMode::Proxy => {
+            if !spec.links.is_empty() {
+                warnings.push(LintWarning::new("spec.links", "ignored in proxy mode"));
+            }
+            if spec.interstitial.as_ref().is_some_and(|i| i.enabled) {
+                warnings.push(LintWarning::new(
+                    "spec.interstitial",
+                    "ignored in proxy mode",
+                ));
+            }
+            if spec.split.as_ref().is_some_and(|s| s.sticky.is_some()) {
+                warnings.push(LintWarning::new(
+                    "spec.split.sticky",
+                    "assignment cookies are not set in proxy mode",
+                ));
+            }
+        }
+        RedirectMode::CanonicalHost => {
+            if !spec.to.uri.is_empty() {
+                warnings.push(LintWarning::new("spec.to", "ignored in canonicalHost mode"));
+            }
+            if spec.path_map.is_some() || !spec.short_links.is_empty() {
+                warnings.push(LintWarning::new(
+                    "spec.mode",
+                    "path maps and short links are ignored in canonicalHost mode",
+                ));
+            }
+        }
+    }
+
+    if spec.mode != RedirectMode::Page && spec.page.is_some() {
+        warnings.push(LintWarning::new("spec.page", "only used in page mode"));
+    }
+    let tls = &spec.ingress.tls;
+    if tls.cluster_issuer.is_some() && tls.issuer.is_some() {
+        warnings.push(LintWarning::new(
+            "spec.ingress.tls.issuer",
+            "set together with clusterIssuer, cert-manager picks one of them",
+        ));
+    }
+    if tls.issuer_ref.is_some() && (tls.cluster_issuer.is_some() || tls.issuer.is_some()) {
+        warnings.push(LintWarning::new(
+            "spec.ingress.tls.issuerRef",
+            "set together with an ingress-shim issuer, both manage the same Secret",
+        ));
+    }
+    if !tls.enabled
+        && (tls.cluster_issuer.is_some() || tls.issuer.is_some() || tls.issuer_ref.is_some())
+    {
+        warnings.push(LintWarning::new(
+            "spec.ingress.tls",
+            "issuers are unused while TLS is disabled",
+        ));
+    }
+    if !spec.ingress.tls.enabled && spec.ingress.tls.secret_name.is_some() {
+        warnings.push(LintWarning::new(
+            "spec.ingress.tls.secretName",
+            "unused while TLS is disabled",
+        ));
+    }
+    if !spec.ingress.enabled
+        && (spec.ingress.ingress_class_name.is_some()
+            || spec.ingress.annotations.is_some()
+            || spec.ingress.labels.is_some())
+    {
+        warnings.push(LintWarning::new(
+            "spec.ingress",
+            "settings are unused while the Ingress is disabled",
+        ));
+    }
+    if spec.ingress.namespace.is_some() && spec.ingress.same_namespace == Some(true) {
+        warnings.push(LintWarning::new(
+            "spec.ingress.sameNamespace",
+            "ignored, spec.ingress.namespace wins",
+        ));
+    }
+    if spec.ingress.shared && spec.ingress.namespace.is_some() {
+        warnings.push(LintWarning::new(
+            "spec.ingress.namespace",
+            "ignored, shared Ingresses are in the operator's namespace",
+        ));
+    }
+    if spec.ingress.shared
+        && spec
+            .ingress
+            .offload
+            .as_ref()
+            .is_some_and(|o| *o != IngressOffload::None)
+    {
+        warnings.push(LintWarning::new(
+            "spec.ingress.offload",
+            "ignored, shared Ingresses always route to the operator",
+        ));
+    }
+    if !spec.ingress.paths.is_empty() && !spec.match_.paths.is_empty() {
+        warnings.push(LintWarning::new(
+            "spec.ingress.paths",
+            "set together with spec.match.paths, the Ingress only routes spec.ingress.paths",
+        ));
+    }
+    if spec.ingress.enabled && spec.route.enabled {
+        warnings.push(LintWarning::new(
+            "spec.route",
+            "enabled together with spec.ingress, both claim the same hosts",
+        ));
+    }
+    if spec
+        .interstitial
+        .as_ref()
+        .is_some_and(|i| !i.enabled && i.config_map_name.is_some())
+    {
+        warnings.push(LintWarning::new(
+            "spec.interstitial.configMapName",
+            "unused while interstitials are disabled",
+        ));
+    }
+    if spec.ttl.as_deref().is_some_and(|t| ttl::parse(t).is_none()) {
+        warnings.push(LintWarning::new(
+            "spec.ttl",
+            "neither a duration like 30d nor a time, the Redirect never expires",
+        ));
+    }
+    let tarpit_patterns = [
+        (
+            "spec.tarpitMatch.userAgents",
+            &spec.tarpit_match.user_agents,
+        ),
+        ("spec.tarpitMatch.paths", &spec.tarpit_match.paths),
+    ];
+    for (field, patterns) in tarpit_patterns {
+        for message in pattern::invalid(patterns) {
+            warnings.push(LintWarning::new(
+                field,
+                format!("{message}, it never matches"),
+            ));
+        }
+    }
+    if !spec.paused && spec.pause.remove_ingress {
+        warnings.push(LintWarning::new(
+            "spec.pause",
+            "only used while spec.paused is set",
+        ));
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fields(spec: serde_json::Value) -> Vec<String> {
+        let spec: RedirectSpec = serde_json::from_value(spec).unwrap();
+        lint(&spec).into_iter().map(|w| w.field).collect()
+    }
+
+    #[test]
+    fn plain_redirects_are_fine() {
+        assert!(
+            fields(json!({
+                "hosts": ["old.example.com"],
+                "to": { "uri": "https://example.org" },
+                "ingress": {},
+            }))
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn warns_about_unused_settings() {
+        assert_eq!(
+            fields(json!({
+                "hosts": ["old.example.com", "not a host"],
+                "to": { "uri": "http://example.org" },
+                "ingress": { "tls": { "enabled": false, "secretName": "tls" } },
+                "overrides": [{ "host": "other.example.com", "to": { "uri": "https://example.net" } }],
+                "ttl": "one month",
+                "tarpitMatch": { "paths": ["(wp-admin"] },
+            })),
+            [
+                "spec.hosts",
+                "spec.overrides",
+                "spec.to.uri",
+                "spec.ingress.tls.secretName",
+                "spec.ttl",
+                "spec.tarpitMatch.paths",
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_mode_mismatches() {
+        assert_eq!(
+            fields(json!({
+                "hosts": ["old.example.com"],
+                "mode": "page",
+                "to": { "uri": "https://example.org" },
+                "ingress": {},
+            })),
+            ["spec.to", "spec.page"]
+        );
+        assert_eq!(
+            fields(json!({
+                "hosts": ["example.com", "www.example.com"],
+                "mode": "canonicalHost",
+                "canonicalHost": "example.com",
+                "to": { "uri": "https://example.org" },
+                "ingress": {},
+            })),
+            ["spec.to"]
+        );
+        assert_eq!(
+            fields(json!({
+                "hosts": ["example.com", "www.example.com"],
+                "mode": "canonicalHost",
+                "canonicalHost": "example.com",
+                "ingress": {},
+            })),
+            Vec::<String>::new()
+        );
+    }
+}
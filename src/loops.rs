@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::http::Uri;
+
+use crate::{host, types::Redirect};
+
+/// Give up following redirect chains after this many hops.
+const MAX_CHAIN_LENGTH: usize = 16;
+
+/// Condition type reporting whether the target leads back to managed hosts.
+pub const CONDITION_LOOP_DETECTED: &str = "LoopDetected";
+
+/// The normalized host of a target URI.
+fn target_host(uri: &str) -> Option<String> {
+    let uri: Uri = uri.parse().ok()?;
+    host::normalize(uri.host()?).ok()
+}
+
+/// Follows the targets of all hosts through the Redirects serving their hosts.
+///
+/// Returns the chain of hosts if it comes back to a host already visited.
+pub fn detect(redirect: &Redirect, redirects: &[Arc<Redirect>]) -> Option<Vec<String>> {
+    let (hosts, _) = host::served_hosts(&redirect.spec);
+    hosts
+        .into_iter()
+        .find_map(|start| follow(redirect, start, redirects))
+}
+
+fn follow(redirect: &Redirect, start: String, redirects: &[Arc<Redirect>]) -> Option<Vec<String>> {
+    let mut visited = BTreeSet::from([start.clone()]);
+    let mut chain = vec![start.clone()];
+    let mut current = redirect;
+    let mut current_host = start;
+
+    for _ in 0..MAX_CHAIN_LENGTH {
+        let target = match host::canonical_host(&current.spec) {
+            Some(canonical) => canonical,
+            None => target_host(&host::target_for(&current.spec, &current_host).uri)?,
+        };
+        chain.push(target.clone());
+        if !visited.insert(target.clone()) {
+            return Some(chain);
+        }
+
+        current = redirects.iter().find(|r| host::serves(&r.spec, &target))?;
+        current_host = target;
+    }
+    None
+}
+
+/// Whether the controller found a loop for this Redirect.
+pub fn is_looping(redirect: &Redirect) -> bool {
+    redirect.status.as_ref().is_some_and(|status| {
+        status
+            .conditions
+            .iter()
+            .any(|c| c.type_ == CONDITION_LOOP_DETECTED && c.status == "True")
+    })
+}
+
+/// Hosts of a chain, for messages.
+pub fn describe(chain: &[String]) -> String {
+    chain.join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::redirect_from_json;
+
+    fn redirecting(hosts: &[&str], to: &str) -> Arc<Redirect> {
+        Arc::new(redirect_from_json(json!({
+            "metadata": { "name": hosts[0] },
+            "spec": { "hosts": hosts, "to": { "uri": to }, "ingress": {} },
+        })))
+    }
+
+    #[test]
+    fn detects_cycles_through_other_redirects() {
+        let a = redirecting(&["a.example"], "https://b.example/landing");
+        let b = redirecting(&["b.example"], "https://A.example/");
+        let redirects = [a.clone(), b];
+        assert_eq!(
+            detect(&a, &redirects).map(|chain| describe(&chain)),
+            Some("a.example -> b.example -> a.example".to_string())
+        );
+    }
+
+    #[test]
+    fn detects_redirects_to_themselves() {
+        let a = redirecting(&["a.example", "www.a.example"], "https://a.example/");
+        assert!(detect(&a, &[a.clone()]).is_some());
+    }
+
+    #[test]
+    fn chains_leaving_managed_hosts_do_not_loop() {
+        let a = redirecting(&["a.example"], "https://b.example/");
+        let b = redirecting(&["b.example"], "https://c.example/");
+        assert_eq!(detect(&a, &[a.clone(), b]), None);
+    }
+}
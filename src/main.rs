@@ -1,48 +1,113 @@
+mod analytics;
+mod certificate;
+mod cli;
+mod client;
+mod cloudflare;
+mod contour;
 mod controller;
+mod crd;
+mod defaults;
+mod dns;
+mod dnsverify;
+mod dryrun;
+mod edge;
+mod emissary;
+mod export;
+mod external_dns;
+mod gc;
+mod generator;
+mod hash;
+mod host;
+mod hostpolicy;
+mod http_error;
+mod import;
+mod ingressclass;
+mod interstitial;
+mod istio;
+mod kong;
+mod links;
+mod lint;
+mod loops;
+mod matcher;
 mod metrics;
+mod multicluster;
+mod offload;
+mod params;
+mod pathmap;
+mod pattern;
+mod probe;
+mod proxy;
+mod requeue;
+mod resync;
+mod route;
+mod route53;
+mod shard;
+mod shared;
+mod shortlink;
+mod shutdown;
+mod split;
+mod target;
+mod tarpit;
+mod tls;
+mod trace;
+mod ttl;
 mod types;
+mod watch;
+mod webhook;
+mod webhookcert;
+mod wildcard;
 
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use anyhow::Context as _;
 use axum::{
-    Router,
+    Json, Router,
     body::Body,
-    extract::{Path, State},
-    http::{StatusCode, header},
-    response::{IntoResponse, Redirect, Response},
-    routing::get,
+    extract::{ConnectInfo, FromRef, Path, Query, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, Uri, header},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{any, get, post},
 };
 use axum_extra::{TypedHeader, headers::Host};
-use kube::runtime::reflector;
+use futures::FutureExt;
+use kube::{ResourceExt, runtime::reflector};
 use prometheus_client::encoding::text::encode;
-use tokio::signal::{self, unix::SignalKind};
+use serde::Deserialize;
+use tokio::sync::oneshot;
 use tracing::{error, info};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::metrics::Metrics;
+use crate::{
+    analytics::Analytics, edge::EdgeRules, http_error::HttpError, metrics::Metrics,
+    pathmap::PathMaps, proxy::Proxy, target::TargetPolicy, tarpit::Tarpit, trace::Trace,
+    types::RedirectMode,
+};
 
-#[derive(Clone)]
+#[derive(Clone, FromRef)]
 struct AppState {
     store: reflector::Store<types::Redirect>,
+    hosts: host::HostIndex,
     metrics: Arc<Metrics>,
-}
-
-async fn shutdown_signal() {
-    let ctrl_c = async {
-        signal::ctrl_c()
-            .await
-            .expect("failed to install Ctrl+C handler")
-    };
-    let terminate = async {
-        signal::unix::signal(SignalKind::terminate())
-            .expect("failed to install signal handler")
-            .recv()
-            .await;
-    };
-    tokio::select! {
-        _ = ctrl_c => {},
-        _ = terminate => {}
-    }
+    path_maps: PathMaps,
+    /// answer Redirects the controller found looping with 508
+    refuse_loops: bool,
+    /// enables decision traces for requests sending it in `x-redirect-debug`
+    debug_secret: Option<Arc<str>>,
+    proxy: Proxy,
+    /// mirrors sampled redirect events if configured
+    analytics: Option<Analytics>,
+    tarpit: Tarpit,
+    /// domains Redirects may lead to
+    target_policy: Arc<TargetPolicy>,
+    /// HTML answered for unknown hosts and paths, from `NOT_FOUND_PAGE_FILE`
+    not_found_page: Option<Arc<str>>,
+    /// enables `POST /admin/reconcile` and `GET /admin/export` for requests sending it as
+    /// bearer token
+    admin_token: Option<Arc<str>>,
+    reconcile_all: resync::ReconcileAll,
 }
 
 #[tokio::main]
@@ -57,79 +122,547 @@ async fn main() -> anyhow::Result<()> {
         .with(logger)
         .init();
 
-    let kube_client = kube::Client::try_default().await?;
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|a| a != dryrun::FLAG)
+        .collect();
+    if let Some((command, args)) = args.split_first() {
+        return cli::run(command, args).await;
+    }
+
+    let kube_client = client::from_env().await?;
+    if crd::enabled() && watch::namespaced_only()? {
+        anyhow::bail!("INSTALL_CRDS needs cluster-wide access, not NAMESPACED_RBAC");
+    }
+    if crd::enabled() && dryrun::enabled() {
+        info!("dry run: not installing CRDs");
+    } else if crd::enabled() {
+        crd::install(kube_client.clone()).await?;
+    }
     let leader_handle = controller::setup_leader_election(kube_client.clone()).await?;
-    let (reader, metrics, controller) =
-        controller::get_controller(kube_client, leader_handle.state()).await?;
+    let reconcile_all = resync::ReconcileAll::default();
+    reconcile_all.on_sighup()?;
+    let (stop_controller, controller_stopped) = oneshot::channel();
+    let (reader, hosts, metrics, path_maps, target_policy, host_policies, mut controller) =
+        controller::get_controller(
+            kube_client.clone(),
+            leader_handle.state(),
+            reconcile_all.clone(),
+            controller_stopped,
+        )
+        .await?;
 
+    let analytics = Analytics::from_env(&metrics.http)?;
     let app_state = AppState {
+        hosts,
         store: reader,
-        metrics: metrics.clone(),
+        metrics,
+        path_maps,
+        refuse_loops: std::env::var("REFUSE_REDIRECT_LOOPS").is_ok_and(|v| v == "true"),
+        debug_secret: std::env::var("DEBUG_TRACE_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(Arc::from),
+        proxy: Proxy::from_env()?,
+        analytics,
+        tarpit: Tarpit::from_env()?,
+        target_policy,
+        not_found_page: match std::env::var("NOT_FOUND_PAGE_FILE") {
+            Ok(file) => Some(Arc::from(
+                std::fs::read_to_string(&file)
+                    .with_context(|| format!("cannot read NOT_FOUND_PAGE_FILE {file}"))?,
+            )),
+            Err(_) => None,
+        },
+        admin_token: std::env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(Arc::from),
+        reconcile_all,
     };
 
     let app = Router::new()
-        .route("/", get(redirect))
-        .route("/{*path}", get(redirect))
-        .with_state(app_state);
+        .route("/", any(redirect))
+        .route("/{*path}", any(redirect))
+        .with_state(app_state.clone());
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await.unwrap();
-    let webserver = axum::serve(listener, app).with_graceful_shutdown(shutdown_signal());
+    let (stop_webserver, webserver_stopped) = oneshot::channel::<()>();
+    let webserver = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async {
+        let _ = webserver_stopped.await;
+    });
+    let mut webserver = tokio::spawn(async move { webserver.await });
+
+    let webhook_state = webhook::Webhook {
+        hosts: app_state.hosts.clone(),
+        path_maps: app_state.path_maps.clone(),
+        target_policy: app_state.target_policy.clone(),
+        host_policies,
+    };
 
     let metrics_app = Router::new()
-        .route("/ready", get(get_healthz))
+        .route("/ready", get(get_ready))
         .route("/healthz", get(get_healthz))
         .route("/metrics", get(get_metrics))
-        .with_state(metrics);
+        .route("/edge/rules", get(get_edge_rules))
+        .route("/admin/reconcile", post(post_admin_reconcile))
+        .route("/admin/export", get(get_admin_export))
+        .with_state(app_state);
     let metrics_listener = tokio::net::TcpListener::bind("0.0.0.0:9880").await?;
-    let metrics_server =
-        axum::serve(metrics_listener, metrics_app).with_graceful_shutdown(shutdown_signal());
+    let (stop_metrics_server, metrics_server_stopped) = oneshot::channel::<()>();
+    let metrics_server = axum::serve(metrics_listener, metrics_app).with_graceful_shutdown(async {
+        let _ = metrics_server_stopped.await;
+    });
+    let mut metrics_server = tokio::spawn(async move { metrics_server.await });
 
-    let (_, r1, r2) = tokio::join!(controller, webserver, metrics_server);
-    r1?;
-    r2?;
+    let (stop_webhook, webhook_stopped) = oneshot::channel::<()>();
+    let mut webhook_server = tokio::spawn(webhook::serve(
+        webhook_state,
+        webhookcert::SelfSigned::from_env(kube_client),
+        webhook_stopped,
+    ));
 
-    leader_handle.shutdown().await?;
+    tokio::select! {
+        _ = shutdown::signal() => info!("shutdown: received signal"),
+        res = &mut webserver => error!("redirect server exited: {:?}", res),
+        res = &mut metrics_server => error!("metrics server exited: {:?}", res),
+        res = &mut webhook_server => error!("webhook server exited: {:?}", res),
+        res = &mut controller => error!("controller exited: {:?}", res),
+    }
 
-    Ok(())
-}
+    // stop accepting redirect requests and let in-flight ones finish
+    let _ = stop_webserver.send(());
+    if shutdown::phase(
+        "draining redirect server",
+        Some(shutdown::drain_timeout()),
+        &mut webserver,
+    )
+    .await
+    .is_none()
+    {
+        webserver.abort();
+    }
+
+    let _ = stop_webhook.send(());
+    if shutdown::phase("stopping webhook server", None, &mut webhook_server)
+        .await
+        .is_none()
+    {
+        webhook_server.abort();
+    }
 
-#[derive(Debug)]
-struct NotFoundError {}
+    // let running reconciles finish
+    let _ = stop_controller.send(());
+    if shutdown::phase("stopping controllers", None, &mut controller)
+        .await
+        .is_none()
+    {
+        controller.abort();
+    }
+
+    // hand over leadership before the pod is gone
+    if let Some(Err(e)) = shutdown::phase("releasing lease", None, leader_handle.shutdown()).await {
+        error!("shutdown: cannot release lease: {:?}", e);
+    }
 
-impl IntoResponse for NotFoundError {
-    fn into_response(self) -> Response {
-        todo!()
+    // metrics and probes stay available until the end
+    let _ = stop_metrics_server.send(());
+    if shutdown::phase("stopping metrics server", None, &mut metrics_server)
+        .await
+        .is_none()
+    {
+        metrics_server.abort();
     }
+
+    Ok(())
 }
 
 #[axum::debug_handler]
 async fn redirect(
-    TypedHeader(host): TypedHeader<Host>,
+    TypedHeader(host_header): TypedHeader<Host>,
     path: Option<Path<String>>,
+    request_uri: Uri,
+    method: Method,
+    headers: HeaderMap,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     State(app_state): State<AppState>,
-) -> Result<Response, NotFoundError> {
-    let host = host.to_string();
-    let host = host.trim_end_matches('.');
-    let p = |redirect: &types::Redirect| redirect.spec.hosts.contains(host);
-    if let Some(redirect) = app_state.store.find(p) {
-        let to = &redirect.spec.to;
-        let uri = if to.include_request_uri {
-            let path = path.map(|p| p.0).unwrap_or("".to_string());
+    body: Body,
+) -> Response {
+    let _timer = app_state.metrics.http.measure();
+    // every host would be unknown before the initial sync
+    if !synced(&app_state.store) {
+        app_state.metrics.http.set_error(&HttpError::NotReady);
+        return HttpError::NotReady.respond(&headers);
+    }
+    let mut trace = Trace::from_request(app_state.debug_secret.as_deref(), &headers);
+    let mut delay = None;
+    let resolved = resolve(
+        &host_header,
+        path.map(|p| p.0),
+        &request_uri,
+        &headers,
+        &app_state,
+        &mut trace,
+        &mut delay,
+    );
+    // too many requests are being tarpitted already
+    if let Some(delay) = delay
+        && !app_state.tarpit.wait(delay).await
+    {
+        app_state.metrics.http.set_error(&HttpError::RateLimited);
+        return trace.attach(HttpError::RateLimited.respond(&headers));
+    }
+    let response = match resolved {
+        Ok(Outcome::Respond(response)) => response,
+        Ok(Outcome::Proxy(target)) => {
+            app_state
+                .proxy
+                .forward(
+                    &target,
+                    host_header.hostname(),
+                    peer.ip(),
+                    method,
+                    &headers,
+                    body,
+                )
+                .await
+        }
+        Err(e) => {
+            app_state.metrics.http.set_error(&e);
+            e.with_default_page(app_state.not_found_page.as_deref(), host_header.hostname())
+                .respond(&headers)
+        }
+    };
+    trace.attach(response)
+}
+
+/// What to answer a request with.
+enum Outcome {
+    Respond(Response),
+    /// forward the request to this URI
+    Proxy(String),
+}
+
+impl From<Response> for Outcome {
+    fn from(response: Response) -> Self {
+        Self::Respond(response)
+    }
+}
+
+fn resolve(
+    host_header: &Host,
+    path: Option<String>,
+    request_uri: &Uri,
+    headers: &HeaderMap,
+    app_state: &AppState,
+    trace: &mut Trace,
+    delay: &mut Option<Duration>,
+) -> Result<Outcome, HttpError> {
+    let Ok(host) = host::normalize(host_header.hostname()) else {
+        error!("invalid host {}", host_header.hostname());
+        app_state.metrics.http.set_failure(host_header.hostname());
+        trace.step(|| "host=invalid".to_string());
+        return Err(HttpError::BadHost(host_header.hostname().to_string()));
+    };
+    let host = host.as_str();
+    trace.step(|| format!("host={host}"));
+
+    let candidates = app_state.hosts.find(host);
+    trace.step(|| format!("candidates={}", candidates.len()));
+    // what answers, even another candidate, depends on the conditions of all of them
+    let vary: Vec<&str> = candidates
+        .iter()
+        .flat_map(|r| matcher::varies_on(&r.spec.match_))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let redirect = candidates
+        .iter()
+        .find(|r| matcher::matches(&r.spec.match_, request_uri.path(), headers))
+        .cloned();
+    if let Some(redirect) = redirect {
+        trace.step(|| {
+            format!(
+                "redirect={}/{}",
+                redirect.namespace().unwrap_or_default(),
+                redirect.name_any()
+            )
+        });
+        if !redirect.spec.match_.is_empty() {
+            trace.step(|| "match=conditions".to_string());
+        }
+
+        *delay = app_state
+            .tarpit
+            .delay_for(&redirect, request_uri.path(), headers);
+        if let Some(delay) = delay {
+            trace.step(|| format!("tarpit={}ms", delay.as_millis()));
+        }
+
+        if ttl::is_expired(&redirect) {
+            info!("not redirecting expired {}", host);
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "expired".to_string());
+            return Err(HttpError::NotFound {
+                status: StatusCode::GONE,
+                page: None,
+            });
+        }
+
+        if redirect.spec.paused {
+            info!("not redirecting paused {}", host);
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "paused".to_string());
+            let status = StatusCode::from_u16(redirect.spec.pause.status_code)
+                .unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+            return Err(HttpError::PolicyDenied {
+                status,
+                reason: "redirect paused".to_string(),
+            });
+        }
+
+        if app_state.refuse_loops && loops::is_looping(&redirect) {
+            error!("refusing looping redirect for {}", host);
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "loop=refused".to_string());
+            return Err(HttpError::PolicyDenied {
+                status: StatusCode::LOOP_DETECTED,
+                reason: "redirect loop detected".to_string(),
+            });
+        }
+
+        if target::is_denied(&redirect) {
+            error!("refusing denied target for {}", host);
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "target=denied".to_string());
+            return Err(HttpError::PolicyDenied {
+                status: StatusCode::FORBIDDEN,
+                reason: "target not allowed".to_string(),
+            });
+        }
+
+        if redirect.spec.mode == RedirectMode::Page {
+            trace.step(|| "response=page".to_string());
+            return match app_state.path_maps.page(&redirect) {
+                Some((status, html)) => {
+                    app_state.metrics.http.set_request(host);
+                    Ok((status, Html(html)).into_response().into())
+                }
+                None => {
+                    error!("no page for {}", host);
+                    app_state.metrics.http.set_failure(host);
+                    Err(HttpError::InternalError(format!("no page for {host}")))
+                }
+            };
+        }
+
+        let overridden = host::override_for(&redirect.spec, host);
+        // answers differing between clients must not be cached
+        let mut per_client = !vary.is_empty();
+        let (to, set_cookie) = match (overridden, &redirect.spec.split) {
+            (None, Some(split)) => match split::choose(split, headers) {
+                Some((to, set_cookie)) => {
+                    trace.step(|| format!("target=split uri={}", to.uri));
+                    per_client = true;
+                    (to, set_cookie)
+                }
+                None => {
+                    trace.step(|| "target=to split=no-weight".to_string());
+                    (&redirect.spec.to, None)
+                }
+            },
+            (Some(to), _) => {
+                trace.step(|| "target=override".to_string());
+                (to, None)
+            }
+            (None, None) => {
+                trace.step(|| "target=to".to_string());
+                (&redirect.spec.to, None)
+            }
+        };
+        if let Err(e) = target::check(to, &redirect.spec.mode) {
+            error!("refusing invalid target for {}: {}", host, e);
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "target=invalid".to_string());
+            return Err(HttpError::PolicyDenied {
+                status: StatusCode::FORBIDDEN,
+                reason: "target not allowed".to_string(),
+            });
+        }
+        let short_link = path
+            .as_deref()
+            .and_then(|p| shortlink::resolve(&redirect, p));
+        let request_path = format!("/{}", path.as_deref().unwrap_or(""));
+        let uri = if let Some(canonical) = host::canonical_host(&redirect.spec) {
+            let scheme = matcher::request_scheme(headers);
+            let path_and_query = request_uri
+                .path_and_query()
+                .map(|p| p.as_str())
+                .unwrap_or("/");
+            trace.step(|| "rule=canonical-host".to_string());
+            format!("{scheme}://{canonical}{path_and_query}")
+        } else if let Some(target) = short_link {
+            trace.step(|| "rule=short-link".to_string());
+            target.to_string()
+        } else if let Some(target) = app_state.path_maps.lookup(&redirect, &request_path) {
+            trace.step(|| format!("rule=path-map path={request_path}"));
+            if target.is_empty() {
+                info!("{}{} has no target", host, request_path);
+                app_state.metrics.http.set_failure(host);
+                trace.step(|| "gone".to_string());
+                return Err(match app_state.path_maps.not_found_page(&redirect) {
+                    Some((status, page)) => HttpError::NotFound {
+                        status,
+                        page: Some(page),
+                    },
+                    None => HttpError::not_found(),
+                });
+            }
+            target
+        } else if to.include_request_uri {
+            trace.step(|| "rule=target rewrite=append-request-uri".to_string());
+            let path = path.unwrap_or("".to_string());
             format!("{}/{}", to.uri, path)
         } else {
+            trace.step(|| "rule=target".to_string());
             to.uri.clone()
         };
 
-        info!("redirecting {} to {}", host, uri);
+        let uri = if redirect.spec.mode == RedirectMode::Redirect && !to.append_params.is_empty() {
+            trace.step(|| "rewrite=append-params".to_string());
+            params::append(&uri, &to.append_params)
+        } else {
+            uri
+        };
+
+        if !app_state.target_policy.allows(&uri) {
+            error!(
+                "refusing target {} for {} outside the allowed domains",
+                uri, host
+            );
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "target=denied".to_string());
+            return Err(HttpError::PolicyDenied {
+                status: StatusCode::FORBIDDEN,
+                reason: "target not allowed".to_string(),
+            });
+        }
+
+        if redirect.spec.mode == RedirectMode::Proxy && !app_state.proxy.enabled() {
+            error!(
+                "refusing to proxy {} to {}, proxy mode is not allowed",
+                host, uri
+            );
+            app_state.metrics.http.set_failure(host);
+            trace.step(|| "proxy=denied".to_string());
+            return Err(HttpError::PolicyDenied {
+                status: StatusCode::FORBIDDEN,
+                reason: "proxy mode not allowed".to_string(),
+            });
+        }
+
         app_state.metrics.http.set_request(host);
-        Ok(Redirect::permanent(&uri).into_response())
+        if let Some(analytics) = &app_state.analytics {
+            analytics.record(host, request_uri.path(), headers);
+        }
+        if redirect.spec.mode == RedirectMode::Proxy {
+            let uri = match request_uri.query() {
+                Some(query) if to.include_request_uri => format!("{uri}?{query}"),
+                _ => uri,
+            };
+            info!("proxying {} to {}", host, uri);
+            trace.step(|| "response=proxy".to_string());
+            return Ok(Outcome::Proxy(uri));
+        }
+        if let Some(interstitial) = redirect.spec.interstitial.as_ref().filter(|i| i.enabled) {
+            let templates = interstitial
+                .config_map_name
+                .as_deref()
+                .zip(redirect.namespace())
+                .and_then(|(name, ns)| app_state.path_maps.data(&ns, name))
+                .unwrap_or_default();
+            let languages = headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .map(interstitial::accepted_languages)
+                .unwrap_or_default();
+            let template = interstitial::select_template(&templates, &languages);
+
+            info!("serving interstitial for {} to {}", host, uri);
+            trace.step(|| "response=interstitial".to_string());
+            let mut response = Html(interstitial::render(template, &uri, host)).into_response();
+            if per_client {
+                uncacheable(&mut response, &vary);
+            }
+            if let Some(cookie) = set_cookie {
+                response.headers_mut().append(header::SET_COOKIE, cookie);
+            }
+            if let Some(link) = links::header_value(&redirect.spec.links, &uri) {
+                response.headers_mut().insert(header::LINK, link);
+            }
+            return Ok(response.into());
+        }
+
+        info!("redirecting {} to {}", host, uri);
+        let mut response = redirect_response(&uri, per_client, &vary);
+        trace.step(|| format!("response={}", response.status().as_u16()));
+        if let Some(cookie) = set_cookie {
+            response.headers_mut().append(header::SET_COOKIE, cookie);
+        }
+        if let Some(link) = links::header_value(&redirect.spec.links, &uri) {
+            response.headers_mut().insert(header::LINK, link);
+        }
+        Ok(response.into())
     } else {
         error!("no redirect found for {}", host);
         app_state.metrics.http.set_failure(host);
-        Err(NotFoundError {})
+        trace.step(|| "redirect=none".to_string());
+        if let Some(status) = candidates
+            .iter()
+            .find_map(|r| r.spec.match_.unmatched_status_code)
+            .and_then(|s| StatusCode::from_u16(s).ok())
+        {
+            return Err(HttpError::NotFound { status, page: None });
+        }
+        Err(HttpError::not_found())
+    }
+}
+
+/// `Cache-Control` of answers that differ between clients.
+const PER_CLIENT_CACHE_CONTROL: &str = "private, no-cache";
+
+/// A permanent redirect to `uri`, or a temporary and uncached one if it differs between
+/// clients, e.g. for split targets or conditions on the request headers in `vary`, so that
+/// browsers ask again.
+fn redirect_response(uri: &str, per_client: bool, vary: &[&str]) -> Response {
+    if !per_client {
+        return Redirect::permanent(uri).into_response();
+    }
+    let mut response = Redirect::temporary(uri).into_response();
+    uncacheable(&mut response, vary);
+    response
+}
+
+/// Keeps caches from answering other clients with `response`.
+fn uncacheable(response: &mut Response, vary: &[&str]) {
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(PER_CLIENT_CACHE_CONTROL),
+    );
+    if let Ok(vary) = HeaderValue::from_str(&vary.join(", "))
+        && !vary.is_empty()
+    {
+        headers.insert(header::VARY, vary);
     }
 }
 
-async fn get_metrics(State(metrics): State<Arc<Metrics>>) -> Response {
+async fn get_metrics(State(app_state): State<AppState>) -> Response {
+    let metrics = &app_state.metrics;
+    metrics.objects.update(&app_state.store.state());
     let mut buffer = String::new();
     encode(&mut buffer, &metrics.registry).unwrap();
 
@@ -143,7 +676,156 @@ async fn get_metrics(State(metrics): State<Arc<Metrics>>) -> Response {
         .unwrap()
 }
 
-// this should check the reconcile loop, etc.
+#[derive(Debug, Deserialize)]
+struct EdgeRulesQuery {
+    host: Option<String>,
+}
+
+/// Serves the redirect rules as JSON for edge workers, with an ETag for cheap polling.
+async fn get_edge_rules(
+    State(app_state): State<AppState>,
+    Query(query): Query<EdgeRulesQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let host = match query.host.as_deref().map(host::normalize) {
+        Some(Ok(host)) => Some(host),
+        Some(Err(_)) => return StatusCode::BAD_REQUEST.into_response(),
+        None => None,
+    };
+    let rules = EdgeRules::collect(&app_state.store, &app_state.path_maps, host.as_deref());
+    let etag = rules.etag();
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        });
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    (
+        [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        Json(rules),
+    )
+        .into_response()
+}
+
+/// Always healthy while answering, the process exits once the controller or a server stops.
 async fn get_healthz() -> Response {
     "OK\n".into_response()
 }
+
+/// Whether the Redirects were listed at least once.
+fn synced(store: &reflector::Store<types::Redirect>) -> bool {
+    store
+        .wait_until_ready()
+        .now_or_never()
+        .is_some_and(|res| res.is_ok())
+}
+
+/// Refuses requests without the bearer token in `ADMIN_TOKEN`, hiding the endpoints if unset.
+fn check_admin_token(app_state: &AppState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(token) = &app_state.admin_token else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|given| trace::secret_matches(token, given.as_bytes()));
+    if !authorized {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(())
+}
+
+/// Reconciles all objects, for the bearer token in `ADMIN_TOKEN`.
+async fn post_admin_reconcile(State(app_state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(status) = check_admin_token(&app_state, &headers) {
+        return status.into_response();
+    }
+    info!("reconciling everything on request");
+    app_state.reconcile_all.trigger();
+    (StatusCode::ACCEPTED, "reconciling\n").into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    format: Option<String>,
+}
+
+/// Serves the redirect table as nginx or Caddy configuration or JSON, for the bearer token in
+/// `ADMIN_TOKEN`.
+async fn get_admin_export(
+    State(app_state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(status) = check_admin_token(&app_state, &headers) {
+        return status.into_response();
+    }
+    let format: export::Format = match query.format.as_deref().unwrap_or("nginx").parse() {
+        Ok(format) => format,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("{e}\n")).into_response(),
+    };
+    let rules = EdgeRules::collect(&app_state.store, &app_state.path_maps, None);
+    (
+        [(header::CONTENT_TYPE, format.content_type())],
+        export::render(&rules, format),
+    )
+        .into_response()
+}
+
+/// Keeps the pod out of the Service until it knows all Redirects.
+async fn get_ready(State(app_state): State<AppState>) -> Response {
+    if synced(&app_state.store) {
+        "OK\n".into_response()
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "waiting for Redirects\n").into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_redirects_are_permanent() {
+        let response = redirect_response("https://example.org/", false, &[]);
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers()[header::LOCATION], "https://example.org/");
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+    }
+
+    #[test]
+    fn per_client_redirects_are_temporary_and_uncached() {
+        let response = redirect_response("https://b.example.org/", true, &[]);
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://b.example.org/"
+        );
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            PER_CLIENT_CACHE_CONTROL
+        );
+        assert!(response.headers().get(header::VARY).is_none());
+    }
+
+    #[test]
+    fn cookie_conditioned_redirects_vary_on_cookies() {
+        let response = redirect_response("https://beta.example.org/", true, &["Cookie"]);
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(response.headers()[header::VARY], "Cookie");
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            PER_CLIENT_CACHE_CONTROL
+        );
+    }
+}
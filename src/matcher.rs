@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use axum::http::{HeaderMap, header};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use regex::Regex;
+
+use crate::{
+    controller::condition,
+    pattern,
+    types::{PathMatchType, RedirectCookieMatch, RedirectMatch, RedirectPathMatch, SchemeMatch},
+};
+
+/// Condition type reporting whether the request conditions are usable.
+pub const CONDITION_MATCH_VALID: &str = "MatchValid";
+
+/// Cookies sent with a request, the first of duplicate names wins.
+pub fn cookies(headers: &HeaderMap) -> HashMap<&str, &str> {
+    let mut cookies = HashMap::new();
+    for pair in headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+    {
+        if let Some((name, value)) = pair.trim().split_once('=') {
+            cookies
+                .entry(name.trim())
+                .or_insert(value.trim().trim_matches('"'));
+        }
+    }
+    cookies
+}
+
+/// Scheme of the original request, from `Forwarded` or `X-Forwarded-Proto`.
+///
+/// Requests without either reached the operator directly over plain HTTP.
+pub fn request_scheme(headers: &HeaderMap) -> String {
+    let forwarded = headers
+        .get(header::FORWARDED)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            // the first proxy's element
+            v.split(',').next()?.split(';').find_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                name.eq_ignore_ascii_case("proto")
+                    .then(|| value.trim_matches('"').to_string())
+            })
+        });
+    let x_forwarded_proto = || {
+        headers
+            .get("x-forwarded-proto")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_string())
+    };
+    forwarded
+        .or_else(x_forwarded_proto)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or("http".to_string())
+}
+
+/// Anchors a pattern so that it has to match the whole value.
+///
+/// Compiled once per pattern, requests only look it up.
+fn value_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    pattern::compile(&format!("^(?:{pattern})$"))
+}
+
+fn cookie_matches(rule: &RedirectCookieMatch, cookies: &HashMap<&str, &str>) -> bool {
+    let found = cookies.get(rule.name.as_str()).is_some_and(|value| {
+        rule.value.as_deref().is_none_or(|v| v == *value)
+            && rule
+                .regex
+                .as_deref()
+                .is_none_or(|r| value_regex(r).is_ok_and(|r| r.is_match(value)))
+    });
+    found != rule.absent
+}
+
+/// Whether `path` matches, prefixes match per path element.
+fn path_matches(rule: &RedirectPathMatch, path: &str) -> bool {
+    match rule.path_type {
+        PathMatchType::Exact => path == rule.path,
+        PathMatchType::Prefix => {
+            let prefix = rule.path.trim_end_matches('/');
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        }
+        PathMatchType::Regex => value_regex(&rule.path).is_ok_and(|r| r.is_match(path)),
+    }
+}
+
+/// Whether a request for `path` with `headers` meets all conditions.
+pub fn matches(rules: &RedirectMatch, path: &str, headers: &HeaderMap) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+    if !rules.paths.is_empty() && !rules.paths.iter().any(|rule| path_matches(rule, path)) {
+        return false;
+    }
+    let scheme_matches = match rules.scheme {
+        SchemeMatch::Any => true,
+        SchemeMatch::Http => request_scheme(headers) == "http",
+        SchemeMatch::Https => request_scheme(headers) == "https",
+    };
+    if !scheme_matches {
+        return false;
+    }
+    let cookies = cookies(headers);
+    rules
+        .cookies
+        .iter()
+        .all(|rule| cookie_matches(rule, &cookies))
+}
+
+/// Request headers the conditions depend on besides the URL, for `Vary`.
+pub fn varies_on(rules: &RedirectMatch) -> Vec<&'static str> {
+    let mut headers = Vec::new();
+    if !rules.cookies.is_empty() {
+        headers.push("Cookie");
+    }
+    if rules.scheme != SchemeMatch::Any {
+        headers.extend(["Forwarded", "X-Forwarded-Proto"]);
+    }
+    headers
+}
+
+/// Checks the conditions for invalid regular expressions.
+pub fn check(rules: &RedirectMatch) -> Condition {
+    let cookies = rules.cookies.iter().filter_map(|rule| {
+        let pattern = rule.regex.as_deref()?;
+        value_regex(pattern)
+            .err()
+            .map(|e| format!("cookie {}: {}", rule.name, e))
+    });
+    let paths = rules
+        .paths
+        .iter()
+        .filter(|rule| rule.path_type == PathMatchType::Regex)
+        .filter_map(|rule| {
+            value_regex(&rule.path)
+                .err()
+                .map(|e| format!("path {}: {}", rule.path, e))
+        });
+    let invalid: Vec<String> = cookies.chain(paths).collect();
+
+    if invalid.is_empty() {
+        condition(
+            CONDITION_MATCH_VALID,
+            true,
+            "MatchValid",
+            "all conditions are valid",
+        )
+    } else {
+        condition(
+            CONDITION_MATCH_VALID,
+            false,
+            "InvalidRegex",
+            format!("never matching: {}", invalid.join("; ")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str, path_type: PathMatchType) -> RedirectPathMatch {
+        RedirectPathMatch {
+            path: path.to_string(),
+            path_type,
+        }
+    }
+
+    fn with_cookie(cookie: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, cookie.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn prefixes_match_per_path_element() {
+        let rule = path("/shop/", PathMatchType::Prefix);
+        assert!(path_matches(&rule, "/shop"));
+        assert!(path_matches(&rule, "/shop/cart"));
+        assert!(!path_matches(&rule, "/shopping"));
+    }
+
+    #[test]
+    fn exact_and_regex_paths_match_whole_paths() {
+        assert!(path_matches(&path("/a", PathMatchType::Exact), "/a"));
+        assert!(!path_matches(&path("/a", PathMatchType::Exact), "/a/"));
+        let rule = path("/item/[0-9]+", PathMatchType::Regex);
+        assert!(path_matches(&rule, "/item/42"));
+        assert!(!path_matches(&rule, "/item/42/edit"));
+        assert!(!path_matches(&path("/[", PathMatchType::Regex), "/["));
+    }
+
+    #[test]
+    fn parses_cookies() {
+        let mut headers = with_cookie("a=1; b=\"two\"; a=3");
+        headers.append(header::COOKIE, "c = 4".parse().unwrap());
+        let cookies = cookies(&headers);
+        assert_eq!(cookies.get("a"), Some(&"1"));
+        assert_eq!(cookies.get("b"), Some(&"two"));
+        assert_eq!(cookies.get("c"), Some(&"4"));
+    }
+
+    #[test]
+    fn reads_the_scheme_from_forwarded_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_scheme(&headers), "http");
+        headers.insert("x-forwarded-proto", "HTTPS, http".parse().unwrap());
+        assert_eq!(request_scheme(&headers), "https");
+        // `Forwarded` wins over `X-Forwarded-Proto`
+        headers.insert(
+            header::FORWARDED,
+            "for=192.0.2.1;proto=\"http\", proto=https".parse().unwrap(),
+        );
+        assert_eq!(request_scheme(&headers), "http");
+    }
+
+    #[test]
+    fn matches_the_scheme() {
+        let rules = RedirectMatch {
+            scheme: SchemeMatch::Https,
+            ..Default::default()
+        };
+        let mut headers = HeaderMap::new();
+        assert!(!matches(&rules, "/", &headers));
+        headers.insert("x-forwarded-proto", "https".parse().unwrap());
+        assert!(matches(&rules, "/", &headers));
+        assert_eq!(varies_on(&rules), ["Forwarded", "X-Forwarded-Proto"]);
+    }
+
+    #[test]
+    fn matches_cookie_conditions() {
+        let rules = RedirectMatch {
+            cookies: vec![RedirectCookieMatch {
+                name: "beta".to_string(),
+                regex: Some("yes|1".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(matches(&rules, "/", &with_cookie("beta=1")));
+        assert!(!matches(&rules, "/", &with_cookie("beta=10")));
+        assert!(!matches(&rules, "/", &HeaderMap::new()));
+
+        let absent = RedirectMatch {
+            cookies: vec![RedirectCookieMatch {
+                name: "beta".to_string(),
+                absent: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(matches(&absent, "/", &HeaderMap::new()));
+        assert!(!matches(&absent, "/", &with_cookie("beta=1")));
+    }
+}
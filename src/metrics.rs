@@ -1,38 +1,154 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
+use anyhow::Context as _;
 use kube::{ResourceExt, runtime::finalizer};
 use prometheus_client::{
     encoding::EncodeLabelSet,
-    metrics::{counter::Counter, family::Family, histogram::Histogram},
-    registry::{Registry, Unit},
+    metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::Histogram},
+    registry::{Metric, Registry, Unit},
 };
 
-use crate::types::{Error, Redirect};
+use crate::{
+    host,
+    http_error::HttpError,
+    types::{Error, Redirect},
+};
 
 #[derive(Clone)]
 pub struct Metrics {
     pub reconcile: ReconcileMetrics,
     pub http: HttpMetrics,
+    pub objects: ObjectMetrics,
     pub registry: Arc<Registry>,
 }
 
-impl Default for Metrics {
+/// Histogram buckets used by default for reconcile durations, in seconds.
+pub const DEFAULT_RECONCILE_BUCKETS: [f64; 8] = [0.01, 0.1, 0.25, 0.5, 1., 5., 15., 60.];
+
+/// Histogram buckets used by default for serving durations, in seconds.
+pub const DEFAULT_HTTP_BUCKETS: [f64; 8] = [
+    0.000_01, 0.000_05, 0.000_1, 0.000_5, 0.001, 0.005, 0.01, 0.1,
+];
+
+pub struct MetricsConfig {
+    /// also export renamed metrics under their old names
+    pub legacy_names: bool,
+    pub reconcile_buckets: Vec<f64>,
+    pub http_buckets: Vec<f64>,
+}
+
+impl Default for MetricsConfig {
     fn default() -> Self {
+        Self {
+            legacy_names: true,
+            reconcile_buckets: DEFAULT_RECONCILE_BUCKETS.to_vec(),
+            http_buckets: DEFAULT_HTTP_BUCKETS.to_vec(),
+        }
+    }
+}
+
+/// Parses comma separated bucket bounds, they have to be ascending.
+fn parse_buckets(value: &str) -> anyhow::Result<Vec<f64>> {
+    let buckets = value
+        .split(',')
+        .map(|b| b.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()?;
+    if buckets.is_empty() || !buckets.windows(2).all(|w| w[0] < w[1]) {
+        anyhow::bail!("buckets have to be ascending: {value}");
+    }
+    Ok(buckets)
+}
+
+impl MetricsConfig {
+    /// Reads `METRICS_LEGACY_NAMES`, `METRICS_RECONCILE_BUCKETS` and `METRICS_HTTP_BUCKETS`.
+    ///
+    /// Old names are exported unless `METRICS_LEGACY_NAMES` is `false`, buckets are comma
+    /// separated upper bounds in seconds.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let buckets = |var: &str, default: &[f64]| match std::env::var(var) {
+            Ok(value) => parse_buckets(&value).with_context(|| format!("invalid {var}")),
+            Err(_) => Ok(default.to_vec()),
+        };
+        Ok(Self {
+            legacy_names: !std::env::var("METRICS_LEGACY_NAMES").is_ok_and(|v| v == "false"),
+            reconcile_buckets: buckets("METRICS_RECONCILE_BUCKETS", &DEFAULT_RECONCILE_BUCKETS)?,
+            http_buckets: buckets("METRICS_HTTP_BUCKETS", &DEFAULT_HTTP_BUCKETS)?,
+        })
+    }
+}
+
+impl Metrics {
+    pub fn new(config: &MetricsConfig) -> Self {
         let mut registry = Registry::with_prefix("redirect_operator");
-        let reconcile = ReconcileMetrics::default().register(&mut registry);
-        let http = HttpMetrics::default().register(&mut registry);
+        let mut compat = CompatRegistry {
+            registry: &mut registry,
+            legacy_names: config.legacy_names,
+        };
+        let reconcile =
+            ReconcileMetrics::new(&config.reconcile_buckets).register(&mut *compat.registry);
+        let http = HttpMetrics::new(&config.http_buckets).register(&mut compat);
+        let objects = ObjectMetrics::default().register(&mut registry);
         Self {
             registry: Arc::new(registry),
             reconcile,
             http,
+            objects,
         }
     }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self::new(&MetricsConfig::from_env()?))
+    }
 }
 
-#[derive(Clone, Default)]
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(&MetricsConfig::default())
+    }
+}
+
+/// Registers metrics under their current and, during a deprecation window, their old names.
+struct CompatRegistry<'a> {
+    registry: &'a mut Registry,
+    legacy_names: bool,
+}
+
+impl CompatRegistry<'_> {
+    fn register_renamed(
+        &mut self,
+        name: &str,
+        legacy_name: &str,
+        help: &str,
+        metric: impl Metric + Clone,
+    ) {
+        if self.legacy_names {
+            self.registry.register(
+                legacy_name,
+                format!("{help} (deprecated, use {name})"),
+                metric.clone(),
+            );
+        }
+        self.registry.register(name, help, metric);
+    }
+}
+
+#[derive(Clone)]
 pub struct HttpMetrics {
     pub requests: Family<RequestLabels, Counter>,
     pub failures: Family<RequestLabels, Counter>,
+    pub errors: Family<HttpErrorLabels, Counter>,
+    pub duration: Histogram,
+    pub analytics_dropped: Counter,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct HttpErrorLabels {
+    pub kind: String,
+    pub status: u16,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
@@ -41,6 +157,24 @@ pub struct RequestLabels {
 }
 
 impl HttpMetrics {
+    fn new(buckets: &[f64]) -> Self {
+        Self {
+            requests: Family::<RequestLabels, Counter>::default(),
+            failures: Family::<RequestLabels, Counter>::default(),
+            errors: Family::<HttpErrorLabels, Counter>::default(),
+            duration: Histogram::new(buckets.iter().copied()),
+            analytics_dropped: Counter::default(),
+        }
+    }
+
+    /// Measures a request until the returned value is dropped.
+    pub fn measure(&self) -> DurationMeasurer {
+        DurationMeasurer {
+            start: Instant::now(),
+            metric: self.duration.clone(),
+        }
+    }
+
     pub fn set_failure(&self, host: &str) {
         self.failures
             .get_or_create(&RequestLabels {
@@ -49,6 +183,15 @@ impl HttpMetrics {
             .inc();
     }
 
+    pub fn set_error(&self, error: &HttpError) {
+        self.errors
+            .get_or_create(&HttpErrorLabels {
+                kind: error.kind().to_string(),
+                status: error.status().as_u16(),
+            })
+            .inc();
+    }
+
     pub fn set_request(&self, host: &str) {
         self.requests
             .get_or_create(&RequestLabels {
@@ -57,9 +200,35 @@ impl HttpMetrics {
             .inc();
     }
 
-    fn register(self, r: &mut Registry) -> Self {
-        r.register("http_requests", "Count of requests", self.requests.clone());
-        r.register("http_failures", "Count of failures", self.failures.clone());
+    fn register(self, r: &mut CompatRegistry) -> Self {
+        r.register_renamed(
+            "http_redirects",
+            "http_requests",
+            "Count of redirected requests",
+            self.requests.clone(),
+        );
+        r.register_renamed(
+            "http_errors",
+            "http_failures",
+            "Count of requests that could not be redirected",
+            self.failures.clone(),
+        );
+        r.registry.register(
+            "http_error_responses",
+            "Error responses by kind",
+            self.errors.clone(),
+        );
+        r.registry.register_with_unit(
+            "http_request_duration",
+            "time to answer a request",
+            Unit::Seconds,
+            self.duration.clone(),
+        );
+        r.registry.register(
+            "analytics_dropped_events",
+            "Count of analytics events dropped because the queue was full or sending failed",
+            self.analytics_dropped.clone(),
+        );
         self
     }
 }
@@ -69,14 +238,27 @@ pub struct ReconcileMetrics {
     pub runs: Counter,
     pub failures: Family<ErrorLabels, Counter>,
     pub duration: Histogram,
+    pub loops: Family<InstanceLabels, Gauge>,
+    pub dns_mismatches: Family<InstanceLabels, Gauge>,
+    pub target_reachable: Family<TargetLabels, Gauge>,
+    pub pruned: Family<KindLabels, Counter>,
+    pub leader: Gauge,
+    /// the targets each Redirect has series for, to remove them with it
+    targets: Arc<Mutex<BTreeMap<InstanceLabels, BTreeSet<String>>>>,
 }
 
-impl Default for ReconcileMetrics {
-    fn default() -> Self {
+impl ReconcileMetrics {
+    fn new(buckets: &[f64]) -> Self {
         Self {
             runs: Counter::default(),
             failures: Family::<ErrorLabels, Counter>::default(),
-            duration: Histogram::new([0.01, 0.1, 0.25, 0.5, 1., 5., 15., 60.]),
+            duration: Histogram::new(buckets.iter().copied()),
+            loops: Family::<InstanceLabels, Gauge>::default(),
+            dns_mismatches: Family::<InstanceLabels, Gauge>::default(),
+            target_reachable: Family::<TargetLabels, Gauge>::default(),
+            pruned: Family::<KindLabels, Counter>::default(),
+            leader: Gauge::default(),
+            targets: Default::default(),
         }
     }
 }
@@ -87,10 +269,54 @@ pub struct ErrorLabels {
     pub error: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, EncodeLabelSet)]
+pub struct InstanceLabels {
+    pub namespace: String,
+    pub instance: String,
+}
+
+impl InstanceLabels {
+    fn new(namespace: &str, name: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            instance: name.to_string(),
+        }
+    }
+
+    fn of(redirect: &Redirect) -> Self {
+        Self::new(
+            &redirect.namespace().unwrap_or_default(),
+            &redirect.name_any(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct TargetLabels {
+    pub namespace: String,
+    pub instance: String,
+    pub target: String,
+}
+
+impl TargetLabels {
+    fn new(instance: &InstanceLabels, target: &str) -> Self {
+        Self {
+            namespace: instance.namespace.clone(),
+            instance: instance.instance.clone(),
+            target: target.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct KindLabels {
+    pub kind: String,
+}
+
 impl ReconcileMetrics {
-    pub fn count_and_measure(&self) -> ReconcileMeasurer {
+    pub fn count_and_measure(&self) -> DurationMeasurer {
         self.runs.inc();
-        ReconcileMeasurer {
+        DurationMeasurer {
             start: Instant::now(),
             metric: self.duration.clone(),
         }
@@ -113,6 +339,67 @@ impl ReconcileMetrics {
             .inc();
     }
 
+    pub fn set_loop(&self, redirect: &Redirect, looping: bool) {
+        self.loops
+            .get_or_create(&InstanceLabels::of(redirect))
+            .set(i64::from(looping));
+    }
+
+    pub fn set_dns_mismatches(&self, redirect: &Redirect, mismatches: usize) {
+        self.dns_mismatches
+            .get_or_create(&InstanceLabels::of(redirect))
+            .set(mismatches as i64);
+    }
+
+    /// Sets whether each target of `redirect` is reachable, removing targets it no longer has.
+    pub fn set_targets_reachable(&self, redirect: &Redirect, reachable: &[(&str, bool)]) {
+        let instance = InstanceLabels::of(redirect);
+        let targets: BTreeSet<String> = reachable.iter().map(|(t, _)| t.to_string()).collect();
+        let previous = self
+            .targets
+            .lock()
+            .unwrap()
+            .insert(instance.clone(), targets.clone());
+        for gone in previous.unwrap_or_default().difference(&targets) {
+            self.target_reachable
+                .remove(&TargetLabels::new(&instance, gone));
+        }
+        for (target, reachable) in reachable {
+            self.target_reachable
+                .get_or_create(&TargetLabels::new(&instance, target))
+                .set(i64::from(*reachable));
+        }
+    }
+
+    /// Removes the series of the Redirect `namespace/name`, once it is gone.
+    pub fn forget(&self, namespace: &str, name: &str) {
+        let instance = InstanceLabels::new(namespace, name);
+        self.loops.remove(&instance);
+        self.dns_mismatches.remove(&instance);
+        let targets = self.targets.lock().unwrap().remove(&instance);
+        for target in targets.unwrap_or_default() {
+            self.target_reachable
+                .remove(&TargetLabels::new(&instance, &target));
+        }
+    }
+
+    pub fn set_pruned(&self, kind: &str) {
+        self.pruned
+            .get_or_create(&KindLabels {
+                kind: kind.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn set_error(&self, instance: &str, error: &Error) {
+        self.failures
+            .get_or_create(&ErrorLabels {
+                instance: instance.to_string(),
+                error: error.metric_label(),
+            })
+            .inc();
+    }
+
     fn register(self, r: &mut Registry) -> Self {
         r.register_with_unit(
             "reconcile_duration",
@@ -126,19 +413,90 @@ impl ReconcileMetrics {
             self.failures.clone(),
         );
         r.register("reconcile_runs", "reconciliations", self.runs.clone());
+        r.register(
+            "redirect_loops",
+            "Redirects whose target leads back to managed hosts",
+            self.loops.clone(),
+        );
+        r.register(
+            "dns_mismatched_hosts",
+            "hosts of a Redirect not resolving to its Ingresses' load balancers",
+            self.dns_mismatches.clone(),
+        );
+        r.register(
+            "target_reachable",
+            "whether the target answered the last probe",
+            self.target_reachable.clone(),
+        );
+        r.register(
+            "pruned_objects",
+            "generated objects deleted because their Redirect is gone",
+            self.pruned.clone(),
+        );
+        r.register(
+            "is_leader",
+            "whether this replica holds the leader lease",
+            self.leader.clone(),
+        );
+        self
+    }
+}
+
+/// What the operator manages, as of the last scrape.
+#[derive(Clone, Default)]
+pub struct ObjectMetrics {
+    pub redirects: Gauge,
+    pub hosts: Gauge,
+    pub ingresses: Gauge,
+}
+
+impl ObjectMetrics {
+    /// Counts the Redirects in the store, their distinct hosts and their Ingresses.
+    pub fn update(&self, redirects: &[Arc<Redirect>]) {
+        let hosts: BTreeSet<String> = redirects
+            .iter()
+            .flat_map(|r| host::served_hosts(&r.spec).0)
+            .collect();
+        let status = redirects.iter().filter_map(|r| r.status.as_ref());
+        let shared: BTreeSet<&str> = status
+            .clone()
+            .filter_map(|s| s.shared_ingress.as_deref())
+            .collect();
+        let ingresses = status.map(|s| s.ingresses.len()).sum::<usize>() + shared.len();
+
+        self.redirects.set(redirects.len() as i64);
+        self.hosts.set(hosts.len() as i64);
+        self.ingresses.set(ingresses as i64);
+    }
+
+    fn register(self, r: &mut Registry) -> Self {
+        r.register(
+            "redirects",
+            "Redirects in the store",
+            self.redirects.clone(),
+        );
+        r.register(
+            "served_hosts",
+            "distinct hosts of the Redirects in the store",
+            self.hosts.clone(),
+        );
+        r.register(
+            "managed_ingresses",
+            "Ingresses serving the Redirects, each shared group counted once",
+            self.ingresses.clone(),
+        );
         self
     }
 }
 
-pub struct ReconcileMeasurer {
+pub struct DurationMeasurer {
     start: Instant,
     metric: Histogram,
 }
 
-impl Drop for ReconcileMeasurer {
+impl Drop for DurationMeasurer {
     fn drop(&mut self) {
-        #[allow(clippy::cast_precision_loss)]
-        let duration = self.start.elapsed().as_millis() as f64 / 1000.0;
+        let duration = self.start.elapsed().as_secs_f64();
         self.metric.observe(duration);
     }
 }
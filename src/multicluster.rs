@@ -0,0 +1,204 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, anyhow};
+use k8s_openapi::{
+    api::core::v1::Secret, apimachinery::pkg::apis::meta::v1::Time, jiff::Timestamp,
+};
+use kube::{
+    Api, Client, ResourceExt,
+    api::{ListParams, ObjectMeta, Patch, PatchParams},
+    config::{KubeConfigOptions, Kubeconfig},
+};
+use serde_json::json;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::{
+    controller::{Context, REDIRECT_KUBE_SLUG, apply_status, condition},
+    dryrun,
+    types::{Redirect, RedirectCluster},
+};
+
+/// Label on pushed Redirects naming the RedirectCluster that pushed them.
+///
+/// Labeled Redirects are never pushed on, so hubs can be members of each other.
+pub const SYNCED_FROM_LABEL: &str = "redirect.kube.ibotty.net/synced-from";
+
+/// Condition type reporting whether the member has all Redirects it should have.
+pub const CONDITION_SYNCED: &str = "Synced";
+
+/// How often members are synced, from `MULTICLUSTER_SYNC_SECONDS`; unset disables syncing.
+fn interval() -> anyhow::Result<Option<Duration>> {
+    env::var("MULTICLUSTER_SYNC_SECONDS")
+        .ok()
+        .map(|seconds| {
+            seconds
+                .parse()
+                .map(Duration::from_secs)
+                .context("invalid MULTICLUSTER_SYNC_SECONDS")
+        })
+        .transpose()
+}
+
+/// A client for the member, from the kubeconfig in its Secret.
+async fn member_client(ctx: &Context, cluster: &RedirectCluster) -> anyhow::Result<Client> {
+    let secret_ref = &cluster.spec.kubeconfig_secret_ref;
+    let secrets: Api<Secret> = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+    let secret = secrets
+        .get_opt(&secret_ref.name)
+        .await?
+        .ok_or_else(|| anyhow!("Secret {} does not exist", secret_ref.name))?;
+    let kubeconfig = secret
+        .data
+        .as_ref()
+        .and_then(|data| data.get(&secret_ref.key))
+        .ok_or_else(|| anyhow!("Secret {} has no key {}", secret_ref.name, secret_ref.key))?;
+    let kubeconfig = Kubeconfig::from_yaml(&String::from_utf8_lossy(&kubeconfig.0))
+        .context("invalid kubeconfig")?;
+    let config = kube::Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default())
+        .await
+        .context("unusable kubeconfig")?;
+    Ok(Client::try_from(config)?)
+}
+
+/// The copy of `redirect` pushed to members: its spec, name, labels and annotations.
+fn member_copy(redirect: &Redirect, cluster: &str) -> Redirect {
+    let mut labels = redirect.labels().clone();
+    labels.insert(SYNCED_FROM_LABEL.to_string(), cluster.to_string());
+    Redirect {
+        metadata: ObjectMeta {
+            name: redirect.metadata.name.clone(),
+            namespace: redirect.metadata.namespace.clone(),
+            labels: Some(labels),
+            annotations: redirect.metadata.annotations.clone(),
+            ..ObjectMeta::default()
+        },
+        spec: redirect.spec.clone(),
+        status: None,
+    }
+}
+
+/// Pushes the wanted Redirects to the member and deletes the ones no longer wanted.
+///
+/// Returns the `namespace/name` of the pushed Redirects.
+async fn sync(ctx: &Context, cluster: &RedirectCluster) -> anyhow::Result<Vec<String>> {
+    let client = member_client(ctx, cluster).await?;
+    let name = cluster.name_any();
+    let wanted: Vec<_> = ctx
+        .redirects
+        .state()
+        .into_iter()
+        .filter(|r| !r.labels().contains_key(SYNCED_FROM_LABEL))
+        .filter(|r| {
+            cluster
+                .spec
+                .redirect_labels
+                .iter()
+                .all(|(key, value)| r.labels().get(key) == Some(value))
+        })
+        .collect();
+
+    let mut pushed = BTreeSet::new();
+    for redirect in &wanted {
+        let ns = redirect.namespace().unwrap_or_default();
+        let api: Api<Redirect> = Api::namespaced(client.clone(), &ns);
+        api.patch(
+            &redirect.name_any(),
+            &ctx.patch_params(PatchParams::apply(REDIRECT_KUBE_SLUG).force()),
+            &Patch::Apply(member_copy(redirect, &name)),
+        )
+        .await
+        .with_context(|| format!("cannot push Redirect {ns}/{}", redirect.name_any()))?;
+        pushed.insert(format!("{ns}/{}", redirect.name_any()));
+    }
+
+    let api: Api<Redirect> = Api::all(client.clone());
+    let existing = api
+        .list_metadata(&ListParams::default().labels(&format!("{SYNCED_FROM_LABEL}={name}")))
+        .await
+        .context("cannot list pushed Redirects")?;
+    for stale in existing.items {
+        let ns = stale.namespace().unwrap_or_default();
+        if pushed.contains(&format!("{ns}/{}", stale.name_any())) {
+            continue;
+        }
+        info!(
+            "deleting Redirect {}/{} from member {}",
+            ns,
+            stale.name_any(),
+            name
+        );
+        if ctx.dry_run {
+            dryrun::log_delete("Redirect", &ns, &stale.name_any());
+        }
+        Api::<Redirect>::namespaced(client.clone(), &ns)
+            .delete(&stale.name_any(), &ctx.delete_params())
+            .await
+            .with_context(|| format!("cannot delete Redirect {ns}/{}", stale.name_any()))?;
+    }
+    Ok(pushed.into_iter().collect())
+}
+
+/// Syncs all members and records the outcome in their status.
+async fn sync_all(ctx: &Context) -> anyhow::Result<()> {
+    let api: Api<RedirectCluster> = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+    for cluster in api.list(&ListParams::default()).await? {
+        let name = cluster.name_any();
+        let mut status = cluster.status.clone().unwrap_or_default();
+        status.conditions = vec![match sync(ctx, &cluster).await {
+            Ok(redirects) => {
+                let message = format!("{} Redirects pushed", redirects.len());
+                status.redirects = redirects;
+                status.last_sync_time = Some(Time(Timestamp::now()));
+                condition(CONDITION_SYNCED, true, "Synced", message)
+            }
+            Err(e) => {
+                warn!("syncing member cluster {} failed: {:?}", name, e);
+                condition(CONDITION_SYNCED, false, "SyncFailed", format!("{e:#}"))
+            }
+        }];
+        if ctx.dry_run {
+            dryrun::log_diff(
+                "RedirectCluster status",
+                &ctx.self_namespace,
+                &name,
+                Some(&json!({ "status": cluster.status })),
+                &json!({ "status": status }),
+            );
+        } else if let Err(e) = apply_status(&api, &name, &status).await {
+            warn!("cannot update status of RedirectCluster {}: {:?}", name, e);
+        }
+    }
+    Ok(())
+}
+
+/// Syncs the members periodically while leading, until `shutdown` fires.
+pub async fn run(ctx: Arc<Context>, mut shutdown: oneshot::Receiver<()>) {
+    let interval = match interval() {
+        Ok(Some(interval)) => interval,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("not syncing member clusters: {:?}", e);
+            return;
+        }
+    };
+    // an empty store would delete everything pushed before
+    tokio::select! {
+        res = ctx.redirects.wait_until_ready() => if res.is_err() { return },
+        _ = &mut shutdown => return,
+    }
+    loop {
+        if ctx.leads_cluster()
+            && let Err(e) = sync_all(&ctx).await
+        {
+            warn!("syncing member clusters failed: {:?}", e);
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = &mut shutdown => return,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use axum::http::Uri;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+
+use crate::{
+    controller::{condition, ingress_name_for_redirect},
+    host,
+    types::{IngressOffload, Redirect, RedirectMode, RedirectSpec, SchemeMatch},
+};
+
+/// Condition type reporting whether the Ingress answers redirects without the operator.
+pub const CONDITION_OFFLOADED: &str = "Offloaded";
+
+pub const NGINX_PERMANENT_REDIRECT_ANNOTATION: &str =
+    "nginx.ingress.kubernetes.io/permanent-redirect";
+pub const NGINX_PERMANENT_REDIRECT_CODE_ANNOTATION: &str =
+    "nginx.ingress.kubernetes.io/permanent-redirect-code";
+pub const HAPROXY_REQUEST_REDIRECT_ANNOTATION: &str = "haproxy.org/request-redirect";
+pub const HAPROXY_REQUEST_REDIRECT_CODE_ANNOTATION: &str = "haproxy.org/request-redirect-code";
+pub const KONG_PLUGINS_ANNOTATION: &str = "konghq.com/plugins";
+
+/// Where a proxy redirects to on its own, always with 308.
+#[derive(Debug, PartialEq)]
+pub enum EdgeTarget {
+    /// the request, on this host
+    CanonicalHost(String),
+    /// `scheme://authority`, followed by `path` or the request's path and query
+    Uri {
+        scheme: String,
+        authority: String,
+        path: Option<String>,
+    },
+}
+
+/// What a proxy answers for `spec` without the operator, or why it cannot.
+///
+/// Only Redirects without per-request logic qualify.
+pub fn edge_target(spec: &RedirectSpec) -> Result<EdgeTarget, String> {
+    let unsupported = [
+        (spec.paused, "paused"),
+        (!spec.overrides.is_empty(), "overrides"),
+        (spec.split.is_some(), "split"),
+        (spec.path_map.is_some(), "pathMap"),
+        (!spec.short_links.is_empty(), "shortLinks"),
+        (
+            spec.interstitial.as_ref().is_some_and(|i| i.enabled),
+            "interstitial",
+        ),
+        (spec.tarpit_ms.is_some(), "tarpitMs"),
+        (!spec.links.is_empty(), "links"),
+        (!spec.to.append_params.is_empty(), "to.appendParams"),
+        (!spec.match_.cookies.is_empty(), "match.cookies"),
+        (spec.match_.scheme != SchemeMatch::Any, "match.scheme"),
+    ];
+    if let Some((_, field)) = unsupported.iter().find(|(used, _)| *used) {
+        return Err(format!("{field} needs the operator"));
+    }
+
+    match spec.mode {
+        RedirectMode::CanonicalHost => host::canonical_host(spec)
+            .map(EdgeTarget::CanonicalHost)
+            .ok_or_else(|| "no valid canonicalHost".to_string()),
+        RedirectMode::Redirect => {
+            let uri: Uri = spec.to.uri.parse().map_err(|_| "invalid to.uri")?;
+            let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+                return Err("to.uri is not absolute".to_string());
+            };
+            if uri.query().is_some() {
+                return Err("to.uri has a query".to_string());
+            }
+            let path = match (spec.to.include_request_uri, uri.path()) {
+                (true, "" | "/") => None,
+                (true, _) => return Err("to.uri has a path to prefix requests with".to_string()),
+                (false, path) => Some(path.to_string()),
+            };
+            Ok(EdgeTarget::Uri {
+                scheme: scheme.to_string(),
+                authority: authority.to_string(),
+                path,
+            })
+        }
+        RedirectMode::Proxy | RedirectMode::Page => {
+            Err(format!("{:?} mode needs the operator", spec.mode))
+        }
+    }
+}
+
+/// An Ingress controller that can answer redirects by itself, configured by annotations.
+pub trait OffloadController: Sync {
+    /// the controller's name, for conditions
+    fn name(&self) -> &'static str;
+
+    /// The annotations answering with `target`, or why the controller cannot.
+    ///
+    /// `name` is the name of the objects generated for the Redirect.
+    fn annotations(
+        &self,
+        name: &str,
+        target: EdgeTarget,
+    ) -> Result<BTreeMap<String, String>, String>;
+}
+
+/// ingress-nginx, with `permanent-redirect`.
+pub struct Nginx;
+
+impl OffloadController for Nginx {
+    fn name(&self) -> &'static str {
+        "ingress-nginx"
+    }
+
+    /// The annotation has to be a URL, canonical hosts are redirected to over https.
+    fn annotations(
+        &self,
+        _name: &str,
+        target: EdgeTarget,
+    ) -> Result<BTreeMap<String, String>, String> {
+        let location = match target {
+            EdgeTarget::CanonicalHost(host) => format!("https://{host}$request_uri"),
+            EdgeTarget::Uri {
+                scheme,
+                authority,
+                path,
+            } => match path {
+                Some(path) => format!("{scheme}://{authority}{path}"),
+                None => format!("{scheme}://{authority}$request_uri"),
+            },
+        };
+        Ok(BTreeMap::from([
+            (NGINX_PERMANENT_REDIRECT_ANNOTATION.to_string(), location),
+            (
+                NGINX_PERMANENT_REDIRECT_CODE_ANNOTATION.to_string(),
+                "308".to_string(),
+            ),
+        ]))
+    }
+}
+
+/// The HAProxy Kubernetes Ingress Controller, with `request-redirect`.
+pub struct Haproxy;
+
+impl OffloadController for Haproxy {
+    fn name(&self) -> &'static str {
+        "haproxy-ingress"
+    }
+
+    /// The annotation only names the host, scheme, path and query of requests are kept.
+    fn annotations(
+        &self,
+        _name: &str,
+        target: EdgeTarget,
+    ) -> Result<BTreeMap<String, String>, String> {
+        let host = match target {
+            EdgeTarget::CanonicalHost(host) => host,
+            EdgeTarget::Uri {
+                authority,
+                path: None,
+                ..
+            } => authority,
+            EdgeTarget::Uri { path: Some(_), .. } => {
+                return Err("HAProxy cannot redirect to a fixed path".to_string());
+            }
+        };
+        Ok(BTreeMap::from([
+            (HAPROXY_REQUEST_REDIRECT_ANNOTATION.to_string(), host),
+            (
+                HAPROXY_REQUEST_REDIRECT_CODE_ANNOTATION.to_string(),
+                "308".to_string(),
+            ),
+        ]))
+    }
+}
+
+/// Kong, with the `redirect` KongPlugin generated by [`crate::kong::KongPlugins`].
+pub struct Kong;
+
+impl OffloadController for Kong {
+    fn name(&self) -> &'static str {
+        "kong"
+    }
+
+    /// The annotation only references the KongPlugin, which is named like the Redirect's objects.
+    fn annotations(
+        &self,
+        name: &str,
+        _target: EdgeTarget,
+    ) -> Result<BTreeMap<String, String>, String> {
+        Ok(BTreeMap::from([(
+            KONG_PLUGINS_ANNOTATION.to_string(),
+            name.to_string(),
+        )]))
+    }
+}
+
+/// The annotations offloading the redirect to the Ingress controller, with the condition
+/// describing the outcome.
+///
+/// The Ingress keeps routing to the operator, which answers whatever the controller does not.
+/// `allow_native` is false while the operator has to see requests, e.g. to refuse them.
+pub fn ingress_annotations(
+    offload: &IngressOffload,
+    redirect: &Redirect,
+    allow_native: bool,
+) -> (BTreeMap<String, String>, Option<Condition>) {
+    let Some(controller) = offload.controller() else {
+        return (BTreeMap::new(), None);
+    };
+    let routed = |message: String| {
+        Some(condition(
+            CONDITION_OFFLOADED,
+            false,
+            "RoutedToOperator",
+            message,
+        ))
+    };
+    if !allow_native {
+        return (
+            BTreeMap::new(),
+            routed("not offloading while the Redirect is refused".to_string()),
+        );
+    }
+    let name = ingress_name_for_redirect(redirect);
+    match edge_target(&redirect.spec).and_then(|target| controller.annotations(&name, target)) {
+        Ok(annotations) => (
+            annotations,
+            Some(condition(
+                CONDITION_OFFLOADED,
+                true,
+                "Offloaded",
+                format!("requests are redirected by {}", controller.name()),
+            )),
+        ),
+        Err(reason) => (BTreeMap::new(), routed(format!("cannot offload, {reason}"))),
+    }
+}
+
+impl IngressOffload {
+    /// The controller to offload to, none to route to the operator.
+    pub fn controller(&self) -> Option<&'static dyn OffloadController> {
+        match self {
+            Self::None => None,
+            Self::Nginx => Some(&Nginx),
+            Self::Haproxy => Some(&Haproxy),
+            Self::Kong => Some(&Kong),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+/// Appends `params` to the query of `uri`, replacing parameters of the same name.
+///
+/// Other parameters and the fragment are kept as they are.
+pub fn append(uri: &str, params: &BTreeMap<String, String>) -> String {
+    if params.is_empty() {
+        return uri.to_string();
+    }
+
+    let (uri, fragment) = match uri.split_once('#') {
+        Some((uri, fragment)) => (uri, Some(fragment)),
+        None => (uri, None),
+    };
+    let (base, query) = uri.split_once('?').unwrap_or((uri, ""));
+
+    let kept = query.split('&').filter(|pair| {
+        let name = pair.split('=').next().unwrap_or_default();
+        !pair.is_empty()
+            && !form_urlencoded::parse(name.as_bytes())
+                .next()
+                .is_some_and(|(name, _)| params.contains_key(name.as_ref()))
+    });
+    let appended = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+
+    let query: Vec<&str> = kept.chain([appended.as_str()]).collect();
+    let mut result = format!("{base}?{}", query.join("&"));
+    if let Some(fragment) = fragment {
+        result.push('#');
+        result.push_str(fragment);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn appends_to_uris_without_query() {
+        assert_eq!(
+            append("https://example.org/", &params(&[("utm_source", "legacy")])),
+            "https://example.org/?utm_source=legacy"
+        );
+        assert_eq!(
+            append("https://example.org/", &BTreeMap::new()),
+            "https://example.org/"
+        );
+    }
+
+    #[test]
+    fn replaces_parameters_and_keeps_the_fragment() {
+        assert_eq!(
+            append(
+                "https://example.org/?utm_source=old&page=2#top",
+                &params(&[("utm_source", "new campaign")])
+            ),
+            "https://example.org/?page=2&utm_source=new+campaign#top"
+        );
+    }
+}
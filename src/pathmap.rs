@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use futures::{Stream, StreamExt};
+use k8s_openapi::{api::core::v1::ConfigMap, apimachinery::pkg::apis::meta::v1::Condition};
+use kube::{
+    Api, Client, ResourceExt,
+    runtime::{
+        WatchStreamExt,
+        reflector::{self, ObjectRef, Store},
+        watcher,
+    },
+};
+use tracing::warn;
+
+use crate::{
+    controller::condition,
+    types::{ConfigMapKeyRef, Error, PathMapFormat, Redirect, RedirectPathMap},
+};
+
+/// Label ConfigMaps referenced by Redirects need to carry to be watched.
+pub const CONFIG_MAP_LABEL: &str = "redirect.kube.ibotty.net/config";
+
+/// Condition type reporting whether the path map could be loaded.
+pub const CONDITION_PATH_MAP_VALID: &str = "PathMapValid";
+
+/// Condition type reporting whether the custom 404 page could be loaded.
+pub const CONDITION_NOT_FOUND_PAGE_VALID: &str = "NotFoundPageValid";
+
+/// Condition type reporting whether the page served in `page` mode could be loaded.
+pub const CONDITION_PAGE_VALID: &str = "PageValid";
+
+pub type PathTable = HashMap<String, String>;
+
+/// Parses a `path → target` table, paths are normalized to start with `/`.
+///
+/// An empty target marks a path as gone, it is answered with the 404 page.
+pub fn parse(data: &str, format: &PathMapFormat) -> Result<PathTable, String> {
+    let entries: Vec<(String, String)> = match format {
+        PathMapFormat::Csv => data
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| match line.split_once(',') {
+                Some((path, to)) => Ok((path.trim().to_string(), to.trim().to_string())),
+                _ => Err(format!("line {}: expected `path,target`", i + 1)),
+            })
+            .collect::<Result<_, _>>()?,
+        PathMapFormat::Yaml => serde_yaml::from_str::<HashMap<String, String>>(data)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect(),
+    };
+
+    Ok(entries
+        .into_iter()
+        .map(|(path, to)| {
+            let path = if path.starts_with('/') {
+                path
+            } else {
+                format!("/{path}")
+            };
+            (path, to)
+        })
+        .collect())
+}
+
+struct CachedTable {
+    resource_version: Option<String>,
+    table: Arc<PathTable>,
+}
+
+/// Labeled ConfigMaps and their parsed path maps, shared with the HTTP handler.
+#[derive(Clone)]
+pub struct PathMaps {
+    config_maps: Store<ConfigMap>,
+    cache: Arc<Mutex<HashMap<(ObjectRef<ConfigMap>, String), CachedTable>>>,
+}
+
+impl PathMaps {
+    /// Starts reflecting labeled ConfigMaps from `config_map_watcher`.
+    pub fn spawn(
+        config_map_watcher: impl Stream<Item = Result<watcher::Event<ConfigMap>, watcher::Error>>
+        + Send
+        + 'static,
+    ) -> Self {
+        let (config_maps, writer) = reflector::store();
+        tokio::spawn(
+            reflector::reflector(writer, config_map_watcher)
+                .touched_objects()
+                .for_each(|res| async move {
+                    if let Err(e) = res {
+                        warn!("watching ConfigMaps failed: {:?}", e);
+                    }
+                }),
+        );
+
+        Self {
+            config_maps,
+            cache: Default::default(),
+        }
+    }
+
+    /// The path maps of `config_maps`, e.g. listed once by a command.
+    pub fn from_config_maps(config_maps: Vec<ConfigMap>) -> Self {
+        let (store, mut writer) = reflector::store();
+        for config_map in config_maps {
+            writer.apply_watcher_event(&watcher::Event::Apply(config_map));
+        }
+        Self {
+            config_maps: store,
+            cache: Default::default(),
+        }
+    }
+
+    /// Looks up the target for `path` in the Redirect's path map.
+    pub fn lookup(&self, redirect: &Redirect, path: &str) -> Option<String> {
+        self.table_for(redirect)?.get(path).cloned()
+    }
+
+    /// The parsed path map of a Redirect, if it has a valid one.
+    pub fn table_for(&self, redirect: &Redirect) -> Option<Arc<PathTable>> {
+        let path_map = redirect.spec.path_map.as_ref()?;
+        let ns = redirect.namespace()?;
+        self.table(&ns, path_map)
+    }
+
+    /// The custom 404 page of a Redirect, if it has a valid one.
+    pub fn not_found_page(&self, redirect: &Redirect) -> Option<(StatusCode, String)> {
+        let not_found = redirect.spec.not_found.as_ref()?;
+        let status = StatusCode::from_u16(not_found.status_code).unwrap_or(StatusCode::NOT_FOUND);
+        let html = match (&not_found.html, &not_found.config_map_ref) {
+            (Some(html), _) => html.clone(),
+            (None, Some(source)) => self.value(&redirect.namespace()?, source)?,
+            (None, None) => return None,
+        };
+        Some((status, html))
+    }
+
+    /// The page served in `page` mode, if it has a valid one.
+    pub fn page(&self, redirect: &Redirect) -> Option<(StatusCode, String)> {
+        let page = redirect.spec.page.as_ref()?;
+        let status =
+            StatusCode::from_u16(page.status_code).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+        let html = match (&page.html, &page.config_map_ref) {
+            (Some(html), _) => html.clone(),
+            (None, Some(source)) => self.value(&redirect.namespace()?, source)?,
+            (None, None) => return None,
+        };
+        Some((status, html))
+    }
+
+    /// Data of a labeled ConfigMap.
+    pub fn data(&self, ns: &str, name: &str) -> Option<BTreeMap<String, String>> {
+        let config_map = self.config_maps.get(&ObjectRef::new(name).within(ns))?;
+        config_map.data.clone()
+    }
+
+    fn value(&self, ns: &str, source: &ConfigMapKeyRef) -> Option<String> {
+        let config_map = self
+            .config_maps
+            .get(&ObjectRef::new(&source.name).within(ns))?;
+        config_map.data.as_ref()?.get(&source.key).cloned()
+    }
+
+    fn table(&self, ns: &str, path_map: &RedirectPathMap) -> Option<Arc<PathTable>> {
+        let source = &path_map.config_map_ref;
+        let config_map_ref = ObjectRef::new(&source.name).within(ns);
+        let config_map = self.config_maps.get(&config_map_ref)?;
+        let resource_version = config_map.resource_version();
+
+        let cache_key = (config_map_ref, source.key.clone());
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cached) = cache
+            .get(&cache_key)
+            .filter(|cached| cached.resource_version == resource_version)
+        {
+            return Some(cached.table.clone());
+        }
+
+        let data = config_map.data.as_ref()?.get(&source.key)?;
+        let table = Arc::new(parse(data, &path_map.format).ok()?);
+        cache.insert(
+            cache_key,
+            CachedTable {
+                resource_version,
+                table: table.clone(),
+            },
+        );
+        Some(table)
+    }
+}
+
+/// Fetches a ConfigMap value through the API, `Ok(Err(reason, message))` if it is unusable.
+async fn fetch(
+    client: Client,
+    ns: &str,
+    source: &ConfigMapKeyRef,
+) -> Result<Result<String, (&'static str, String)>, Error> {
+    let api: Api<ConfigMap> = Api::namespaced(client, ns);
+
+    let Some(config_map) = api
+        .get_opt(&source.name)
+        .await
+        .map_err(Error::ConfigMapFetchFailed)?
+    else {
+        return Ok(Err((
+            "ConfigMapNotFound",
+            format!("ConfigMap {} does not exist", source.name),
+        )));
+    };
+    if !config_map.labels().contains_key(CONFIG_MAP_LABEL) {
+        return Ok(Err((
+            "MissingLabel",
+            format!(
+                "ConfigMap {} is not labeled {}",
+                source.name, CONFIG_MAP_LABEL
+            ),
+        )));
+    }
+    match config_map.data.and_then(|mut d| d.remove(&source.key)) {
+        Some(data) => Ok(Ok(data)),
+        None => Ok(Err((
+            "KeyNotFound",
+            format!("ConfigMap {} has no key {}", source.name, source.key),
+        ))),
+    }
+}
+
+/// Loads the path map through the API to report problems on the Redirect.
+pub async fn check(
+    client: Client,
+    ns: &str,
+    path_map: &RedirectPathMap,
+) -> Result<Condition, Error> {
+    let source = &path_map.config_map_ref;
+    Ok(match fetch(client, ns, source).await? {
+        Err((reason, message)) => condition(CONDITION_PATH_MAP_VALID, false, reason, message),
+        Ok(data) => match parse(&data, &path_map.format) {
+            Ok(table) => condition(
+                CONDITION_PATH_MAP_VALID,
+                true,
+                "Loaded",
+                format!("loaded {} paths", table.len()),
+            ),
+            Err(e) => condition(
+                CONDITION_PATH_MAP_VALID,
+                false,
+                "ParseError",
+                format!("ConfigMap {}: {}", source.name, e),
+            ),
+        },
+    })
+}
+
+/// Checks that a page in a ConfigMap can be loaded, reported as `condition_type`.
+pub async fn check_page(
+    client: Client,
+    ns: &str,
+    source: &ConfigMapKeyRef,
+    condition_type: &str,
+) -> Result<Condition, Error> {
+    Ok(match fetch(client, ns, source).await? {
+        Err((reason, message)) => condition(condition_type, false, reason, message),
+        Ok(_) => condition(
+            condition_type,
+            true,
+            "Loaded",
+            format!("loaded page from ConfigMap {}", source.name),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::redirect_from_json;
+
+    #[test]
+    fn parses_csv() {
+        let table = parse(
+            "# old shop\n/shop,https://example.org/store\n\n about , https://example.org/about-us\n/gone,\n",
+            &PathMapFormat::Csv,
+        )
+        .unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table["/shop"], "https://example.org/store");
+        assert_eq!(table["/about"], "https://example.org/about-us");
+        assert_eq!(table["/gone"], "");
+    }
+
+    #[test]
+    fn reports_the_invalid_csv_line() {
+        assert_eq!(
+            parse("/a,https://example.org\n/b\n", &PathMapFormat::Csv),
+            Err("line 2: expected `path,target`".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_yaml() {
+        let table = parse(
+            "/shop: https://example.org/store\nabout: https://example.org/about-us\n",
+            &PathMapFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(table["/shop"], "https://example.org/store");
+        assert_eq!(table["/about"], "https://example.org/about-us");
+        assert!(parse("- /shop\n", &PathMapFormat::Yaml).is_err());
+    }
+
+    #[test]
+    fn looks_up_paths_in_labeled_config_maps() {
+        let config_map: ConfigMap = serde_json::from_value(json!({
+            "metadata": { "name": "paths", "namespace": "web", "resourceVersion": "1" },
+            "data": { "paths.csv": "/shop,https://example.org/store\n" },
+        }))
+        .unwrap();
+        let redirect = redirect_from_json(json!({
+            "metadata": { "name": "shop", "namespace": "web" },
+            "spec": {
+                "hosts": ["shop.example.com"],
+                "ingress": {},
+                "pathMap": { "configMapRef": { "name": "paths", "key": "paths.csv" } },
+            },
+        }));
+        let path_maps = PathMaps::from_config_maps(vec![config_map]);
+        assert_eq!(
+            path_maps.lookup(&redirect, "/shop").as_deref(),
+            Some("https://example.org/store")
+        );
+        assert_eq!(path_maps.lookup(&redirect, "/other"), None);
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use regex::Regex;
+
+/// How many patterns are kept compiled, the cache starts over beyond that.
+const CAPACITY: usize = 4096;
+
+/// Compiled patterns of all Redirects, shared by all requests.
+static COMPILED: LazyLock<RwLock<HashMap<String, Result<Regex, regex::Error>>>> =
+    LazyLock::new(Default::default);
+
+/// Compiles `pattern`, only once while it is in use.
+pub fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(compiled) = COMPILED.read().unwrap().get(pattern) {
+        return compiled.clone();
+    }
+    let compiled = Regex::new(pattern);
+    let mut cache = COMPILED.write().unwrap();
+    if cache.len() >= CAPACITY {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), compiled.clone());
+    compiled
+}
+
+/// The patterns that do not compile, with why.
+pub fn invalid(patterns: &[String]) -> impl Iterator<Item = String> + '_ {
+    patterns.iter().filter_map(|p| {
+        compile(p)
+            .err()
+            .map(|e| format!("{p} is not a valid regular expression: {e}"))
+    })
+}
@@ -0,0 +1,134 @@
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use reqwest::{Method, StatusCode};
+use tracing::debug;
+
+use crate::{controller::condition, metrics::Metrics, target, types::Redirect};
+
+/// Condition type reporting whether the targets answer.
+pub const CONDITION_TARGET_REACHABLE: &str = "TargetReachable";
+
+/// Probes targets, at most once per interval each.
+pub struct Prober {
+    client: reqwest::Client,
+    /// how often targets are probed
+    pub interval: Duration,
+    /// when each target was probed and why it failed, if it did
+    results: Mutex<HashMap<String, (Instant, Result<(), String>)>>,
+}
+
+impl Prober {
+    /// Reads `TARGET_PROBE_INTERVAL_SECONDS` and `TARGET_PROBE_TIMEOUT_SECONDS`.
+    ///
+    /// Probing is off unless an interval is set.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(interval) = env::var("TARGET_PROBE_INTERVAL_SECONDS") else {
+            return Ok(None);
+        };
+        let interval = interval
+            .parse()
+            .context("invalid TARGET_PROBE_INTERVAL_SECONDS")?;
+        let timeout = match env::var("TARGET_PROBE_TIMEOUT_SECONDS") {
+            Ok(timeout) => timeout
+                .parse()
+                .context("invalid TARGET_PROBE_TIMEOUT_SECONDS")?,
+            Err(_) => 5,
+        };
+        let client = reqwest::Client::builder()
+            // a redirecting target answers as well
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(timeout))
+            .build()?;
+        Ok(Some(Self {
+            client,
+            interval: Duration::from_secs(interval),
+            results: Mutex::default(),
+        }))
+    }
+
+    /// Sends `HEAD`, or `GET` to targets not supporting it.
+    ///
+    /// Server errors and missing pages count as unreachable, anything else is an answer.
+    async fn probe(&self, uri: &str) -> Result<(), String> {
+        let mut status = StatusCode::OK;
+        for method in [Method::HEAD, Method::GET] {
+            status = self
+                .client
+                .request(method, uri)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?
+                .status();
+            if status != StatusCode::METHOD_NOT_ALLOWED && status != StatusCode::NOT_IMPLEMENTED {
+                break;
+            }
+        }
+        if status.is_server_error() || status == StatusCode::NOT_FOUND || status == StatusCode::GONE
+        {
+            return Err(format!("answered {status}"));
+        }
+        Ok(())
+    }
+
+    /// The last result for `uri`, probing it first if that is older than the interval.
+    async fn result(&self, uri: &str) -> Result<(), String> {
+        if let Some((at, result)) = self.results.lock().unwrap().get(uri)
+            && at.elapsed() < self.interval
+        {
+            return result.clone();
+        }
+        let result = self.probe(uri).await;
+        debug!("probed {}: {:?}", uri, result);
+        self.results
+            .lock()
+            .unwrap()
+            .insert(uri.to_string(), (Instant::now(), result.clone()));
+        result
+    }
+
+    /// Probes the http targets of `redirect`, returning the `TargetReachable` condition.
+    ///
+    /// `None` for Redirects without such targets.
+    pub async fn check(&self, redirect: &Redirect, metrics: &Metrics) -> Option<Condition> {
+        let uris: BTreeSet<&str> = target::targets(&redirect.spec)
+            .into_iter()
+            .map(|(_, to)| to.uri.as_str())
+            .filter(|uri| target::is_http(uri))
+            .collect();
+        let mut reachable = Vec::new();
+        let mut unreachable = Vec::new();
+        for uri in uris {
+            let result = self.result(uri).await;
+            reachable.push((uri, result.is_ok()));
+            if let Err(e) = result {
+                unreachable.push(format!("{uri} {e}"));
+            }
+        }
+        metrics
+            .reconcile
+            .set_targets_reachable(redirect, &reachable);
+        if reachable.is_empty() {
+            return None;
+        }
+        Some(if unreachable.is_empty() {
+            condition(
+                CONDITION_TARGET_REACHABLE,
+                true,
+                "Reachable",
+                "all targets answer",
+            )
+        } else {
+            condition(
+                CONDITION_TARGET_REACHABLE,
+                false,
+                "Unreachable",
+                unreachable.join("; "),
+            )
+        })
+    }
+}
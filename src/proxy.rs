@@ -0,0 +1,358 @@
+use std::env;
+use std::error::Error;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use tracing::warn;
+
+use crate::matcher;
+
+/// Headers that only apply to a single connection and are not forwarded.
+const HOP_BY_HOP_HEADERS: [HeaderName; 7] = [
+    header::CONNECTION,
+    header::PROXY_AUTHENTICATE,
+    header::PROXY_AUTHORIZATION,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+fn forwardable(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in &HOP_BY_HOP_HEADERS {
+        headers.remove(name);
+    }
+    headers.remove("keep-alive");
+    headers
+}
+
+/// Headers carrying the client's credentials for this host, only forwarded if allowed.
+const CREDENTIAL_HEADERS: [HeaderName; 2] = [header::AUTHORIZATION, header::COOKIE];
+
+fn without_credentials(mut headers: HeaderMap, allowed: &[HeaderName]) -> HeaderMap {
+    for name in CREDENTIAL_HEADERS.iter().filter(|n| !allowed.contains(n)) {
+        headers.remove(name);
+    }
+    headers
+}
+
+/// An address range, like `10.96.0.0/12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Network {
+    addr: IpAddr,
+    prefix: u32,
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = s.split_once('/').unwrap_or((s, ""));
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("{s} is not an address range"))?;
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            "" => bits,
+            prefix => prefix
+                .parse()
+                .ok()
+                .filter(|p| *p <= bits)
+                .ok_or(format!("{s} has an invalid prefix length"))?,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl Network {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (net, ip, bits) = match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => (u32::from(net).into(), u32::from(ip).into(), 32),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => (u128::from(net), u128::from(ip), 128),
+            _ => return false,
+        };
+        let shift = bits - self.prefix;
+        net.checked_shr(shift).unwrap_or(0) == ip.checked_shr(shift).unwrap_or(0)
+    }
+}
+
+/// Whether `ip` is only reachable from inside, e.g. loopback, private or link-local
+/// addresses like the cloud metadata service.
+pub fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // shared address space, carrier-grade NAT
+                || (a == 100 && b & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_internal(IpAddr::V4(ip)),
+            None => {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+            }
+        },
+    }
+}
+
+/// Where proxied requests may go: no internal addresses and none of the denied networks.
+#[derive(Clone)]
+struct Destinations {
+    denied: Arc<[Network]>,
+}
+
+impl Destinations {
+    fn allows(&self, ip: IpAddr) -> bool {
+        !is_internal(ip) && !self.denied.iter().any(|n| n.contains(ip))
+    }
+
+    async fn lookup(self, name: Name) -> Result<Addrs, Box<dyn Error + Send + Sync>> {
+        let allowed: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+            .await?
+            .filter(|addr| self.allows(addr.ip()))
+            .collect();
+        if allowed.is_empty() {
+            return Err(format!("{} has no address proxying may reach", name.as_str()).into());
+        }
+        Ok(Box::new(allowed.into_iter()))
+    }
+}
+
+/// Resolves target hosts to allowed addresses only, so that connections never reach
+/// denied ones, even if the DNS answer changes after a check.
+impl Resolve for Destinations {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(self.clone().lookup(name))
+    }
+}
+
+/// Forwards requests of Redirects in `proxy` mode.
+#[derive(Clone)]
+pub struct Proxy {
+    client: reqwest::Client,
+    enabled: bool,
+    destinations: Destinations,
+    forwarded_credentials: Arc<[HeaderName]>,
+}
+
+impl Proxy {
+    /// Reads `ALLOW_PROXY_MODE`, proxy mode is refused unless it is `true`, and
+    /// `PROXY_DENIED_NETWORKS`, comma-separated ranges like the cluster's pod and Service
+    /// CIDRs. Loopback, private and link-local addresses are always denied.
+    ///
+    /// `Authorization` and `Cookie` are only forwarded if named in the comma-separated
+    /// `PROXY_FORWARD_CREDENTIALS`, they are meant for the redirect's host, not the target.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let denied = match env::var("PROXY_DENIED_NETWORKS") {
+            Ok(networks) => networks
+                .split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<Network>, _>>()
+                .map_err(anyhow::Error::msg)
+                .context("invalid PROXY_DENIED_NETWORKS")?,
+            Err(_) => Vec::new(),
+        };
+        let destinations = Destinations {
+            denied: denied.into(),
+        };
+        let forwarded_credentials = match env::var("PROXY_FORWARD_CREDENTIALS") {
+            Ok(names) => names
+                .split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(|n| {
+                    HeaderName::from_str(n)
+                        .ok()
+                        .filter(|n| CREDENTIAL_HEADERS.contains(n))
+                        .ok_or_else(|| anyhow::anyhow!("{n} is not a credential header"))
+                })
+                .collect::<anyhow::Result<Vec<HeaderName>>>()
+                .context("invalid PROXY_FORWARD_CREDENTIALS")?,
+            Err(_) => Vec::new(),
+        };
+        let client = reqwest::Client::builder()
+            // responses are passed on as they are, including redirects
+            .redirect(reqwest::redirect::Policy::none())
+            // a proxy would resolve the targets itself
+            .no_proxy()
+            .dns_resolver(Arc::new(destinations.clone()))
+            .build()?;
+        Ok(Self {
+            client,
+            enabled: env::var("ALLOW_PROXY_MODE").is_ok_and(|v| v == "true"),
+            destinations,
+            forwarded_credentials: forwarded_credentials.into(),
+        })
+    }
+
+    /// Whether Redirects may proxy at all.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Streams the request to `target` and the response back.
+    ///
+    /// `Host` is set from `target`, the original one is passed as `X-Forwarded-Host`, the
+    /// client's address is appended to `X-Forwarded-For`. Credentials are dropped unless
+    /// allowed.
+    pub async fn forward(
+        &self,
+        target: &str,
+        host: &str,
+        client: IpAddr,
+        method: Method,
+        headers: &HeaderMap,
+        body: Body,
+    ) -> Response {
+        // addresses in the URL are not resolved
+        let literal = reqwest::Url::parse(target)
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .map(|h| h.trim_start_matches('[').trim_end_matches(']').to_string())
+            })
+            .and_then(|h| h.parse::<IpAddr>().ok());
+        if !self.enabled || literal.is_some_and(|ip| !self.destinations.allows(ip)) {
+            warn!("refusing to proxy to {}", target);
+            return (StatusCode::FORBIDDEN, "proxy target not allowed\n").into_response();
+        }
+
+        let mut request_headers =
+            without_credentials(forwardable(headers), &self.forwarded_credentials);
+        request_headers.remove(header::HOST);
+        if let Ok(host) = HeaderValue::from_str(host) {
+            request_headers.insert("x-forwarded-host", host);
+        }
+        let forwarded_for = match headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+            Some(previous) => format!("{previous}, {client}"),
+            None => client.to_string(),
+        };
+        if let Ok(forwarded_for) = HeaderValue::from_str(&forwarded_for) {
+            request_headers.insert("x-forwarded-for", forwarded_for);
+        }
+        if let Ok(proto) = HeaderValue::from_str(&matcher::request_scheme(headers)) {
+            request_headers.insert("x-forwarded-proto", proto);
+        }
+
+        let response = self
+            .client
+            .request(method, target)
+            .headers(request_headers)
+            .body(reqwest::Body::wrap_stream(body.into_data_stream()))
+            .send()
+            .await;
+
+        match response {
+            Ok(response) => {
+                let status = response.status();
+                let headers = forwardable(response.headers());
+                let mut proxied = Body::from_stream(response.bytes_stream()).into_response();
+                *proxied.status_mut() = status;
+                *proxied.headers_mut() = headers;
+                proxied
+            }
+            Err(e) => {
+                warn!("proxying to {} failed: {}", target, e);
+                (StatusCode::BAD_GATEWAY, "upstream unavailable\n").into_response()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn internal_addresses_are_denied() {
+        for internal in [
+            "127.0.0.1",
+            "10.96.0.1",
+            "172.16.3.4",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(is_internal(ip(internal)), "{internal}");
+        }
+        for public in ["93.184.216.34", "2606:2800:220:1::1"] {
+            assert!(!is_internal(ip(public)), "{public}");
+        }
+    }
+
+    #[test]
+    fn credentials_are_only_forwarded_if_allowed() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer x"));
+        headers.insert(header::COOKIE, HeaderValue::from_static("session=x"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+
+        let stripped = without_credentials(headers.clone(), &[]);
+        assert!(!stripped.contains_key(header::AUTHORIZATION));
+        assert!(!stripped.contains_key(header::COOKIE));
+        assert!(stripped.contains_key(header::ACCEPT));
+
+        let allowed = without_credentials(headers, &[header::COOKIE]);
+        assert!(!allowed.contains_key(header::AUTHORIZATION));
+        assert!(allowed.contains_key(header::COOKIE));
+    }
+
+    #[test]
+    fn networks_contain_their_addresses() {
+        let network: Network = "203.0.113.0/25".parse().unwrap();
+        assert!(network.contains(ip("203.0.113.127")));
+        assert!(!network.contains(ip("203.0.113.128")));
+        assert!(!network.contains(ip("2001:db8::1")));
+
+        let all: Network = "::/0".parse().unwrap();
+        assert!(all.contains(ip("2001:db8::1")));
+        let single: Network = "2001:db8::1".parse().unwrap();
+        assert!(single.contains(ip("2001:db8::1")));
+        assert!(!single.contains(ip("2001:db8::2")));
+    }
+
+    #[test]
+    fn invalid_networks_are_refused() {
+        assert!("10.0.0.0/33".parse::<Network>().is_err());
+        assert!("cluster".parse::<Network>().is_err());
+    }
+
+    #[test]
+    fn denied_networks_are_not_allowed() {
+        let destinations = Destinations {
+            denied: vec!["198.51.100.0/24".parse().unwrap()].into(),
+        };
+        assert!(!destinations.allows(ip("198.51.100.7")));
+        assert!(!destinations.allows(ip("127.0.0.1")));
+        assert!(destinations.allows(ip("93.184.216.34")));
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use kube::{Resource, ResourceExt};
+
+use crate::ttl;
+
+/// Annotation overriding the resync interval of a single object, e.g. `1h`.
+pub const RESYNC_ANNOTATION: &str = "redirect.kube.ibotty.net/resync-interval";
+
+/// When objects are reconciled again.
+#[derive(Debug)]
+pub struct Requeue {
+    /// after a successful reconcile
+    pub interval: Duration,
+    /// after the first failure, doubled on every further failure
+    pub error: Duration,
+    /// upper bound of the error backoff
+    pub max_error: Duration,
+    /// consecutive failures per object
+    failures: Mutex<HashMap<String, u32>>,
+}
+
+fn seconds(var: &str, default: u64) -> anyhow::Result<Duration> {
+    Ok(Duration::from_secs(match env::var(var) {
+        Ok(seconds) => seconds.parse().with_context(|| format!("invalid {var}"))?,
+        Err(_) => default,
+    }))
+}
+
+fn key<K: Resource<DynamicType = ()>>(object: &K) -> String {
+    format!(
+        "{}/{}/{}",
+        K::kind(&()),
+        object.namespace().unwrap_or_default(),
+        object.name_any()
+    )
+}
+
+impl Requeue {
+    /// Reads `REQUEUE_SECONDS`, `ERROR_REQUEUE_SECONDS` and `ERROR_REQUEUE_MAX_SECONDS`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            interval: seconds("REQUEUE_SECONDS", 300)?,
+            error: seconds("ERROR_REQUEUE_SECONDS", 1)?,
+            max_error: seconds("ERROR_REQUEUE_MAX_SECONDS", 300)?,
+            failures: Mutex::default(),
+        })
+    }
+
+    /// The resync interval of `object`, from its annotation or the default.
+    ///
+    /// Also resets the error backoff, as it is asked for after successful reconciles.
+    pub fn resync<K: Resource<DynamicType = ()>>(&self, object: &K) -> Duration {
+        self.failures.lock().unwrap().remove(&key(object));
+        object
+            .annotations()
+            .get(RESYNC_ANNOTATION)
+            .map(String::as_str)
+            .and_then(ttl::parse_duration)
+            .unwrap_or(self.interval)
+    }
+
+    /// The backoff after another failed reconcile of `object`.
+    pub fn backoff<K: Resource<DynamicType = ()>>(&self, object: &K) -> Duration {
+        let mut failures = self.failures.lock().unwrap();
+        let count = failures.entry(key(object)).or_default();
+        let backoff = self.error.saturating_mul(1 << (*count).min(16));
+        *count = count.saturating_add(1);
+        backoff.min(self.max_error)
+    }
+
+    /// Forgets the backoff of a deleted object.
+    pub fn forget<K: Resource<DynamicType = ()>>(&self, object: &K) {
+        self.failures.lock().unwrap().remove(&key(object));
+    }
+}
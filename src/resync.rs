@@ -0,0 +1,43 @@
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::signal::unix::{SignalKind, signal};
+use tracing::info;
+
+/// Requests to reconcile all objects, e.g. after namespace defaults changed.
+///
+/// Every controller subscribes once, triggers reach all of them.
+#[derive(Clone, Default)]
+pub struct ReconcileAll {
+    subscribers: Arc<Mutex<Vec<UnboundedSender<()>>>>,
+}
+
+impl ReconcileAll {
+    /// A stream for `Controller::reconcile_all_on`.
+    pub fn subscribe(&self) -> UnboundedReceiver<()> {
+        let (tx, rx) = mpsc::unbounded();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Reconciles all objects of all controllers, forgetting stopped ones.
+    pub fn trigger(&self) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.unbounded_send(()).is_ok());
+    }
+
+    /// Triggers on every SIGHUP.
+    pub fn on_sighup(&self) -> anyhow::Result<()> {
+        let mut hangup = signal(SignalKind::hangup())?;
+        let this = self.clone();
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                info!("received SIGHUP, reconciling everything");
+                this.trigger();
+            }
+        });
+        Ok(())
+    }
+}
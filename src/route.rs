@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::{
+    ResourceExt,
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+};
+use serde_json::json;
+
+use crate::{
+    controller::{
+        NetworkingBackend, REDIRECT_SERVICE_PORT, RenderContext, ingress_name_for_redirect,
+    },
+    host,
+    types::{
+        Error, InsecureEdgeTerminationPolicy, Redirect, RedirectRoute, RedirectSpec,
+        RedirectStatus, RedirectStatusHostObject, RouteTermination,
+    },
+};
+
+/// The OpenShift `route.openshift.io/v1` Route API.
+pub fn api_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk("route.openshift.io", "v1", "Route"))
+}
+
+/// Name of the Route serving `host`, Routes have a single host each.
+pub fn route_name(redirect: &Redirect, host: &str) -> String {
+    format!("{}.{}", ingress_name_for_redirect(redirect), host)
+}
+
+/// The Routes serving a Redirect, one per host.
+pub fn routes_for_redirect(
+    namespace: &str,
+    service_name: &str,
+    redirect: &Redirect,
+    redirect_route: &RedirectRoute,
+) -> Vec<DynamicObject> {
+    let (hosts, _) = host::served_hosts(&redirect.spec);
+    let tls = match redirect_route.tls {
+        RouteTermination::Edge => Some(json!({
+            "termination": "edge",
+            "insecureEdgeTerminationPolicy": match redirect_route.insecure_edge_termination_policy {
+                InsecureEdgeTerminationPolicy::Allow => "Allow",
+                InsecureEdgeTerminationPolicy::Redirect => "Redirect",
+                InsecureEdgeTerminationPolicy::None => "None",
+            },
+        })),
+        RouteTermination::None => None,
+    };
+
+    hosts
+        .iter()
+        .map(|host| {
+            let mut spec = json!({
+                "host": host,
+                "to": {
+                    "kind": "Service",
+                    "name": service_name,
+                    "weight": 100,
+                },
+                "port": {
+                    "targetPort": REDIRECT_SERVICE_PORT,
+                },
+                "wildcardPolicy": "None",
+            });
+            if let Some(tls) = &tls {
+                spec["tls"] = tls.clone();
+            }
+
+            let mut route = DynamicObject::new(&route_name(redirect, host), &api_resource())
+                .within(namespace)
+                .data(json!({ "spec": spec }));
+            route.metadata.annotations = redirect_route.annotations.clone();
+            route.metadata.labels = redirect_route.labels.clone();
+            route
+        })
+        .collect()
+}
+
+/// The host an object serving a single host serves, from its `spec` at `pointer`.
+pub fn object_host(object: &DynamicObject, pointer: &str) -> String {
+    object
+        .data
+        .pointer(pointer)
+        .and_then(|host| host.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| object.name_any())
+}
+
+/// OpenShift Routes, one per host.
+pub struct Routes;
+
+impl NetworkingBackend for Routes {
+    fn kind(&self) -> &'static str {
+        "Route"
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        api_resource()
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn wanted(&self, spec: &RedirectSpec) -> bool {
+        spec.wants_route()
+    }
+
+    fn render(
+        &self,
+        rctx: &RenderContext,
+        redirect: &Redirect,
+    ) -> (Vec<DynamicObject>, Vec<Condition>) {
+        let routes = routes_for_redirect(
+            rctx.namespace,
+            rctx.service_name,
+            redirect,
+            &redirect.spec.route,
+        );
+        (routes, Vec::new())
+    }
+
+    fn existing(&self, redirect: &Redirect, _self_namespace: &str) -> BTreeSet<(String, String)> {
+        redirect
+            .status
+            .iter()
+            .flat_map(|status| &status.routes)
+            .map(|r| (r.namespace.clone(), r.name.clone()))
+            .collect()
+    }
+
+    fn record(&self, status: &mut RedirectStatus, namespace: &str, applied: &[DynamicObject]) {
+        status.routes = applied
+            .iter()
+            .map(|route| RedirectStatusHostObject {
+                name: route.name_any(),
+                namespace: namespace.to_string(),
+                host: object_host(route, "/spec/host"),
+            })
+            .collect();
+    }
+
+    fn apply_failed(&self, error: kube::Error) -> Error {
+        Error::RouteCreationFailed(error)
+    }
+
+    fn delete_failed(&self, error: kube::Error) -> Error {
+        Error::RouteDeletionFailed(error)
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeSet;
+use std::env;
+
+use anyhow::Context as _;
+use aws_sdk_route53::{
+    config::{BehaviorVersion, Credentials, Region},
+    error::DisplayErrorContext,
+    types::{
+        AliasTarget, Change, ChangeAction, ChangeBatch, ResourceRecord, ResourceRecordSet, RrType,
+    },
+};
+use futures::future::BoxFuture;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{Api, Client};
+use serde_json::json;
+use tracing::info;
+
+use crate::{
+    dns::{DnsProvider, Record},
+    dryrun,
+};
+
+/// Keys of the static credentials in the Secret.
+const ACCESS_KEY_ID_KEY: &str = "aws_access_key_id";
+const SECRET_ACCESS_KEY_KEY: &str = "aws_secret_access_key";
+
+/// Prefix of the TXT records marking address records as the operator's.
+const OWNER_PREFIX: &str = "_redirect-operator";
+
+/// AWS Route53, for hosts in public hosted zones the credentials can change.
+///
+/// Route53 records have no comments, a TXT record next to them holds the tag.
+pub struct Route53 {
+    client: aws_sdk_route53::Client,
+    ttl: i64,
+    /// hosted zone of the load balancers, to ALIAS them instead of using CNAMEs
+    alias_zone: Option<String>,
+    dry_run: bool,
+}
+
+impl Route53 {
+    /// Enabled by `ROUTE53_DNS`, with the pod's credentials, e.g. from IRSA, or by
+    /// `ROUTE53_CREDENTIALS_SECRET`, a Secret in the operator's namespace with
+    /// `aws_access_key_id` and `aws_secret_access_key`.
+    ///
+    /// Reads `ROUTE53_TTL` (default 300) and `ROUTE53_ALIAS_HOSTED_ZONE_ID`, the load balancers'
+    /// hosted zone. The Secret is only read on startup.
+    pub async fn from_env(
+        client: Client,
+        namespace: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<Self>> {
+        let secret_name = env::var("ROUTE53_CREDENTIALS_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+        if secret_name.is_none() && !env::var("ROUTE53_DNS").is_ok_and(|v| v == "true") {
+            return Ok(None);
+        }
+        let ttl = match env::var("ROUTE53_TTL") {
+            Ok(ttl) => ttl.parse().context("invalid ROUTE53_TTL")?,
+            Err(_) => 300,
+        };
+
+        // the API is global, served from us-east-1
+        let mut config =
+            aws_config::defaults(BehaviorVersion::latest()).region(Region::new("us-east-1"));
+        if let Some(name) = secret_name {
+            let secrets: Api<Secret> = Api::namespaced(client, namespace);
+            let secret = secrets
+                .get(&name)
+                .await
+                .with_context(|| format!("cannot read Secret {name}"))?;
+            let key = |key: &str| {
+                secret
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get(key))
+                    .map(|value| String::from_utf8_lossy(&value.0).trim().to_string())
+                    .with_context(|| format!("Secret {name} has no key {key}"))
+            };
+            config = config.credentials_provider(Credentials::new(
+                key(ACCESS_KEY_ID_KEY)?,
+                key(SECRET_ACCESS_KEY_KEY)?,
+                None,
+                None,
+                "ROUTE53_CREDENTIALS_SECRET",
+            ));
+        }
+        Ok(Some(Self {
+            client: aws_sdk_route53::Client::new(&config.load().await),
+            ttl,
+            alias_zone: env::var("ROUTE53_ALIAS_HOSTED_ZONE_ID")
+                .ok()
+                .filter(|z| !z.is_empty()),
+            dry_run,
+        }))
+    }
+
+    /// The id of the public hosted zone `host` is in, none if there is none.
+    async fn find_zone(&self, host: &str) -> Result<Option<String>, String> {
+        let host = host.strip_prefix("*.").unwrap_or(host);
+        let labels: Vec<&str> = host.split('.').collect();
+        // from the most specific candidate, top-level domains are no zones
+        for i in 0..labels.len().saturating_sub(1) {
+            let name = format!("{}.", labels[i..].join("."));
+            let zones = self
+                .client
+                .list_hosted_zones_by_name()
+                .dns_name(&name)
+                .send()
+                .await
+                .map_err(|e| format!("cannot list hosted zones: {}", DisplayErrorContext(e)))?;
+            if let Some(zone) = zones.hosted_zones().iter().find(|zone| {
+                zone.name() == name && !zone.config().is_some_and(|c| c.private_zone())
+            }) {
+                return Ok(Some(
+                    zone.id().trim_start_matches("/hostedzone/").to_string(),
+                ));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The record sets named `name`.
+    async fn record_sets(&self, zone: &str, name: &str) -> Result<Vec<ResourceRecordSet>, String> {
+        let sets = self
+            .client
+            .list_resource_record_sets()
+            .hosted_zone_id(zone)
+            .start_record_name(name)
+            .send()
+            .await
+            .map_err(|e| format!("cannot list records of {name}: {}", DisplayErrorContext(e)))?;
+        Ok(sets
+            .resource_record_sets()
+            .iter()
+            .filter(|set| record_name(set.name()) == name)
+            .cloned()
+            .collect())
+    }
+
+    fn record_set(
+        &self,
+        name: &str,
+        type_: RrType,
+        values: &[String],
+    ) -> Result<ResourceRecordSet, String> {
+        let mut set = ResourceRecordSet::builder()
+            .name(name)
+            .r#type(type_)
+            .ttl(self.ttl);
+        for value in values {
+            set = set.resource_records(
+                ResourceRecord::builder()
+                    .value(value)
+                    .build()
+                    .map_err(|e| e.to_string())?,
+            );
+        }
+        set.build().map_err(|e| e.to_string())
+    }
+
+    /// The record sets for `wanted`, an ALIAS instead of a CNAME if the zone is known.
+    fn wanted_sets(&self, host: &str, wanted: &[Record]) -> Result<Vec<ResourceRecordSet>, String> {
+        let values = |type_: &str| -> Vec<String> {
+            wanted
+                .iter()
+                .filter(|(t, _)| *t == type_)
+                .map(|(_, content)| content.clone())
+                .collect()
+        };
+        let mut sets = Vec::new();
+        for (type_, rr_type) in [("A", RrType::A), ("AAAA", RrType::Aaaa)] {
+            let values = values(type_);
+            if !values.is_empty() {
+                sets.push(self.record_set(host, rr_type, &values)?);
+            }
+        }
+        if let Some(target) = values("CNAME").first() {
+            sets.push(match &self.alias_zone {
+                Some(zone) => ResourceRecordSet::builder()
+                    .name(host)
+                    .r#type(RrType::A)
+                    .alias_target(
+                        AliasTarget::builder()
+                            .hosted_zone_id(zone)
+                            .dns_name(target)
+                            .evaluate_target_health(false)
+                            .build()
+                            .map_err(|e| e.to_string())?,
+                    )
+                    .build()
+                    .map_err(|e| e.to_string())?,
+                None => self.record_set(host, RrType::Cname, std::slice::from_ref(target))?,
+            });
+        }
+        Ok(sets)
+    }
+
+    /// Makes the records of `host` match `wanted`, in one change batch.
+    async fn sync_records(
+        &self,
+        zone: &str,
+        host: &str,
+        tag: &str,
+        wanted: &[Record],
+    ) -> Result<(), String> {
+        let owner_name = owner_name(host);
+        let owner_value = format!("\"{tag}\"");
+        let existing: Vec<_> = self
+            .record_sets(zone, host)
+            .await?
+            .into_iter()
+            .filter(|set| matches!(set.r#type(), RrType::A | RrType::Aaaa | RrType::Cname))
+            .collect();
+        let owner = self
+            .record_sets(zone, &owner_name)
+            .await?
+            .into_iter()
+            .find(|set| {
+                *set.r#type() == RrType::Txt
+                    && set
+                        .resource_records()
+                        .iter()
+                        .any(|r| r.value() == owner_value)
+            });
+        if owner.is_none() {
+            // none of ours to delete
+            if wanted.is_empty() {
+                return Ok(());
+            }
+            if !existing.is_empty() {
+                return Err(format!(
+                    "{host} has DNS records not managed by the operator"
+                ));
+            }
+        }
+
+        let wanted = self.wanted_sets(host, wanted)?;
+        let mut changes = Vec::new();
+        for set in &existing {
+            if !wanted.iter().any(|w| same(w, set)) {
+                changes.push((ChangeAction::Delete, set.clone()));
+            }
+        }
+        for set in &wanted {
+            if !existing.iter().any(|e| same(e, set)) {
+                changes.push((ChangeAction::Upsert, set.clone()));
+            }
+        }
+        match owner {
+            Some(owner) if wanted.is_empty() => changes.push((ChangeAction::Delete, owner)),
+            None if !wanted.is_empty() => changes.push((
+                ChangeAction::Upsert,
+                self.record_set(&owner_name, RrType::Txt, &[owner_value])?,
+            )),
+            _ => {}
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        if self.dry_run {
+            for (action, set) in &changes {
+                let name = record_name(set.name());
+                match action {
+                    ChangeAction::Delete => dryrun::log_delete("DNS record", zone, &name),
+                    _ => dryrun::log_diff("DNS record", zone, &name, None, &describe(set)),
+                }
+            }
+            return Ok(());
+        }
+        info!("changing {} Route53 records of {}", changes.len(), host);
+        let changes = changes
+            .into_iter()
+            .map(|(action, set)| {
+                Change::builder()
+                    .action(action)
+                    .resource_record_set(set)
+                    .build()
+                    .map_err(|e| e.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = ChangeBatch::builder()
+            .set_changes(Some(changes))
+            .comment(tag)
+            .build()
+            .map_err(|e| e.to_string())?;
+        self.client
+            .change_resource_record_sets()
+            .hosted_zone_id(zone)
+            .change_batch(batch)
+            .send()
+            .await
+            .map_err(|e| {
+                format!(
+                    "cannot change records of {host}: {}",
+                    DisplayErrorContext(e)
+                )
+            })?;
+        Ok(())
+    }
+}
+
+impl DnsProvider for Route53 {
+    fn name(&self) -> &'static str {
+        "Route53"
+    }
+
+    fn zone<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(self.find_zone(host))
+    }
+
+    fn sync_host<'a>(
+        &'a self,
+        zone: &'a str,
+        host: &'a str,
+        tag: &'a str,
+        wanted: &'a [Record],
+    ) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(self.sync_records(zone, host, tag, wanted))
+    }
+}
+
+/// The name of the TXT record marking the records of `host`.
+///
+/// Wildcards are only allowed as the first label.
+fn owner_name(host: &str) -> String {
+    match host.strip_prefix("*.") {
+        Some(domain) => format!("{OWNER_PREFIX}-wildcard.{domain}"),
+        None => format!("{OWNER_PREFIX}.{host}"),
+    }
+}
+
+/// A record set's name as a host, Route53 escapes `*` and appends the root.
+fn record_name(name: &str) -> String {
+    name.trim_end_matches('.')
+        .replace("\\052", "*")
+        .to_ascii_lowercase()
+}
+
+/// Whether two record sets have the same name, type, TTL and content.
+fn same(a: &ResourceRecordSet, b: &ResourceRecordSet) -> bool {
+    let values = |set: &ResourceRecordSet| -> BTreeSet<String> {
+        set.resource_records()
+            .iter()
+            .map(|r| r.value().to_string())
+            .collect()
+    };
+    let alias = |set: &ResourceRecordSet| {
+        set.alias_target()
+            .map(|t| (t.hosted_zone_id().to_string(), record_name(t.dns_name())))
+    };
+    record_name(a.name()) == record_name(b.name())
+        && a.r#type() == b.r#type()
+        && alias(a) == alias(b)
+        && (alias(a).is_some() || (a.ttl() == b.ttl() && values(a) == values(b)))
+}
+
+/// A record set, for dry runs.
+fn describe(set: &ResourceRecordSet) -> serde_json::Value {
+    json!({
+        "type": set.r#type().as_str(),
+        "ttl": set.ttl(),
+        "values": set.resource_records().iter().map(|r| r.value()).collect::<Vec<_>>(),
+        "alias": set.alias_target().map(|t| t.dns_name()),
+    })
+}
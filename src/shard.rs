@@ -0,0 +1,55 @@
+use std::env;
+
+use anyhow::{Context as _, bail};
+
+use crate::hash;
+
+/// The part of the Redirects this replica reconciles, when they are split across replicas.
+///
+/// Every replica still watches and serves all Redirects. Each shard is guarded by its own
+/// Lease, so that a replica replacing another one only takes over once the old one is gone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Shard {
+    /// Reads `SHARDS` and `SHARD_INDEX`, sharding is off unless there are several shards.
+    ///
+    /// In a StatefulSet, `SHARD_INDEX` can be set from the pod's `apps.kubernetes.io/pod-index`
+    /// label through the downward API.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let count: u64 = match env::var("SHARDS") {
+            Ok(count) => count.parse().context("invalid SHARDS")?,
+            Err(_) => return Ok(None),
+        };
+        if count <= 1 {
+            return Ok(None);
+        }
+        let index: u64 = env::var("SHARD_INDEX")
+            .context("SHARDS needs SHARD_INDEX")?
+            .parse()
+            .context("invalid SHARD_INDEX")?;
+        if index >= count {
+            bail!("SHARD_INDEX {index} is not below SHARDS {count}");
+        }
+        Ok(Some(Self { index, count }))
+    }
+
+    /// Whether the Redirect `namespace/name` hashes into this shard's range.
+    pub fn owns(&self, namespace: &str, name: &str) -> bool {
+        let hash = hash::fnv1a(&format!("{namespace}/{name}"));
+        ((u128::from(hash) * u128::from(self.count)) >> 64) as u64 == self.index
+    }
+
+    /// Whether this shard runs what cannot be split, e.g. shared Ingresses and sweeps.
+    pub fn is_primary(&self) -> bool {
+        self.index == 0
+    }
+
+    /// The Lease guarding this shard.
+    pub fn lease_name(&self, base: &str) -> String {
+        format!("{base}-shard-{}", self.index)
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use k8s_openapi::api::networking::v1::{
+    HTTPIngressRuleValue, Ingress, IngressRule, IngressSpec, IngressTLS,
+};
+use kube::{
+    Api, ResourceExt,
+    api::{ListParams, ObjectMeta, Patch, PatchParams},
+};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::{
+    controller::{
+        Context, MAX_HOSTS_PER_INGRESS, REDIRECT_KUBE_SLUG, ingress_annotations,
+        ingress_name_for_redirect, ingress_paths,
+    },
+    defaults::NamespaceDefaults,
+    dryrun,
+    external_dns::ExternalDnsDefaults,
+    hash, host,
+    types::{Error, Redirect, RedirectIngress},
+};
+
+/// Label on shared Ingresses, with the name of their group.
+pub const SHARED_INGRESS_LABEL: &str = "redirect.kube.ibotty.net/shared-ingress";
+
+/// Label on shared Ingresses of an operator instance with a Redirect label selector.
+pub const PARTITION_LABEL: &str = "redirect.kube.ibotty.net/partition";
+
+/// How often shared Ingresses are rebuilt without being triggered.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Waits for more changes before rebuilding, to batch bursts of reconciles.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// A Redirect sharing Ingresses, with its effective Ingress settings.
+struct Member {
+    redirect: Arc<Redirect>,
+    settings: RedirectIngress,
+    external_dns: Option<ExternalDnsDefaults>,
+}
+
+/// Name of the shared Ingress group for Ingress settings with namespace defaults applied.
+///
+/// Redirects share Ingresses if they agree on everything that is set per Ingress:
+/// class, TLS, annotations, labels and external-dns settings.
+/// Operator instances with different label selectors never share, see [`partition`].
+pub fn group_name(
+    settings: &RedirectIngress,
+    external_dns: Option<&ExternalDnsDefaults>,
+    partition: Option<&str>,
+) -> String {
+    let mut key = format!(
+        "{:?}",
+        (
+            &settings.ingress_class_name,
+            settings.tls.enabled,
+            ingress_annotations(settings),
+            &settings.labels,
+            external_dns.map(|e| (e.ttl, &e.target)),
+        )
+    );
+    // keeps the names of existing groups without a partition
+    if let Some(partition) = partition {
+        key.push_str(partition);
+    }
+    format!("shared-{:016x}", hash::fnv1a(&key))
+}
+
+/// Identifies the Redirects of an operator instance with a label selector.
+pub fn partition(redirect_selector: Option<&str>) -> Option<String> {
+    redirect_selector.map(|selector| format!("{:016x}", hash::fnv1a(selector)))
+}
+
+/// Name of the `index`th Ingress of a group, the first one keeps the group name.
+fn chunk_name(group: &str, index: usize) -> String {
+    if index == 0 {
+        group.to_string()
+    } else {
+        format!("{group}-{index}")
+    }
+}
+
+/// The Ingresses of one group, hosts claimed twice go to the first Redirect by namespace and name.
+fn render_group(
+    namespace: &str,
+    service_name: &str,
+    group: &str,
+    partition: Option<&str>,
+    members: &[Member],
+) -> Vec<Ingress> {
+    let Some(first) = members.first() else {
+        return Vec::new();
+    };
+    let settings = &first.settings;
+
+    let mut seen = BTreeSet::new();
+    let hosts: Vec<(String, &Member)> = members
+        .iter()
+        .flat_map(|member| {
+            let (hosts, _) = host::served_hosts(&member.redirect.spec);
+            hosts.into_iter().map(move |h| (h, member))
+        })
+        .filter(|(h, _)| seen.insert(h.clone()))
+        .collect();
+
+    let mut labels = settings.labels.clone().unwrap_or_default();
+    labels.insert(SHARED_INGRESS_LABEL.to_string(), group.to_string());
+    if let Some(partition) = partition {
+        labels.insert(PARTITION_LABEL.to_string(), partition.to_string());
+    }
+
+    hosts
+        .chunks(MAX_HOSTS_PER_INGRESS)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let rules = chunk
+                .iter()
+                .map(|(host, member)| IngressRule {
+                    host: Some(host.clone()),
+                    http: Some(HTTPIngressRuleValue {
+                        paths: ingress_paths(
+                            service_name,
+                            &member.settings,
+                            &member.redirect.spec.match_.paths,
+                        ),
+                    }),
+                })
+                .collect();
+
+            // one TLS entry per Redirect, so certificates stay the same as without sharing
+            let tls = settings.tls.enabled.then(|| {
+                let mut by_secret: BTreeMap<String, Vec<String>> = BTreeMap::new();
+                for (host, member) in chunk {
+                    let secret_name =
+                        member.settings.tls.secret_name.clone().unwrap_or_else(|| {
+                            format!("{}-tls-certs", ingress_name_for_redirect(&member.redirect))
+                        });
+                    by_secret.entry(secret_name).or_default().push(host.clone());
+                }
+                by_secret
+                    .into_iter()
+                    .map(|(secret_name, hosts)| IngressTLS {
+                        hosts: Some(hosts),
+                        secret_name: Some(secret_name),
+                    })
+                    .collect()
+            });
+
+            let mut ingress = Ingress {
+                metadata: ObjectMeta {
+                    name: Some(chunk_name(group, index)),
+                    namespace: Some(namespace.to_string()),
+                    annotations: ingress_annotations(settings),
+                    labels: Some(labels.clone()),
+                    ..ObjectMeta::default()
+                },
+                spec: Some(IngressSpec {
+                    ingress_class_name: settings.ingress_class_name.clone(),
+                    rules: Some(rules),
+                    tls,
+                    ..IngressSpec::default()
+                }),
+                status: None,
+            };
+            if let Some(external_dns) = &first.external_dns {
+                external_dns.annotate(&mut ingress);
+            }
+            ingress
+        })
+        .collect()
+}
+
+/// Rebuilds all shared Ingresses from the Redirects in the store.
+///
+/// Redirects being deleted are left out, so their hosts are pruned from the
+/// Ingresses while the rest of their group keeps being served.
+pub async fn sync(ctx: &Context) -> Result<(), Error> {
+    let mut members: Vec<Arc<Redirect>> = ctx
+        .redirects
+        .state()
+        .into_iter()
+        .filter(|r| {
+            r.spec.wants_ingress()
+                && r.spec.ingress.shared
+                && r.metadata.deletion_timestamp.is_none()
+        })
+        .collect();
+    members.sort_by_key(|r| (r.namespace(), r.name_any()));
+
+    let partition = partition(ctx.redirect_selector.as_deref());
+    let mut defaults: HashMap<String, NamespaceDefaults> = HashMap::new();
+    let mut groups: BTreeMap<String, Vec<Member>> = BTreeMap::new();
+    for redirect in members {
+        let ns = redirect.namespace().unwrap_or_default();
+        if !defaults.contains_key(&ns) {
+            let fetched = ctx.namespace_defaults(&ns).await?;
+            defaults.insert(ns.clone(), fetched);
+        }
+        let settings = defaults[&ns].apply(&redirect.spec.ingress);
+        let external_dns = ctx
+            .external_dns
+            .resolve(&redirect.spec.ingress.external_dns);
+        groups
+            .entry(group_name(
+                &settings,
+                external_dns.as_ref(),
+                partition.as_deref(),
+            ))
+            .or_default()
+            .push(Member {
+                redirect,
+                settings,
+                external_dns,
+            });
+    }
+
+    let ingress_api: Api<Ingress> = Api::namespaced(ctx.client.clone(), &ctx.self_namespace);
+    let mut applied = BTreeSet::new();
+    for (group, members) in &groups {
+        for ingress in render_group(
+            &ctx.self_namespace,
+            &ctx.self_service_name,
+            group,
+            partition.as_deref(),
+            members,
+        ) {
+            let name = ingress.name_any();
+            ingress_api
+                .patch(
+                    &name,
+                    &ctx.patch_params(PatchParams::apply(REDIRECT_KUBE_SLUG)),
+                    &Patch::Apply(ingress),
+                )
+                .await
+                .map_err(Error::IngressCreationFailed)?;
+            applied.insert(name);
+        }
+    }
+
+    // leave the shared Ingresses of other instances alone
+    let selector = match &partition {
+        Some(partition) => format!("{SHARED_INGRESS_LABEL},{PARTITION_LABEL}={partition}"),
+        None => format!("{SHARED_INGRESS_LABEL},!{PARTITION_LABEL}"),
+    };
+    let existing = ingress_api
+        .list_metadata(&ListParams::default().labels(&selector))
+        .await
+        .map_err(Error::IngressListFailed)?;
+    for stale in existing
+        .items
+        .iter()
+        .map(|i| i.name_any())
+        .filter(|name| !applied.contains(name))
+    {
+        info!("removing shared Ingress {} without Redirects", stale);
+        if ctx.dry_run {
+            dryrun::log_delete("Ingress", &ctx.self_namespace, &stale);
+        }
+        match ingress_api.delete(&stale, &ctx.delete_params()).await {
+            Err(kube::Error::Api(response)) if response.code == 404 => {}
+            Err(e) => return Err(Error::IngressDeletionFailed(e)),
+            Ok(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Rebuilds shared Ingresses when triggered and periodically, until `shutdown` fires.
+pub async fn run(ctx: Arc<Context>, mut shutdown: oneshot::Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = ctx.shared_ingress_sync.notified() => tokio::time::sleep(DEBOUNCE).await,
+            _ = tokio::time::sleep(RESYNC_INTERVAL) => {}
+            _ = &mut shutdown => return,
+        }
+        if !ctx.leads_cluster() {
+            continue;
+        }
+        if let Err(e) = sync(&ctx).await {
+            warn!("syncing shared Ingresses failed: {:?}", e);
+            // retry soon
+            ctx.shared_ingress_sync.notify_one();
+        }
+    }
+}
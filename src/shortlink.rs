@@ -0,0 +1,152 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    hash,
+    types::{Redirect, RedirectShortLink, RedirectStatusShortLink},
+};
+
+const CODE_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const CODE_LENGTH: usize = 7;
+
+/// Derives a short code from `seed` (FNV-1a, base62 encoded).
+fn generate_code(seed: &str, attempt: u32) -> String {
+    let mut data = seed.as_bytes().to_vec();
+    data.extend(attempt.to_le_bytes());
+    let mut hash = hash::fnv1a(data);
+
+    (0..CODE_LENGTH)
+        .map(|_| {
+            let c = CODE_ALPHABET[(hash % CODE_ALPHABET.len() as u64) as usize];
+            hash /= CODE_ALPHABET.len() as u64;
+            c as char
+        })
+        .collect()
+}
+
+/// Assigns codes to all short links of a Redirect.
+///
+/// Explicit codes win, generated codes already recorded in `.status` are kept stable.
+pub fn assign_codes(redirect: &Redirect) -> Vec<RedirectStatusShortLink> {
+    let links = &redirect.spec.short_links;
+    let previous = redirect
+        .status
+        .as_ref()
+        .map(|s| s.short_links.as_slice())
+        .unwrap_or_default();
+
+    let mut taken: BTreeSet<String> = links.iter().filter_map(|l| l.code.clone()).collect();
+    let mut assigned: Vec<RedirectStatusShortLink> = links
+        .iter()
+        .filter_map(|l| {
+            l.code.as_ref().map(|code| RedirectStatusShortLink {
+                code: code.clone(),
+                to: l.to.clone(),
+            })
+        })
+        .collect();
+
+    for RedirectShortLink { to, .. } in links.iter().filter(|l| l.code.is_none()) {
+        let reused = previous
+            .iter()
+            .find(|p| &p.to == to && !taken.contains(&p.code))
+            .map(|p| p.code.clone());
+        let code = reused.unwrap_or_else(|| {
+            let seed = format!("{}/{}", redirect.metadata.uid.as_deref().unwrap_or(""), to);
+            (0..)
+                .map(|attempt| generate_code(&seed, attempt))
+                .find(|code| !taken.contains(code))
+                .unwrap()
+        });
+        taken.insert(code.clone());
+        assigned.push(RedirectStatusShortLink {
+            code,
+            to: to.clone(),
+        });
+    }
+
+    assigned
+}
+
+/// Looks up the target for `code`, preferring explicit codes from the spec.
+pub fn resolve<'a>(redirect: &'a Redirect, code: &str) -> Option<&'a str> {
+    redirect
+        .spec
+        .short_links
+        .iter()
+        .find(|l| l.code.as_deref() == Some(code))
+        .map(|l| l.to.as_str())
+        .or_else(|| {
+            redirect.status.as_ref().and_then(|s| {
+                s.short_links
+                    .iter()
+                    .find(|l| l.code == code)
+                    .map(|l| l.to.as_str())
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::redirect_from_json;
+
+    fn with_links(links: serde_json::Value, recorded: serde_json::Value) -> Redirect {
+        redirect_from_json(json!({
+            "metadata": { "name": "links", "uid": "4f1c" },
+            "spec": {
+                "hosts": ["go.example.com"],
+                "to": { "uri": "https://example.org" },
+                "ingress": {},
+                "shortLinks": links,
+            },
+            "status": {
+                "ingress": { "name": "links", "namespace": "default" },
+                "shortLinks": recorded,
+            },
+        }))
+    }
+
+    fn codes(links: &[RedirectStatusShortLink]) -> Vec<&str> {
+        links.iter().map(|l| l.code.as_str()).collect()
+    }
+
+    #[test]
+    fn duplicate_targets_get_distinct_codes() {
+        let redirect = with_links(
+            json!([
+                { "to": "https://example.org/a" },
+                { "to": "https://example.org/a" },
+            ]),
+            json!([]),
+        );
+        let assigned = assign_codes(&redirect);
+        assert_eq!(assigned.len(), 2);
+        assert_ne!(assigned[0].code, assigned[1].code);
+        assert!(assigned.iter().all(|l| l.code.len() == CODE_LENGTH));
+        // stable across reconciles
+        assert_eq!(codes(&assign_codes(&redirect)), codes(&assigned));
+    }
+
+    #[test]
+    fn explicit_codes_win_over_recorded_ones() {
+        let redirect = with_links(
+            json!([
+                { "code": "sale", "to": "https://example.org/sale" },
+                { "to": "https://example.org/a" },
+                { "to": "https://example.org/b" },
+            ]),
+            json!([
+                { "code": "sale", "to": "https://example.org/a" },
+                { "code": "b0", "to": "https://example.org/b" },
+            ]),
+        );
+        let assigned = assign_codes(&redirect);
+        assert_eq!(assigned[0].code, "sale");
+        assert_eq!(assigned[1].to, "https://example.org/a");
+        assert_ne!(assigned[1].code, "sale");
+        assert_eq!(assigned[2].code, "b0");
+        assert_eq!(resolve(&redirect, "sale"), Some("https://example.org/sale"));
+    }
+}
@@ -0,0 +1,58 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::signal::{self, unix::SignalKind};
+use tracing::{info, warn};
+
+/// How long phases other than draining may take.
+const PHASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Waits for Ctrl+C or SIGTERM.
+pub async fn signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler")
+    };
+    let terminate = async {
+        signal::unix::signal(SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {}
+    }
+}
+
+/// Time given to in-flight requests, from `SHUTDOWN_DRAIN_SECONDS`.
+pub fn drain_timeout() -> Duration {
+    std::env::var("SHUTDOWN_DRAIN_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(20))
+}
+
+/// Runs one shutdown phase, giving up after `timeout`.
+///
+/// Returns `None` if the phase timed out.
+pub async fn phase<T>(
+    name: &str,
+    timeout: Option<Duration>,
+    future: impl Future<Output = T>,
+) -> Option<T> {
+    let timeout = timeout.unwrap_or(PHASE_TIMEOUT);
+    info!("shutdown: {}", name);
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => {
+            info!("shutdown: {} done", name);
+            Some(result)
+        }
+        Err(_) => {
+            warn!("shutdown: {} timed out after {:?}", name, timeout);
+            None
+        }
+    }
+}
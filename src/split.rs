@@ -0,0 +1,111 @@
+use axum::http::{HeaderMap, HeaderValue};
+
+use crate::{
+    hash, matcher,
+    types::{RedirectSplit, RedirectSticky, RedirectTo},
+};
+
+/// A random client id, hex encoded.
+fn new_client_id() -> String {
+    format!("{:016x}", hash::random_u64())
+}
+
+/// The target of the weighted split `client_id` falls into.
+fn pick<'a>(split: &'a RedirectSplit, client_id: &str) -> Option<&'a RedirectTo> {
+    let total: u64 = split.targets.iter().map(|t| u64::from(t.weight)).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut point = hash::fnv1a(client_id) % total;
+    split.targets.iter().find_map(|t| {
+        let weight = u64::from(t.weight);
+        if point < weight {
+            Some(&t.to)
+        } else {
+            point -= weight;
+            None
+        }
+    })
+}
+
+fn set_cookie(sticky: &RedirectSticky, client_id: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(&format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax",
+        sticky.cookie_name, client_id, sticky.max_age_seconds
+    ))
+    .ok()
+}
+
+/// Chooses a target for a request, with a `Set-Cookie` value for new sticky clients.
+///
+/// `None` if no target has any weight.
+pub fn choose<'a>(
+    split: &'a RedirectSplit,
+    headers: &HeaderMap,
+) -> Option<(&'a RedirectTo, Option<HeaderValue>)> {
+    let assigned = split.sticky.as_ref().and_then(|sticky| {
+        matcher::cookies(headers)
+            .get(sticky.cookie_name.as_str())
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string())
+    });
+
+    match (assigned, &split.sticky) {
+        (Some(client_id), _) => Some((pick(split, &client_id)?, None)),
+        (None, sticky) => {
+            let client_id = new_client_id();
+            let target = pick(split, &client_id)?;
+            Some((
+                target,
+                sticky.as_ref().and_then(|s| set_cookie(s, &client_id)),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RedirectSplitTarget;
+
+    fn split(weights: &[u32]) -> RedirectSplit {
+        RedirectSplit {
+            targets: weights
+                .iter()
+                .enumerate()
+                .map(|(i, weight)| RedirectSplitTarget {
+                    weight: *weight,
+                    to: RedirectTo {
+                        uri: format!("https://{i}.example.org"),
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+            sticky: None,
+        }
+    }
+
+    #[test]
+    fn zero_weights_pick_nothing() {
+        assert!(pick(&split(&[]), "client").is_none());
+        assert!(pick(&split(&[0, 0]), "client").is_none());
+        assert!(choose(&split(&[0]), &HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn targets_without_weight_are_never_picked() {
+        let split = split(&[0, 1, 0]);
+        for client in ["a", "b", "c", "d"] {
+            assert_eq!(pick(&split, client).unwrap().uri, "https://1.example.org");
+        }
+    }
+
+    #[test]
+    fn clients_stay_on_their_target() {
+        let split = split(&[1, 1, 1]);
+        assert_eq!(
+            pick(&split, "client").unwrap().uri,
+            pick(&split, "client").unwrap().uri
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeSet;
+use std::env;
+
+use anyhow::{Context as _, anyhow};
+use axum::http::Uri;
+use regex::Regex;
+
+use crate::{
+    host,
+    types::{Redirect, RedirectMode, RedirectSpec, RedirectTo},
+};
+
+/// The scheme of `uri`, lowercased, `None` if it has none.
+pub fn scheme(uri: &str) -> Option<String> {
+    let (scheme, _) = uri.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// Whether `uri` uses `http` or `https`.
+pub fn is_http(uri: &str) -> bool {
+    scheme(uri).is_some_and(|s| s == "http" || s == "https")
+}
+
+/// All targets of a spec with the path of their field, empty for modes without targets.
+pub fn targets(spec: &RedirectSpec) -> Vec<(&'static str, &RedirectTo)> {
+    match spec.mode {
+        RedirectMode::Redirect | RedirectMode::Proxy => {}
+        RedirectMode::CanonicalHost | RedirectMode::Page => return Vec::new(),
+    }
+    let split = spec
+        .split
+        .iter()
+        .flat_map(|s| &s.targets)
+        .map(|t| ("spec.split.targets.to.uri", &t.to));
+    std::iter::once(("spec.to.uri", &spec.to))
+        .chain(
+            spec.overrides
+                .iter()
+                .map(|o| ("spec.overrides.to.uri", &o.to)),
+        )
+        .chain(split)
+        .filter(|(_, to)| !to.uri.is_empty())
+        .collect()
+}
+
+/// Checks that a target is a usable URI for `mode`.
+///
+/// Schemes other than `http` and `https` need `allowNonHttpScheme` and cannot be proxied.
+pub fn check(to: &RedirectTo, mode: &RedirectMode) -> Result<(), String> {
+    let Some(scheme) = scheme(&to.uri) else {
+        return Err(format!("{} has no scheme", to.uri));
+    };
+    if scheme == "http" || scheme == "https" {
+        return match to.uri.parse::<Uri>() {
+            Ok(uri) if uri.host().is_some_and(|h| !h.is_empty()) => Ok(()),
+            _ => Err(format!("{} is not a valid URI", to.uri)),
+        };
+    }
+    if *mode == RedirectMode::Proxy {
+        Err(format!(
+            "{} cannot be proxied, only http and https can",
+            to.uri
+        ))
+    } else if !to.allow_non_http_scheme {
+        Err(format!(
+            "{} uses scheme {}, set allowNonHttpScheme to allow it",
+            to.uri, scheme
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks all targets of a spec, returning the problems found.
+pub fn check_all(spec: &RedirectSpec) -> Vec<String> {
+    targets(spec)
+        .into_iter()
+        .filter_map(|(field, to)| check(to, &spec.mode).err().map(|e| format!("{field}: {e}")))
+        .collect()
+}
+
+/// Condition type reporting whether a target is outside the allowed domains.
+pub const CONDITION_TARGET_DENIED: &str = "TargetDenied";
+
+/// Operator-wide restriction of the domains Redirects may lead to.
+///
+/// Configured with `TARGET_ALLOWED_DOMAINS`, a comma separated list of domains
+/// allowing themselves and their subdomains, and `TARGET_ALLOWED_PATTERN`, a
+/// regular expression the whole target host has to match. A target is allowed
+/// if either allows it, all targets are allowed if neither is set.
+#[derive(Clone, Debug, Default)]
+pub struct TargetPolicy {
+    domains: Vec<String>,
+    pattern: Option<Regex>,
+}
+
+impl TargetPolicy {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let domains = match env::var("TARGET_ALLOWED_DOMAINS") {
+            Ok(domains) => domains
+                .split(',')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(|d| {
+                    host::normalize(d.trim_start_matches("*."))
+                        .map_err(|_| anyhow!("invalid domain {d} in TARGET_ALLOWED_DOMAINS"))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            Err(_) => Vec::new(),
+        };
+        let pattern = env::var("TARGET_ALLOWED_PATTERN")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(|p| Regex::new(&format!("^(?:{p})$")))
+            .transpose()
+            .context("invalid TARGET_ALLOWED_PATTERN")?;
+        Ok(Self { domains, pattern })
+    }
+
+    /// Whether targets are restricted at all.
+    pub fn is_restricted(&self) -> bool {
+        !self.domains.is_empty() || self.pattern.is_some()
+    }
+
+    /// Whether a normalized host is allowed.
+    pub fn allows_host(&self, host: &str) -> bool {
+        !self.is_restricted()
+            || self.domains.iter().any(|d| {
+                host == d
+                    || host
+                        .strip_suffix(d.as_str())
+                        .is_some_and(|s| s.ends_with('.'))
+            })
+            || self.pattern.as_ref().is_some_and(|p| p.is_match(host))
+    }
+
+    /// Whether a target is allowed, relative targets stay on the requested host.
+    pub fn allows(&self, uri: &str) -> bool {
+        if !self.is_restricted() {
+            return true;
+        }
+        match destination(uri) {
+            Ok(Some(host)) => self.allows_host(&host),
+            Ok(None) => true,
+            Err(()) => false,
+        }
+    }
+
+    /// Targets of a Redirect that are not allowed, including its short links and path map.
+    pub fn denied<'a>(
+        &self,
+        spec: &'a RedirectSpec,
+        path_map: impl IntoIterator<Item = &'a String>,
+    ) -> Vec<String> {
+        if !self.is_restricted() {
+            return Vec::new();
+        }
+        let canonical = host::canonical_host(spec)
+            .filter(|h| !self.allows_host(h))
+            .map(|h| format!("canonicalHost {h}"));
+        let targets = targets(spec)
+            .into_iter()
+            .map(|(_, to)| &to.uri)
+            .chain(spec.short_links.iter().map(|l| &l.to))
+            .chain(path_map.into_iter().filter(|t| !t.is_empty()))
+            .filter(|uri| !self.allows(uri))
+            .cloned();
+        canonical
+            .into_iter()
+            .chain(targets)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Whether the controller found targets of a Redirect outside the allowed domains.
+pub fn is_denied(redirect: &Redirect) -> bool {
+    redirect.status.as_ref().is_some_and(|status| {
+        status
+            .conditions
+            .iter()
+            .any(|c| c.type_ == CONDITION_TARGET_DENIED && c.status == "True")
+    })
+}
+
+/// The normalized host a target leads to, `None` for paths on the requested host.
+///
+/// Backslashes count as slashes, as they do for browsers. Errs for targets
+/// without a host, like `mailto:`, and for invalid hosts.
+fn destination(uri: &str) -> Result<Option<String>, ()> {
+    let uri = uri.trim().replace('\\', "/");
+    let scheme = scheme(&uri);
+    let rest = match &scheme {
+        Some(scheme) => &uri[scheme.len() + 1..],
+        None => uri.as_str(),
+    };
+    let Some(rest) = rest.strip_prefix("//") else {
+        return if scheme.is_some() { Err(()) } else { Ok(None) };
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_and_port = authority.rsplit('@').next().unwrap_or_default();
+    let host = match host_and_port.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => match host_and_port.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+            _ => host_and_port,
+        },
+    };
+    if host.is_empty() {
+        return Err(());
+    }
+    host::normalize(host).map(Some).map_err(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to(uri: &str) -> RedirectTo {
+        RedirectTo {
+            uri: uri.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_the_destination_host() {
+        assert_eq!(
+            destination("https://user@Bücher.example:8443/path"),
+            Ok(Some("xn--bcher-kva.example".to_string()))
+        );
+        assert_eq!(destination("/elsewhere"), Ok(None));
+        // browsers read backslashes as slashes
+        assert_eq!(
+            destination("/\\evil.example/"),
+            Ok(Some("evil.example".to_string()))
+        );
+        assert_eq!(destination("mailto:info@example.com"), Err(()));
+        assert_eq!(destination("https:///path"), Err(()));
+    }
+
+    #[test]
+    fn finds_the_scheme() {
+        assert_eq!(scheme("HTTPS://example.org"), Some("https".to_string()));
+        assert_eq!(scheme("web+app:open"), Some("web+app".to_string()));
+        assert_eq!(scheme("/path:with:colons"), None);
+        assert!(!is_http("mailto:info@example.org"));
+    }
+
+    #[test]
+    fn checks_schemes_per_mode() {
+        assert!(check(&to("https://example.org/"), &RedirectMode::Redirect).is_ok());
+        assert!(check(&to("example.org"), &RedirectMode::Redirect).is_err());
+        assert!(check(&to("https://"), &RedirectMode::Redirect).is_err());
+        assert!(check(&to("mailto:info@example.org"), &RedirectMode::Redirect).is_err());
+
+        let mailto = RedirectTo {
+            allow_non_http_scheme: true,
+            ..to("mailto:info@example.org")
+        };
+        assert!(check(&mailto, &RedirectMode::Redirect).is_ok());
+        assert!(check(&mailto, &RedirectMode::Proxy).is_err());
+    }
+
+    #[test]
+    fn policies_allow_domains_and_subdomains() {
+        let policy = TargetPolicy {
+            domains: vec!["example.org".to_string()],
+            pattern: Some(Regex::new("^(?:cdn[0-9]\\.example\\.net)$").unwrap()),
+        };
+        assert!(policy.allows("https://example.org/"));
+        assert!(policy.allows("https://www.example.org/"));
+        assert!(policy.allows("https://cdn1.example.net/"));
+        assert!(policy.allows("/relative"));
+        assert!(!policy.allows("https://notexample.org/"));
+        assert!(!policy.allows("https://example.org.evil.example/"));
+        assert!(!policy.allows("mailto:info@example.org"));
+        assert!(TargetPolicy::default().allows("https://anywhere.example/"));
+    }
+
+    #[test]
+    fn policies_report_denied_targets() {
+        let policy = TargetPolicy {
+            domains: vec!["example.org".to_string()],
+            pattern: None,
+        };
+        let spec: RedirectSpec = serde_json::from_value(serde_json::json!({
+            "hosts": ["old.example.com"],
+            "to": { "uri": "https://example.org/" },
+            "ingress": {},
+            "overrides": [{ "host": "old.example.com", "to": { "uri": "https://evil.example/" } }],
+            "shortLinks": [{ "to": "https://example.org/a" }],
+        }))
+        .unwrap();
+        let path_map = ["https://other.example/".to_string(), String::new()];
+        assert_eq!(
+            policy.denied(&spec, &path_map),
+            ["https://evil.example/", "https://other.example/"]
+        );
+    }
+}
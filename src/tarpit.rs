@@ -0,0 +1,116 @@
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::{HeaderMap, header};
+use tokio::sync::Semaphore;
+
+use crate::{pattern, types::Redirect};
+
+/// Delays responses to scrapers, with a bounded delay and number of delayed requests.
+#[derive(Clone)]
+pub struct Tarpit {
+    permits: Arc<Semaphore>,
+    max_delay: Duration,
+}
+
+impl Tarpit {
+    /// Reads `TARPIT_MAX_MS` (default 10000) and `TARPIT_CONCURRENCY` (default 256).
+    pub fn from_env() -> anyhow::Result<Self> {
+        let max_delay = match env::var("TARPIT_MAX_MS") {
+            Ok(ms) => Duration::from_millis(ms.parse()?),
+            Err(_) => Duration::from_secs(10),
+        };
+        let concurrency = match env::var("TARPIT_CONCURRENCY") {
+            Ok(n) => n.parse()?,
+            Err(_) => 256,
+        };
+        Ok(Self {
+            permits: Arc::new(Semaphore::new(concurrency)),
+            max_delay,
+        })
+    }
+
+    /// The delay for a request, if the Redirect tarpits it.
+    ///
+    /// Requests are tarpitted when they match any pattern of each non-empty list, an empty
+    /// list is ignored rather than matching nothing. Invalid patterns never match and are
+    /// reported by the linter.
+    pub fn delay_for(
+        &self,
+        redirect: &Redirect,
+        path: &str,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        let ms = redirect.spec.tarpit_ms.filter(|ms| *ms > 0)?;
+        let rules = &redirect.spec.tarpit_match;
+        if rules.user_agents.is_empty() && rules.paths.is_empty() {
+            return None;
+        }
+
+        let any_matches = |patterns: &[String], value: &str| {
+            patterns.is_empty()
+                || patterns
+                    .iter()
+                    .any(|p| pattern::compile(p).is_ok_and(|r| r.is_match(value)))
+        };
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        (any_matches(&rules.user_agents, user_agent) && any_matches(&rules.paths, path))
+            .then(|| Duration::from_millis(ms.into()).min(self.max_delay))
+    }
+
+    /// Waits for `delay`, `false` if too many requests are already waiting.
+    pub async fn wait(&self, delay: Duration) -> bool {
+        let Ok(_permit) = self.permits.try_acquire() else {
+            return false;
+        };
+        tokio::time::sleep(delay).await;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::redirect_from_json;
+
+    fn tarpitting(user_agents: &[&str], paths: &[&str]) -> Redirect {
+        redirect_from_json(json!({
+            "metadata": { "name": "scrapers" },
+            "spec": {
+                "hosts": [],
+                "ingress": {},
+                "tarpitMs": 5000,
+                "tarpitMatch": { "userAgents": user_agents, "paths": paths },
+            },
+        }))
+    }
+
+    #[test]
+    fn ignores_empty_pattern_lists() {
+        let tarpit = Tarpit {
+            permits: Arc::new(Semaphore::new(1)),
+            max_delay: Duration::from_secs(10),
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, HeaderValue::from_static("GPTBot/1.0"));
+        let delay = Some(Duration::from_secs(5));
+
+        let by_path = tarpitting(&[], &["^/wp-"]);
+        assert_eq!(tarpit.delay_for(&by_path, "/wp-login.php", &headers), delay);
+        assert_eq!(tarpit.delay_for(&by_path, "/", &headers), None);
+
+        let by_agent = tarpitting(&["GPTBot"], &[]);
+        assert_eq!(tarpit.delay_for(&by_agent, "/", &headers), delay);
+        assert_eq!(tarpit.delay_for(&by_agent, "/", &HeaderMap::new()), None);
+
+        let neither = tarpitting(&[], &[]);
+        assert_eq!(tarpit.delay_for(&neither, "/wp-login.php", &headers), None);
+    }
+}
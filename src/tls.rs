@@ -0,0 +1,132 @@
+use k8s_openapi::{
+    api::core::v1::Secret,
+    apimachinery::pkg::apis::meta::v1::{Condition, Time},
+    jiff::Timestamp,
+};
+use kube::{Api, Client, ResourceExt, api::DynamicObject};
+use tracing::warn;
+
+use crate::{
+    controller::{CONDITION_TLS_READY, condition},
+    types::RedirectStatusTlsSecret,
+};
+
+/// Only TLS Secrets are watched, as selected by this field selector.
+pub const SECRET_FIELD_SELECTOR: &str = "type=kubernetes.io/tls";
+
+/// The TLS Secrets `ingresses` refer to.
+pub fn secrets(ingresses: &[DynamicObject]) -> Vec<RedirectStatusTlsSecret> {
+    let mut secrets: Vec<RedirectStatusTlsSecret> = ingresses
+        .iter()
+        .flat_map(|ingress| {
+            let namespace = ingress.namespace().unwrap_or_default();
+            ingress
+                .data
+                .pointer("/spec/tls")
+                .and_then(|tls| tls.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|tls| tls.get("secretName")?.as_str())
+                .map(move |name| RedirectStatusTlsSecret {
+                    name: name.to_string(),
+                    namespace: namespace.clone(),
+                })
+        })
+        .collect();
+    secrets.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+    secrets.dedup_by(|a, b| a.namespace == b.namespace && a.name == b.name);
+    secrets
+}
+
+/// Why a Secret does not hold a usable certificate.
+enum Unusable {
+    Missing,
+    Unreadable(String),
+    Invalid(String),
+    Expired(Timestamp),
+}
+
+/// When the certificate in `secret` expires.
+fn not_after(secret: &Secret) -> Result<Timestamp, Unusable> {
+    let pem = secret
+        .data
+        .as_ref()
+        .and_then(|data| data.get("tls.crt"))
+        .ok_or_else(|| Unusable::Invalid("no tls.crt".to_string()))?;
+    let (_, pem) = x509_parser::pem::parse_x509_pem(&pem.0)
+        .map_err(|e| Unusable::Invalid(format!("tls.crt is not PEM: {e}")))?;
+    let certificate = pem
+        .parse_x509()
+        .map_err(|e| Unusable::Invalid(format!("tls.crt is not a certificate: {e}")))?;
+    let not_after = Timestamp::from_second(certificate.validity().not_after.timestamp())
+        .map_err(|e| Unusable::Invalid(format!("invalid expiry: {e}")))?;
+    if not_after <= Timestamp::now() {
+        return Err(Unusable::Expired(not_after));
+    }
+    Ok(not_after)
+}
+
+async fn check(client: &Client, secret: &RedirectStatusTlsSecret) -> Result<Timestamp, Unusable> {
+    let api: Api<Secret> = Api::namespaced(client.clone(), &secret.namespace);
+    match api.get_opt(&secret.name).await {
+        Ok(Some(s)) => not_after(&s),
+        Ok(None) => Err(Unusable::Missing),
+        Err(e) => {
+            warn!(
+                "cannot read TLS Secret {}/{}: {:?}",
+                secret.namespace, secret.name, e
+            );
+            Err(Unusable::Unreadable(e.to_string()))
+        }
+    }
+}
+
+/// Checks that all `secrets` hold unexpired certificates.
+///
+/// Returns the `TLSReady` condition and when the first certificate expires.
+pub async fn readiness(
+    client: &Client,
+    secrets: &[RedirectStatusTlsSecret],
+) -> (Condition, Option<Time>) {
+    let mut expiry: Option<Timestamp> = None;
+    let mut problems = Vec::new();
+    let mut reason = "Valid";
+    for secret in secrets {
+        let name = format!("{}/{}", secret.namespace, secret.name);
+        match check(client, secret).await {
+            Ok(not_after) => expiry = Some(expiry.map_or(not_after, |e| e.min(not_after))),
+            Err(Unusable::Missing) => {
+                reason = "SecretMissing";
+                problems.push(format!("{name} does not exist"));
+            }
+            Err(Unusable::Unreadable(e)) => {
+                reason = "SecretUnreadable";
+                problems.push(format!("{name} cannot be read: {e}"));
+            }
+            Err(Unusable::Invalid(e)) => {
+                reason = "SecretInvalid";
+                problems.push(format!("{name}: {e}"));
+            }
+            Err(Unusable::Expired(not_after)) => {
+                reason = "CertificateExpired";
+                expiry = Some(expiry.map_or(not_after, |e| e.min(not_after)));
+                problems.push(format!("{name} expired at {not_after}"));
+            }
+        }
+    }
+
+    let condition = if problems.is_empty() {
+        condition(
+            CONDITION_TLS_READY,
+            true,
+            reason,
+            match expiry {
+                Some(expiry) => format!("certificates are valid until {expiry}"),
+                None => "no TLS Secrets in use".to_string(),
+            },
+        )
+    } else {
+        condition(CONDITION_TLS_READY, false, reason, problems.join("; "))
+    };
+    (condition, expiry.map(Time))
+}
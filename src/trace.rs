@@ -0,0 +1,58 @@
+use axum::{
+    http::{HeaderMap, HeaderValue},
+    response::Response,
+};
+
+/// Request header carrying the shared secret that enables tracing.
+pub const DEBUG_HEADER: &str = "x-redirect-debug";
+
+/// Response header with the decision trace.
+pub const TRACE_HEADER: &str = "x-redirect-trace";
+
+/// Compares without short-circuiting on the first differing byte.
+pub(crate) fn secret_matches(expected: &str, given: &[u8]) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// The decisions taken for one request, only recorded when enabled.
+#[derive(Debug, Default)]
+pub struct Trace {
+    steps: Option<Vec<String>>,
+}
+
+impl Trace {
+    /// Enabled if `secret` is configured and the request sends it in `x-redirect-debug`.
+    pub fn from_request(secret: Option<&str>, headers: &HeaderMap) -> Self {
+        let enabled = secret
+            .zip(headers.get(DEBUG_HEADER))
+            .is_some_and(|(secret, given)| {
+                !secret.is_empty() && secret_matches(secret, given.as_bytes())
+            });
+        Self {
+            steps: enabled.then(Vec::new),
+        }
+    }
+
+    /// Records a step, `step` is only evaluated when tracing.
+    pub fn step(&mut self, step: impl FnOnce() -> String) {
+        if let Some(steps) = &mut self.steps {
+            steps.push(step());
+        }
+    }
+
+    /// Adds the trace to `response` as `x-redirect-trace`.
+    pub fn attach(self, mut response: Response) -> Response {
+        let Some(steps) = self.steps else {
+            return response;
+        };
+        if let Ok(value) = HeaderValue::from_str(&steps.join("; ")) {
+            response.headers_mut().insert(TRACE_HEADER, value);
+        }
+        response
+    }
+}
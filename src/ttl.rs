@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use k8s_openapi::jiff::{SignedDuration, Timestamp};
+
+use crate::types::Redirect;
+
+/// Condition type reporting whether `spec.ttl` is usable.
+pub const CONDITION_TTL_VALID: &str = "TTLValid";
+
+/// When a Redirect expires, per `spec.ttl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// this long after its creation
+    AfterCreation(Duration),
+    /// at this time
+    At(Timestamp),
+}
+
+/// Parses durations like `90s`, `30m`, `12h`, `7d` or `1d12h`.
+pub fn parse_duration(ttl: &str) -> Option<Duration> {
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in ttl.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return None,
+        };
+        let value: u64 = number.parse().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        number.clear();
+    }
+    (number.is_empty() && total > 0).then(|| Duration::from_secs(total))
+}
+
+/// Parses `spec.ttl`, a duration like `30d` or a time like `2030-01-01T00:00:00Z`.
+pub fn parse(ttl: &str) -> Option<Expiry> {
+    match parse_duration(ttl) {
+        Some(duration) => Some(Expiry::AfterCreation(duration)),
+        None => ttl.trim().parse().ok().map(Expiry::At),
+    }
+}
+
+/// When a Redirect expires, none without a valid TTL.
+pub fn expires_at(redirect: &Redirect) -> Option<Timestamp> {
+    match parse(redirect.spec.ttl.as_deref()?)? {
+        Expiry::AfterCreation(ttl) => redirect
+            .metadata
+            .creation_timestamp
+            .as_ref()?
+            .0
+            .checked_add(SignedDuration::try_from(ttl).ok()?)
+            .ok(),
+        Expiry::At(at) => Some(at),
+    }
+}
+
+/// Whether a Redirect has expired, it is not served anymore even before it is deleted.
+pub fn is_expired(redirect: &Redirect) -> bool {
+    expires_at(redirect).is_some_and(|t| t <= Timestamp::now())
+}
+
+/// Checks `spec.ttl`, a Redirect with an invalid one never expires.
+pub fn check(ttl: &str) -> Result<Expiry, String> {
+    parse(ttl)
+        .ok_or_else(|| format!("{ttl} is neither a duration like 30d nor a time, never expiring"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::redirect_from_json;
+
+    fn with_ttl(ttl: &str) -> Redirect {
+        redirect_from_json(json!({
+            "metadata": { "name": "campaign", "creationTimestamp": "2030-01-01T00:00:00Z" },
+            "spec": { "hosts": [], "ingress": {}, "ttl": ttl },
+        }))
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(
+            parse_duration(" 1d12h "),
+            Some(Duration::from_secs(36 * 60 * 60))
+        );
+        for invalid in ["", "0s", "12", "h", "1w", "-1d", "99999999999999999999d"] {
+            assert_eq!(parse_duration(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_durations_and_times() {
+        assert_eq!(
+            parse("30m"),
+            Some(Expiry::AfterCreation(Duration::from_secs(30 * 60)))
+        );
+        assert_eq!(
+            parse("2030-06-01T00:00:00Z"),
+            Some(Expiry::At("2030-06-01T00:00:00Z".parse().unwrap()))
+        );
+        assert_eq!(parse("next week"), None);
+    }
+
+    #[test]
+    fn expires_after_creation_or_at_a_time() {
+        assert_eq!(
+            expires_at(&with_ttl("1d")),
+            Some("2030-01-02T00:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            expires_at(&with_ttl("2029-12-31T00:00:00Z")),
+            Some("2029-12-31T00:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn bad_durations_never_expire() {
+        let redirect = with_ttl("1 fortnight");
+        assert_eq!(expires_at(&redirect), None);
+        assert!(!is_expired(&redirect));
+        assert!(check("1 fortnight").is_err());
+        assert!(check("1d").is_ok());
+    }
+}
@@ -1,20 +1,81 @@
 use std::collections::{BTreeMap, HashSet};
 
-use kube::CustomResource;
+use k8s_openapi::{
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+        CustomResourceConversion, CustomResourceDefinition, JSONSchemaProps, ServiceReference,
+        WebhookClientConfig, WebhookConversion,
+    },
+    apimachinery::pkg::apis::meta::v1::{Condition, Time},
+};
+use kube::{CustomResource, CustomResourceExt, core::crd::merge_crds};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod v1beta1;
+
 #[allow(unused)]
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Failed to create Ingress: {0}")]
     IngressCreationFailed(#[source] kube::Error),
-    #[error("Failed to create Ingress: {0}")]
+    #[error("Failed to delete Ingress: {0}")]
     IngressDeletionFailed(#[source] kube::Error),
+    #[error("Failed to get Ingress: {0}")]
+    IngressFetchFailed(#[source] kube::Error),
+    #[error("Failed to list Ingresses: {0}")]
+    IngressListFailed(#[source] kube::Error),
+    #[error("Failed to get generated object: {0}")]
+    ObjectFetchFailed(#[source] kube::Error),
+    #[error("Failed to list generated objects: {0}")]
+    ObjectListFailed(#[source] kube::Error),
+    #[error("Failed to apply Service: {0}")]
+    ServiceApplyFailed(#[source] kube::Error),
+    #[error("Failed to create Route: {0}")]
+    RouteCreationFailed(#[source] kube::Error),
+    #[error("Failed to delete Route: {0}")]
+    RouteDeletionFailed(#[source] kube::Error),
+    #[error("Failed to create VirtualService: {0}")]
+    VirtualServiceCreationFailed(#[source] kube::Error),
+    #[error("Failed to delete VirtualService: {0}")]
+    VirtualServiceDeletionFailed(#[source] kube::Error),
+    #[error("Failed to create HTTPProxy: {0}")]
+    HttpProxyCreationFailed(#[source] kube::Error),
+    #[error("Failed to delete HTTPProxy: {0}")]
+    HttpProxyDeletionFailed(#[source] kube::Error),
+    #[error("Failed to create Mapping: {0}")]
+    MappingCreationFailed(#[source] kube::Error),
+    #[error("Failed to delete Mapping: {0}")]
+    MappingDeletionFailed(#[source] kube::Error),
+    #[error("Failed to create KongPlugin: {0}")]
+    KongPluginCreationFailed(#[source] kube::Error),
+    #[error("Failed to delete KongPlugin: {0}")]
+    KongPluginDeletionFailed(#[source] kube::Error),
+    #[error("Failed to create Certificate: {0}")]
+    CertificateCreationFailed(#[source] kube::Error),
+    #[error("Failed to delete Certificate: {0}")]
+    CertificateDeletionFailed(#[source] kube::Error),
     #[error("Failed to update RedirectStatus: {0}")]
     StatusUpdateFailed(#[source] kube::Error),
+    #[error("Failed to apply generated Redirect: {0}")]
+    RedirectApplyFailed(#[source] kube::Error),
+    #[error("Failed to delete generated Redirect: {0}")]
+    RedirectDeletionFailed(#[source] kube::Error),
+    #[error("Failed to get Redirect: {0}")]
+    RedirectFetchFailed(#[source] kube::Error),
+    #[error("Failed to list generated Redirects: {0}")]
+    RedirectListFailed(#[source] kube::Error),
+    #[error("Failed to delete expired Redirect: {0}")]
+    RedirectExpiryFailed(#[source] kube::Error),
+    #[error("Failed to get Namespace: {0}")]
+    NamespaceFetchFailed(#[source] kube::Error),
+    #[error("Failed to get ConfigMap: {0}")]
+    ConfigMapFetchFailed(#[source] kube::Error),
+    #[error("Failed to get wildcard TLS Secret: {0}")]
+    SecretFetchFailed(#[source] kube::Error),
+    #[error("Failed to copy wildcard TLS Secret: {0}")]
+    SecretCopyFailed(#[source] kube::Error),
 }
 
 impl Error {
@@ -22,6 +83,17 @@ impl Error {
     pub(crate) fn metric_label(&self) -> String {
         format!("{self:?}").to_lowercase()
     }
+
+    /// The variant name, e.g. `IngressDeletionFailed`, for Event reasons.
+    #[allow(unused)]
+    pub(crate) fn reason(&self) -> String {
+        let debug = format!("{self:?}");
+        debug
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
 }
 
 #[derive(CustomResource, Debug, Serialize, Deserialize, Default, Clone, JsonSchema)]
@@ -32,19 +104,405 @@ impl Error {
     namespaced
 )]
 #[kube(status = "RedirectStatus")]
+#[kube(
+    printcolumn = r#"{"name":"Hosts", "type":"string", "description":"hosts served", "jsonPath":".status.servedHosts"}"#,
+    printcolumn = r#"{"name":"Target", "type":"string", "description":"where requests go", "jsonPath":".status.target"}"#,
+    printcolumn = r#"{"name":"Ingress", "type":"string", "description":"the first Ingress", "jsonPath":".status.ingress.name", "priority":1}"#,
+    printcolumn = r#"{"name":"Ready", "type":"string", "description":"whether the Redirect is served as specified", "jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Expires", "type":"string", "description":"when the Redirect expires", "jsonPath":".status.expiresAt"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
 #[serde(rename_all = "camelCase")]
 pub struct RedirectSpec {
     pub hosts: HashSet<String>,
+    #[serde(default)]
     pub to: RedirectTo,
+
+    #[serde(default)]
+    pub mode: RedirectMode,
+    /// host all other hosts redirect to in `canonicalHost` mode, keeping scheme, path and query
+    pub canonical_host: Option<String>,
     pub ingress: RedirectIngress,
+    /// OpenShift Routes, one per host
+    #[serde(default)]
+    pub route: RedirectRoute,
+    /// an Istio VirtualService for all hosts, for Istio gateways
+    #[serde(default)]
+    pub virtual_service: RedirectVirtualService,
+    /// Contour HTTPProxies, one per host
+    #[serde(default)]
+    pub http_proxy: RedirectHttpProxy,
+    /// Emissary Mappings, one per host and path
+    #[serde(default)]
+    pub mapping: RedirectMapping,
+
+    /// short codes resolved under all hosts, codes are generated if unset
+    #[serde(default)]
+    pub short_links: Vec<RedirectShortLink>,
+
+    /// per-path targets loaded from a ConfigMap
+    pub path_map: Option<RedirectPathMap>,
+
+    /// page served for paths without a target
+    pub not_found: Option<RedirectNotFound>,
+
+    /// page served in `page` mode
+    pub page: Option<RedirectPage>,
+
+    /// serve a page linking to the target instead of redirecting
+    pub interstitial: Option<RedirectInterstitial>,
+
+    /// stop redirecting without deleting the Redirect
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub pause: RedirectPause,
+
+    /// targets for individual hosts from `hosts`, overriding `to`
+    #[serde(default)]
+    pub overrides: Vec<RedirectHostOverride>,
+
+    /// conditions requests have to meet for the Redirect to apply
+    #[serde(default, rename = "match")]
+    pub match_: RedirectMatch,
+
+    /// delete the Redirect this long after its creation, e.g. `30d` or `12h`, or at a time
+    /// like `2030-01-01T00:00:00Z`
+    pub ttl: Option<String>,
+
+    /// split requests across weighted targets instead of `to`, overrides still apply
+    pub split: Option<RedirectSplit>,
+    /// delay responses to requests matching `tarpitMatch` by this many milliseconds
+    pub tarpit_ms: Option<u32>,
+    #[serde(default)]
+    pub tarpit_match: RedirectTarpitMatch,
+    /// `Link` headers sent with the redirect, e.g. `rel: canonical`
+    #[serde(default)]
+    pub links: Vec<RedirectLink>,
+    /// reject changes to `hosts`, and unsetting this, at the API server
+    #[serde(default)]
+    pub immutable_hosts: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectLink {
+    /// link relation, e.g. `canonical` or `successor-version`
+    pub rel: String,
+    /// linked URI, the redirect target if unset
+    pub uri: Option<String>,
+}
+
+#[allow(unused)]
+impl RedirectSpec {
+    /// Whether an Ingress should exist for this Redirect.
+    pub fn wants_ingress(&self) -> bool {
+        self.ingress.enabled && !(self.paused && self.pause.remove_ingress)
+    }
+
+    /// Whether Routes should exist for this Redirect right now.
+    pub fn wants_route(&self) -> bool {
+        self.route.enabled && !(self.paused && self.pause.remove_ingress)
+    }
+
+    /// Whether a VirtualService should exist for this Redirect right now.
+    pub fn wants_virtual_service(&self) -> bool {
+        self.virtual_service.enabled && !(self.paused && self.pause.remove_ingress)
+    }
+
+    /// Whether HTTPProxies should exist for this Redirect right now.
+    pub fn wants_http_proxy(&self) -> bool {
+        self.http_proxy.enabled && !(self.paused && self.pause.remove_ingress)
+    }
+
+    /// Whether Mappings should exist for this Redirect right now.
+    ///
+    /// `default` is the operator's setting for Redirects not saying.
+    pub fn wants_mapping(&self, default: bool) -> bool {
+        self.mapping.enabled.unwrap_or(default) && !(self.paused && self.pause.remove_ingress)
+    }
+
+    /// Names of all ConfigMaps the Redirect references.
+    pub fn config_map_names(&self) -> impl Iterator<Item = &str> {
+        let path_map = self
+            .path_map
+            .as_ref()
+            .map(|p| p.config_map_ref.name.as_str());
+        let not_found = self
+            .not_found
+            .as_ref()
+            .and_then(|n| n.config_map_ref.as_ref())
+            .map(|c| c.name.as_str());
+        let interstitial = self
+            .interstitial
+            .as_ref()
+            .and_then(|i| i.config_map_name.as_deref());
+        let page = self
+            .page
+            .as_ref()
+            .and_then(|p| p.config_map_ref.as_ref())
+            .map(|c| c.name.as_str());
+        path_map
+            .into_iter()
+            .chain(not_found)
+            .chain(interstitial)
+            .chain(page)
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
+pub struct RedirectHostOverride {
+    pub host: String,
+    pub to: RedirectTo,
+}
+
+/// Request conditions, all of them have to be met.
+///
+/// Redirects with conditions take precedence over others for the same host.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectMatch {
+    #[serde(default)]
+    pub cookies: Vec<RedirectCookieMatch>,
+
+    /// request paths the Redirect applies to, any of them has to match
+    #[serde(default)]
+    pub paths: Vec<RedirectPathMatch>,
+
+    /// scheme of the original request, from `Forwarded` or `X-Forwarded-Proto`
+    #[serde(default)]
+    pub scheme: SchemeMatch,
+
+    /// answer requests not matching any Redirect of their host with this status, default 404
+    pub unmatched_status_code: Option<u16>,
+}
+
+#[allow(unused)]
+impl RedirectMatch {
+    /// Whether there are no conditions, matching all requests.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty() && self.paths.is_empty() && self.scheme == SchemeMatch::Any
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SchemeMatch {
+    #[default]
+    Any,
+    /// only plain HTTP requests, e.g. to upgrade them to HTTPS
+    Http,
+    Https,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectPathMatch {
+    pub path: String,
+    #[serde(default)]
+    pub path_type: PathMatchType,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, PartialEq, JsonSchema)]
+pub enum PathMatchType {
+    /// the path and everything below it, per path element like Ingress `Prefix`
+    #[default]
+    Prefix,
+    Exact,
+    /// a regular expression matching the whole path
+    Regex,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectCookieMatch {
+    pub name: String,
+    /// exact value the cookie has to have
+    pub value: Option<String>,
+    /// regular expression the whole value has to match
+    pub regex: Option<String>,
+    /// match requests lacking the (matching) cookie instead
+    #[serde(default)]
+    pub absent: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectSplit {
+    pub targets: Vec<RedirectSplitTarget>,
+    /// keep clients on the same target with an assignment cookie
+    pub sticky: Option<RedirectSticky>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectSplitTarget {
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub to: RedirectTo,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectSticky {
+    #[serde(default = "default_sticky_cookie_name")]
+    pub cookie_name: String,
+    #[serde(default = "default_sticky_max_age")]
+    pub max_age_seconds: u64,
+}
+
+impl Default for RedirectSticky {
+    fn default() -> Self {
+        Self {
+            cookie_name: default_sticky_cookie_name(),
+            max_age_seconds: default_sticky_max_age(),
+        }
+    }
+}
+
+fn default_sticky_cookie_name() -> String {
+    "redirect-split".to_string()
+}
+
+fn default_sticky_max_age() -> u64 {
+    30 * 24 * 60 * 60
+}
+
+/// Requests to tarpit, matching any pattern of each non-empty list.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectTarpitMatch {
+    /// regular expressions for the `User-Agent`, an empty list is ignored
+    #[serde(default)]
+    pub user_agents: Vec<String>,
+    /// regular expressions for the request path, an empty list is ignored
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+/// Behaviour while `paused` is set.
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectPause {
+    /// delete the Ingress while paused instead of keeping it
+    #[serde(default)]
+    pub remove_ingress: bool,
+    /// status code answered while paused, usually 404 or 503
+    #[serde(default = "default_paused_status")]
+    pub status_code: u16,
+}
+
+impl Default for RedirectPause {
+    fn default() -> Self {
+        Self {
+            remove_ingress: false,
+            status_code: default_paused_status(),
+        }
+    }
+}
+
+fn default_paused_status() -> u16 {
+    503
+}
+
+/// Interstitial page, localized by `Accept-Language`.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectInterstitial {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// ConfigMap labeled `redirect.kube.ibotty.net/config` with a `template.html` and
+    /// localized `template.<lang>.html` keys, `{{target}}` and `{{host}}` are substituted
+    pub config_map_name: Option<String>,
+}
+
+/// A custom 404 page, inline or from a ConfigMap labeled `redirect.kube.ibotty.net/config`.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectNotFound {
+    #[serde(default = "default_not_found_status")]
+    pub status_code: u16,
+    pub html: Option<String>,
+    pub config_map_ref: Option<ConfigMapKeyRef>,
+}
+
+fn default_not_found_status() -> u16 {
+    404
+}
+
+/// A static page, inline or from a ConfigMap labeled `redirect.kube.ibotty.net/config`.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectPage {
+    #[serde(default = "default_page_status")]
+    pub status_code: u16,
+    pub html: Option<String>,
+    pub config_map_ref: Option<ConfigMapKeyRef>,
+}
+
+fn default_page_status() -> u16 {
+    503
+}
+
+/// A `path → target` table in a ConfigMap labeled `redirect.kube.ibotty.net/config`.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectPathMap {
+    pub config_map_ref: ConfigMapKeyRef,
+    #[serde(default)]
+    pub format: PathMapFormat,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PathMapFormat {
+    /// `path,target` lines
+    #[default]
+    Csv,
+    /// a mapping of paths to targets
+    Yaml,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum RedirectMode {
+    /// redirect to `to`
+    #[default]
+    Redirect,
+    /// redirect to `canonicalHost`
+    CanonicalHost,
+    /// forward requests to `to` instead of redirecting, for clients not following redirects;
+    /// only if the operator sets `ALLOW_PROXY_MODE`
+    Proxy,
+    /// serve `page`, e.g. a maintenance or landing page
+    Page,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectShortLink {
+    pub code: Option<String>,
+    pub to: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct RedirectTo {
     pub uri: String,
     #[serde(default = "default_true")]
     pub include_request_uri: bool,
+    /// query parameters added to the target, e.g. `utm_source: legacy-domain`
+    #[serde(default)]
+    pub append_params: BTreeMap<String, String>,
+    /// allow schemes other than http and https, e.g. `mailto:` or app deep links
+    #[serde(default)]
+    pub allow_non_http_scheme: bool,
 }
 
 #[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
@@ -59,25 +517,296 @@ pub struct RedirectIngress {
     #[serde(default)]
     pub ingress_class_name: Option<String>,
 
+    /// publish DNS records for the hosts with external-dns
+    #[serde(default)]
+    pub external_dns: RedirectExternalDns,
+
+    /// serve the hosts from Ingresses shared with Redirects having the same settings
+    #[serde(default)]
+    pub shared: bool,
+
+    /// namespace to create the Ingress in, the operator's namespace if unset;
+    /// must be allowed by the operator
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    /// create the Ingress in the Redirect's namespace, owned by the Redirect;
+    /// the operator's default if unset
+    #[serde(default)]
+    pub same_namespace: Option<bool>,
+
+    /// paths of the Ingress rules, derived from `match.paths` if empty
+    #[serde(default)]
+    pub paths: Vec<RedirectIngressPath>,
+
+    /// let the Ingress controller answer with the redirect where the Redirect allows;
+    /// the operator's default if unset
+    pub offload: Option<IngressOffload>,
+
+    pub annotations: Option<BTreeMap<String, String>>,
+    pub labels: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectIngressPath {
+    pub path: String,
+    #[serde(default)]
+    pub path_type: IngressPathType,
+}
+
+/// Ingress controllers that can answer redirects themselves.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum IngressOffload {
+    /// route all requests to the operator
+    #[default]
+    None,
+    /// ingress-nginx `permanent-redirect` annotations
+    Nginx,
+    /// HAProxy Kubernetes Ingress Controller `request-redirect` annotations
+    Haproxy,
+    /// a Kong `redirect` KongPlugin next to the Ingress, referenced by `konghq.com/plugins`
+    Kong,
+}
+
+/// The Ingress `pathType`.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+pub enum IngressPathType {
+    #[default]
+    Prefix,
+    Exact,
+    ImplementationSpecific,
+}
+
+impl IngressPathType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Prefix => "Prefix",
+            Self::Exact => "Exact",
+            Self::ImplementationSpecific => "ImplementationSpecific",
+        }
+    }
+}
+
+impl RedirectIngress {
+    /// The namespace to create the Ingress in, `None` for the operator's namespace.
+    ///
+    /// An explicit `namespace` wins over `sameNamespace`. Shared Ingresses always
+    /// live in the operator's namespace.
+    pub fn target_namespace<'a>(
+        &'a self,
+        redirect_namespace: &'a str,
+        same_namespace_default: bool,
+    ) -> Option<&'a str> {
+        if self.shared {
+            None
+        } else if let Some(namespace) = &self.namespace {
+            Some(namespace)
+        } else {
+            self.same_namespace
+                .unwrap_or(same_namespace_default)
+                .then_some(redirect_namespace)
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectExternalDns {
+    /// the operator's default if unset
+    pub enabled: Option<bool>,
+    /// record TTL in seconds
+    pub ttl: Option<u32>,
+    /// record target, the Ingress' load balancer address if unset
+    pub target: Option<String>,
+}
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectRoute {
+    #[serde(default)]
+    pub enabled: bool,
+    /// TLS termination at the router
+    #[serde(default)]
+    pub tls: RouteTermination,
+    /// handling of plain HTTP requests with TLS, `Allow` lets them be redirected directly
+    #[serde(default)]
+    pub insecure_edge_termination_policy: InsecureEdgeTerminationPolicy,
+
+    pub annotations: Option<BTreeMap<String, String>>,
+    pub labels: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectVirtualService {
+    #[serde(default)]
+    pub enabled: bool,
+    /// gateways to bind to, as `namespace/name`
+    #[serde(default)]
+    pub gateways: Vec<String>,
+    /// let Istio answer with the redirect itself where the Redirect allows, instead of routing to the operator
+    #[serde(default)]
+    pub native: bool,
+
+    pub annotations: Option<BTreeMap<String, String>>,
+    pub labels: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectHttpProxy {
+    #[serde(default)]
+    pub enabled: bool,
+    /// the Contour instance to use
+    pub ingress_class_name: Option<String>,
+    /// TLS Secret, `namespace/name` for delegated ones, plain HTTP only if unset
+    pub tls_secret_name: Option<String>,
+
+    pub annotations: Option<BTreeMap<String, String>>,
+    pub labels: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectMapping {
+    /// the operator's `EMISSARY_MAPPINGS` if unset
+    pub enabled: Option<bool>,
+    /// the Emissary instances to use, the default one if unset
+    #[serde(default)]
+    pub ambassador_id: Vec<String>,
+    /// let Emissary answer with the redirect itself where the Redirect allows, for https targets
+    #[serde(default)]
+    pub native: bool,
+
     pub annotations: Option<BTreeMap<String, String>>,
     pub labels: Option<BTreeMap<String, String>>,
 }
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum RouteTermination {
+    #[default]
+    Edge,
+    /// plain HTTP only
+    None,
+}
+
+/// As in the Route API.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+pub enum InsecureEdgeTerminationPolicy {
+    #[default]
+    Allow,
+    Redirect,
+    None,
+}
+
 #[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RedirectIngressTLS {
     #[serde(default = "default_true")]
     pub enabled: bool,
     pub secret_name: Option<String>,
+    /// cert-manager ClusterIssuer for the Ingress, sets `cert-manager.io/cluster-issuer`
+    pub cluster_issuer: Option<String>,
+    /// cert-manager Issuer in the operator's namespace, sets `cert-manager.io/issuer`
+    pub issuer: Option<String>,
+    /// issue the certificate with a cert-manager Certificate managed by the operator
+    pub issuer_ref: Option<RedirectIssuerRef>,
+}
+
+/// A cert-manager issuer, as in `Certificate.spec.issuerRef`.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectIssuerRef {
+    pub name: String,
+    #[serde(default = "default_issuer_kind")]
+    pub kind: String,
+    #[serde(default = "default_issuer_group")]
+    pub group: String,
+}
+
+fn default_issuer_kind() -> String {
+    "ClusterIssuer".to_string()
+}
+
+fn default_issuer_group() -> String {
+    "cert-manager.io".to_string()
 }
 
 fn default_true() -> bool {
     true
 }
 
+/// A Redirect from the `metadata`, `spec` and `status` of a manifest, for tests.
+#[cfg(test)]
+pub fn redirect_from_json(mut manifest: serde_json::Value) -> Redirect {
+    manifest["apiVersion"] = "kube.ibotty.net/v1alpha1".into();
+    manifest["kind"] = "Redirect".into();
+    serde_json::from_value(manifest).unwrap()
+}
+
 #[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RedirectStatus {
+    /// the first of `ingresses`
     pub ingress: RedirectStatusIngress,
+    /// all Ingresses serving the Redirect, large host sets are split across several
+    #[serde(default)]
+    pub ingresses: Vec<RedirectStatusIngress>,
+    /// the group of shared Ingresses serving the Redirect
+    pub shared_ingress: Option<String>,
+    /// load balancer IPs and host names of the Ingresses, where DNS should point to
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// all Routes serving the Redirect
+    #[serde(default)]
+    pub routes: Vec<RedirectStatusHostObject>,
+    /// all HTTPProxies serving the Redirect
+    #[serde(default)]
+    pub http_proxies: Vec<RedirectStatusHostObject>,
+    /// all Emissary Mappings serving the Redirect
+    #[serde(default)]
+    pub mappings: Vec<RedirectStatusHostObject>,
+    /// the KongPlugin redirecting at the edge, if offloaded to Kong
+    pub kong_plugin: Option<RedirectStatusObject>,
+    /// Certificates issued for the Ingresses
+    #[serde(default)]
+    pub certificates: Vec<RedirectStatusCertificate>,
+    /// TLS Secrets the Ingresses use
+    #[serde(default)]
+    pub tls_secrets: Vec<RedirectStatusTlsSecret>,
+    /// when the first certificate in `tlsSecrets` expires
+    pub certificate_expiry: Option<Time>,
+    /// name of the VirtualService in the operator's namespace, if there is one
+    pub virtual_service: Option<String>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub short_links: Vec<RedirectStatusShortLink>,
+    /// when the Redirect expires and gets deleted
+    pub expires_at: Option<Time>,
+    /// seconds until `expiresAt` as of the last reconcile
+    pub remaining_seconds: Option<u64>,
+    /// the generation the status describes
+    pub observed_generation: Option<i64>,
+    /// served hosts, comma separated, for `kubectl get`
+    pub served_hosts: Option<String>,
+    /// hosts the web tier answers for with this Redirect, as of the last reconcile
+    #[serde(default)]
+    pub observed_hosts: Vec<String>,
+    /// where requests go, for `kubectl get`
+    pub target: Option<String>,
+    /// hosts with DNS records managed by the operator
+    #[serde(default)]
+    pub dns_records: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectStatusShortLink {
+    pub code: String,
+    pub to: String,
 }
 
 #[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
@@ -85,4 +814,275 @@ pub struct RedirectStatus {
 pub struct RedirectStatusIngress {
     pub name: String,
     pub namespace: String,
+    /// number of hosts in this Ingress
+    #[serde(default)]
+    pub hosts: usize,
+    /// the hosts themselves, to tell which ones were dropped
+    #[serde(default)]
+    pub host_names: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectStatusCertificate {
+    pub name: String,
+    pub namespace: String,
+    /// whether cert-manager reports the certificate as issued
+    #[serde(default)]
+    pub ready: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectStatusTlsSecret {
+    pub name: String,
+    pub namespace: String,
+}
+
+/// A generated object.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectStatusObject {
+    pub name: String,
+    pub namespace: String,
+}
+
+/// A generated object serving a single host.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectStatusHostObject {
+    pub name: String,
+    pub namespace: String,
+    pub host: String,
+}
+
+/// Reference to a key in a ConfigMap in the same namespace.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigMapKeyRef {
+    pub name: String,
+    pub key: String,
+}
+
+#[derive(CustomResource, Debug, Serialize, Deserialize, Default, Clone, JsonSchema)]
+#[kube(
+    group = "kube.ibotty.net",
+    version = "v1alpha1",
+    kind = "RedirectGenerator",
+    namespaced
+)]
+#[kube(status = "RedirectGeneratorStatus")]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectGeneratorSpec {
+    pub template: RedirectGeneratorTemplate,
+
+    #[serde(default)]
+    pub items: Vec<RedirectGeneratorItem>,
+
+    /// ConfigMap key with additional `host,target` lines
+    pub config_map_ref: Option<ConfigMapKeyRef>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectGeneratorTemplate {
+    #[serde(default = "default_true")]
+    pub include_request_uri: bool,
+    pub ingress: RedirectIngress,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectGeneratorItem {
+    pub host: String,
+    pub to: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectGeneratorStatus {
+    #[serde(default)]
+    pub redirects: Vec<String>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+/// Restricts which namespaces may claim hosts.
+///
+/// A host matched by any rule may only be claimed from the namespaces of the rules matching it.
+#[derive(CustomResource, Debug, Serialize, Deserialize, Default, Clone, JsonSchema)]
+#[kube(
+    group = "kube.ibotty.net",
+    version = "v1alpha1",
+    kind = "RedirectHostPolicy"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectHostPolicySpec {
+    #[serde(default)]
+    pub rules: Vec<RedirectHostPolicyRule>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectHostPolicyRule {
+    /// matches this domain and all its subdomains
+    pub suffix: Option<String>,
+    /// matches hosts this regular expression matches completely
+    pub regex: Option<String>,
+    /// namespaces that may claim matching hosts
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+}
+
+/// A member cluster Redirects are pushed to.
+///
+/// Lives in the operator's namespace, next to the Secret holding the cluster's kubeconfig.
+#[derive(CustomResource, Debug, Serialize, Deserialize, Default, Clone, JsonSchema)]
+#[kube(
+    group = "kube.ibotty.net",
+    version = "v1alpha1",
+    kind = "RedirectCluster",
+    namespaced
+)]
+#[kube(status = "RedirectClusterStatus")]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectClusterSpec {
+    /// the Secret key holding the member's kubeconfig
+    pub kubeconfig_secret_ref: SecretKeyRef,
+    /// labels a Redirect needs to be pushed, all Redirects if empty
+    #[serde(default)]
+    pub redirect_labels: BTreeMap<String, String>,
+}
+
+/// Reference to a key in a Secret in the same namespace.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretKeyRef {
+    pub name: String,
+    #[serde(default = "default_kubeconfig_key")]
+    pub key: String,
+}
+
+fn default_kubeconfig_key() -> String {
+    "kubeconfig".to_string()
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectClusterStatus {
+    /// `namespace/name` of the Redirects pushed to the member
+    #[serde(default)]
+    pub redirects: Vec<String>,
+    /// when the member was last synced successfully
+    pub last_sync_time: Option<Time>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+/// The CustomResourceDefinitions of the operator, as `crdgen` prints them.
+///
+/// `v1alpha1` stays the storage version of Redirects, the operator's webhook converts `v1beta1`.
+/// It is reached through the operator's Service `service_name` in `namespace`.
+#[allow(unused)]
+pub fn crds(namespace: &str, service_name: &str) -> Vec<CustomResourceDefinition> {
+    let mut redirect = merge_crds(vec![Redirect::crd(), v1beta1::Redirect::crd()], "v1alpha1")
+        .expect("Redirect versions merge");
+    redirect.spec.conversion = Some(CustomResourceConversion {
+        strategy: "Webhook".to_string(),
+        webhook: Some(WebhookConversion {
+            client_config: Some(WebhookClientConfig {
+                service: Some(ServiceReference {
+                    name: service_name.to_string(),
+                    namespace: namespace.to_string(),
+                    path: Some("/convert".to_string()),
+                    port: Some(443),
+                }),
+                ..Default::default()
+            }),
+            conversion_review_versions: vec!["v1".to_string()],
+        }),
+    });
+    for version in &mut redirect.spec.versions {
+        if let Some(schema) = version
+            .schema
+            .as_mut()
+            .and_then(|s| s.open_api_v3_schema.as_mut())
+        {
+            add_validations(schema);
+        }
+    }
+    vec![
+        redirect,
+        RedirectGenerator::crd(),
+        RedirectHostPolicy::crd(),
+        RedirectCluster::crd(),
+    ]
+}
+
+/// Adds CEL rules enforcing basic invariants, for clusters without the admission webhook.
+fn add_validations(schema: &mut JSONSchemaProps) {
+    let rule = |rule: &str, message: &str| {
+        serde_json::json!({
+            "rule": rule,
+            "message": message,
+        })
+    };
+    let to_rule = rule(
+        "self.uri == '' || self.uri.matches('^[hH][tT][tT][pP][sS]?://[^/?#]+') \
+         || (has(self.allowNonHttpScheme) && self.allowNonHttpScheme \
+         && self.uri.matches('^[a-zA-Z][a-zA-Z0-9+.-]*:'))",
+        "uri must be an absolute http or https URL, or allowNonHttpScheme be set",
+    );
+    let validations = [
+        (
+            "/properties/spec",
+            vec![
+                rule("size(self.hosts) > 0", "at least one host is required"),
+                rule(
+                    "!has(oldSelf.immutableHosts) || !oldSelf.immutableHosts || self.hosts == oldSelf.hosts",
+                    "hosts are immutable",
+                ),
+                rule(
+                    "!has(oldSelf.immutableHosts) || !oldSelf.immutableHosts \
+                     || (has(self.immutableHosts) && self.immutableHosts)",
+                    "immutableHosts cannot be unset",
+                ),
+            ],
+        ),
+        ("/properties/spec/properties/to", vec![to_rule.clone()]),
+        (
+            "/properties/spec/properties/overrides/items/properties/to",
+            vec![to_rule.clone()],
+        ),
+        (
+            "/properties/spec/properties/split/properties/targets/items/properties/to",
+            vec![to_rule],
+        ),
+    ];
+    // string lengths bound the estimated cost of the rules
+    let bounded_strings = [
+        ("/properties/spec/properties/hosts/items", 253),
+        ("/properties/spec/properties/to/properties/uri", 2048),
+        (
+            "/properties/spec/properties/overrides/items/properties/to/properties/uri",
+            2048,
+        ),
+        (
+            "/properties/spec/properties/split/properties/targets/items/properties/to/properties/uri",
+            2048,
+        ),
+    ];
+
+    let mut value = serde_json::to_value(&*schema).expect("schemas serialize");
+    for (pointer, rules) in validations {
+        if let Some(props) = value.pointer_mut(pointer).and_then(|p| p.as_object_mut()) {
+            props.insert("x-kubernetes-validations".to_string(), rules.into());
+        }
+    }
+    for (pointer, max_length) in bounded_strings {
+        if let Some(props) = value.pointer_mut(pointer).and_then(|p| p.as_object_mut()) {
+            props.insert("maxLength".to_string(), max_length.into());
+        }
+    }
+    *schema = serde_json::from_value(value).expect("schemas deserialize");
 }
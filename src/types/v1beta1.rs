@@ -0,0 +1,351 @@
+//! The `v1beta1` Redirect API, grouping related fields of `v1alpha1`.
+//!
+//! `v1alpha1` stays the storage version, the controller works on it. Objects
+//! convert losslessly in both directions.
+
+use std::collections::HashSet;
+
+use kube::{CustomResource, Resource};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    RedirectHostOverride, RedirectHttpProxy, RedirectIngress, RedirectInterstitial, RedirectLink,
+    RedirectMapping, RedirectMatch, RedirectMode, RedirectNotFound, RedirectPage, RedirectPathMap,
+    RedirectPause, RedirectRoute, RedirectShortLink, RedirectSplit, RedirectTarpitMatch,
+    RedirectTo, RedirectVirtualService,
+};
+
+#[derive(CustomResource, Debug, Serialize, Deserialize, Default, Clone, JsonSchema)]
+#[kube(
+    group = "kube.ibotty.net",
+    version = "v1beta1",
+    kind = "Redirect",
+    namespaced
+)]
+#[kube(status = "super::RedirectStatus")]
+#[kube(
+    printcolumn = r#"{"name":"Hosts", "type":"string", "description":"hosts served", "jsonPath":".status.servedHosts"}"#,
+    printcolumn = r#"{"name":"Target", "type":"string", "description":"where requests go", "jsonPath":".status.target"}"#,
+    printcolumn = r#"{"name":"Ingress", "type":"string", "description":"the first Ingress", "jsonPath":".status.ingress.name", "priority":1}"#,
+    printcolumn = r#"{"name":"Ready", "type":"string", "description":"whether the Redirect is served as specified", "jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Expires", "type":"string", "description":"when the Redirect expires", "jsonPath":".status.expiresAt"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectSpec {
+    pub hosts: HashSet<String>,
+    #[serde(default)]
+    pub to: RedirectTo,
+
+    #[serde(default)]
+    pub mode: RedirectMode,
+    /// host all other hosts redirect to in `canonicalHost` mode, keeping scheme, path and query
+    pub canonical_host: Option<String>,
+
+    /// objects routing the hosts to the operator
+    #[serde(default)]
+    pub networking: RedirectNetworking,
+
+    /// short codes resolved under all hosts, codes are generated if unset
+    #[serde(default)]
+    pub short_links: Vec<RedirectShortLink>,
+
+    /// per-path targets loaded from a ConfigMap
+    pub path_map: Option<RedirectPathMap>,
+
+    /// page served for paths without a target
+    pub not_found: Option<RedirectNotFound>,
+
+    /// page served in `page` mode
+    pub page: Option<RedirectPage>,
+
+    /// serve a page linking to the target instead of redirecting
+    pub interstitial: Option<RedirectInterstitial>,
+
+    /// stop redirecting without deleting the Redirect
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub pause: RedirectPause,
+
+    /// targets for individual hosts from `hosts`, overriding `to`
+    #[serde(default)]
+    pub overrides: Vec<RedirectHostOverride>,
+
+    /// conditions requests have to meet for the Redirect to apply
+    #[serde(default, rename = "match")]
+    pub match_: RedirectMatch,
+
+    /// delete the Redirect this long after its creation, e.g. `30d` or `12h`, or at a time
+    /// like `2030-01-01T00:00:00Z`
+    pub ttl: Option<String>,
+
+    /// split requests across weighted targets instead of `to`, overrides still apply
+    pub split: Option<RedirectSplit>,
+
+    /// slow down abusive clients
+    #[serde(default)]
+    pub tarpit: RedirectTarpit,
+
+    /// `Link` headers sent with the redirect, e.g. `rel: canonical`
+    #[serde(default)]
+    pub links: Vec<RedirectLink>,
+    /// reject changes to `hosts`, and unsetting this, at the API server
+    #[serde(default)]
+    pub immutable_hosts: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectNetworking {
+    #[serde(default = "default_ingress")]
+    pub ingress: RedirectIngress,
+    /// OpenShift Routes, one per host
+    #[serde(default)]
+    pub route: RedirectRoute,
+    /// an Istio VirtualService for all hosts, for Istio gateways
+    #[serde(default)]
+    pub virtual_service: RedirectVirtualService,
+    /// Contour HTTPProxies, one per host
+    #[serde(default)]
+    pub http_proxy: RedirectHttpProxy,
+    /// Emissary Mappings, one per host and path
+    #[serde(default)]
+    pub mapping: RedirectMapping,
+}
+
+impl Default for RedirectNetworking {
+    fn default() -> Self {
+        Self {
+            ingress: default_ingress(),
+            route: Default::default(),
+            virtual_service: Default::default(),
+            http_proxy: Default::default(),
+            mapping: Default::default(),
+        }
+    }
+}
+
+/// An empty `ingress` as in `v1alpha1`, with the serde defaults of all fields.
+fn default_ingress() -> RedirectIngress {
+    serde_json::from_value(serde_json::json!({})).expect("an empty Ingress deserializes")
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectTarpit {
+    /// delay responses to requests matching `match` by this many milliseconds
+    pub delay_ms: Option<u32>,
+    #[serde(default, rename = "match")]
+    pub match_: RedirectTarpitMatch,
+}
+
+impl From<super::Redirect> for Redirect {
+    fn from(alpha: super::Redirect) -> Self {
+        let spec = alpha.spec;
+        let mut redirect = Redirect::new(
+            "",
+            RedirectSpec {
+                hosts: spec.hosts,
+                to: spec.to,
+                mode: spec.mode,
+                canonical_host: spec.canonical_host,
+                networking: RedirectNetworking {
+                    ingress: spec.ingress,
+                    route: spec.route,
+                    virtual_service: spec.virtual_service,
+                    http_proxy: spec.http_proxy,
+                    mapping: spec.mapping,
+                },
+                short_links: spec.short_links,
+                path_map: spec.path_map,
+                not_found: spec.not_found,
+                page: spec.page,
+                interstitial: spec.interstitial,
+                paused: spec.paused,
+                pause: spec.pause,
+                overrides: spec.overrides,
+                match_: spec.match_,
+                ttl: spec.ttl,
+                split: spec.split,
+                tarpit: RedirectTarpit {
+                    delay_ms: spec.tarpit_ms,
+                    match_: spec.tarpit_match,
+                },
+                links: spec.links,
+                immutable_hosts: spec.immutable_hosts,
+            },
+        );
+        redirect.metadata = alpha.metadata;
+        redirect.status = alpha.status;
+        redirect
+    }
+}
+
+impl From<Redirect> for super::Redirect {
+    fn from(beta: Redirect) -> Self {
+        let spec = beta.spec;
+        let mut redirect = super::Redirect::new(
+            "",
+            super::RedirectSpec {
+                hosts: spec.hosts,
+                to: spec.to,
+                mode: spec.mode,
+                canonical_host: spec.canonical_host,
+                ingress: spec.networking.ingress,
+                route: spec.networking.route,
+                virtual_service: spec.networking.virtual_service,
+                http_proxy: spec.networking.http_proxy,
+                mapping: spec.networking.mapping,
+                short_links: spec.short_links,
+                path_map: spec.path_map,
+                not_found: spec.not_found,
+                page: spec.page,
+                interstitial: spec.interstitial,
+                paused: spec.paused,
+                pause: spec.pause,
+                overrides: spec.overrides,
+                match_: spec.match_,
+                ttl: spec.ttl,
+                split: spec.split,
+                tarpit_ms: spec.tarpit.delay_ms,
+                tarpit_match: spec.tarpit.match_,
+                links: spec.links,
+                immutable_hosts: spec.immutable_hosts,
+            },
+        );
+        redirect.metadata = beta.metadata;
+        redirect.status = beta.status;
+        redirect
+    }
+}
+
+/// Converts a Redirect object of either version to `api_version`, for the conversion webhook.
+#[allow(unused)]
+pub fn convert(object: serde_json::Value, api_version: &str) -> Result<serde_json::Value, String> {
+    let alpha_version = super::Redirect::api_version(&());
+    let beta_version = Redirect::api_version(&());
+    let from = object["apiVersion"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    if from == api_version {
+        return Ok(object);
+    }
+
+    let alpha: super::Redirect = if from == alpha_version {
+        serde_json::from_value(object)
+    } else if from == beta_version {
+        serde_json::from_value::<Redirect>(object).map(Into::into)
+    } else {
+        return Err(format!("cannot convert from {from}"));
+    }
+    .map_err(|e| format!("invalid {from} Redirect: {e}"))?;
+
+    if api_version == alpha_version {
+        serde_json::to_value(alpha)
+    } else if api_version == beta_version {
+        serde_json::to_value(Redirect::from(alpha))
+    } else {
+        return Err(format!("cannot convert to {api_version}"));
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    const ALPHA: &str = "kube.ibotty.net/v1alpha1";
+    const BETA: &str = "kube.ibotty.net/v1beta1";
+
+    /// `object` as the API server would store it, with all defaults filled in.
+    fn normalized<K: serde::de::DeserializeOwned + Serialize>(object: Value) -> Value {
+        serde_json::to_value(serde_json::from_value::<K>(object).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn missing_networking_is_an_empty_ingress() {
+        let beta = json!({
+            "apiVersion": BETA,
+            "kind": "Redirect",
+            "metadata": { "name": "example", "namespace": "default" },
+            "spec": { "hosts": ["example.com"], "to": { "uri": "https://example.org" } },
+        });
+        let alpha = json!({
+            "apiVersion": ALPHA,
+            "kind": "Redirect",
+            "metadata": { "name": "example", "namespace": "default" },
+            "spec": {
+                "hosts": ["example.com"],
+                "to": { "uri": "https://example.org" },
+                "ingress": {},
+            },
+        });
+
+        let converted = convert(beta, ALPHA).unwrap();
+        assert_eq!(converted["spec"]["ingress"]["enabled"], true);
+        assert_eq!(converted["spec"]["ingress"]["tls"]["enabled"], true);
+        assert_eq!(converted, normalized::<super::super::Redirect>(alpha));
+    }
+
+    #[test]
+    fn alpha_round_trips() {
+        let alpha = normalized::<super::super::Redirect>(json!({
+            "apiVersion": ALPHA,
+            "kind": "Redirect",
+            "metadata": { "name": "example", "namespace": "default" },
+            "spec": {
+                "hosts": ["example.com"],
+                "to": { "uri": "https://example.org", "includeRequestUri": false },
+                "ingress": { "tls": { "enabled": false }, "shared": true },
+                "route": { "enabled": true },
+                "ttl": "30d",
+                "tarpitMs": 500,
+                "tarpitMatch": { "userAgents": ["scraper"] },
+                "immutableHosts": true,
+            },
+            "status": {
+                "ingress": { "name": "example", "namespace": "default" },
+                "servedHosts": "example.com",
+            },
+        }));
+
+        let beta = convert(alpha.clone(), BETA).unwrap();
+        assert_eq!(beta["apiVersion"], BETA);
+        assert_eq!(beta["spec"]["networking"]["ingress"]["shared"], true);
+        assert_eq!(beta["spec"]["ttl"], "30d");
+        assert_eq!(beta["spec"]["tarpit"]["delayMs"], 500);
+        assert_eq!(convert(beta, ALPHA).unwrap(), alpha);
+    }
+
+    #[test]
+    fn beta_round_trips() {
+        let beta = normalized::<Redirect>(json!({
+            "apiVersion": BETA,
+            "kind": "Redirect",
+            "metadata": { "name": "example", "namespace": "default" },
+            "spec": {
+                "hosts": ["example.com"],
+                "to": { "uri": "https://example.org" },
+                "networking": { "mapping": { "native": true } },
+                "ttl": "2030-01-01T00:00:00Z",
+            },
+        }));
+
+        let alpha = convert(beta.clone(), ALPHA).unwrap();
+        assert_eq!(alpha["spec"]["ttl"], "2030-01-01T00:00:00Z");
+        assert_eq!(convert(alpha, BETA).unwrap(), beta);
+    }
+
+    #[test]
+    fn unknown_versions_are_refused() {
+        let object = json!({ "apiVersion": "kube.ibotty.net/v2", "kind": "Redirect" });
+        assert!(convert(object.clone(), ALPHA).is_err());
+        let object = json!({ "apiVersion": ALPHA, "kind": "Redirect", "spec": { "hosts": [], "ingress": {} } });
+        assert!(convert(object, "kube.ibotty.net/v2").is_err());
+    }
+}
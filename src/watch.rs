@@ -0,0 +1,173 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::iter;
+
+use anyhow::bail;
+use futures::{
+    StreamExt,
+    stream::{self, BoxStream},
+};
+use k8s_openapi::NamespaceResourceScope;
+use kube::{
+    Api, Client, Resource, ResourceExt,
+    runtime::{
+        WatchStreamExt,
+        watcher::{self, Event, watcher},
+    },
+};
+use serde::de::DeserializeOwned;
+
+pub type WatchEvent<K> = Result<Event<K>, watcher::Error>;
+
+/// Parses `WATCH_NAMESPACE`, a comma separated list of namespaces; none means all.
+pub fn namespaces_from_env() -> BTreeSet<String> {
+    std::env::var("WATCH_NAMESPACE")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|ns| !ns.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether to stay within `WATCH_NAMESPACE`, from `NAMESPACED_RBAC`.
+///
+/// The operator then never lists or watches cluster-wide and only needs Roles. Cluster-scoped
+/// settings are ignored: RedirectHostPolicies, namespace defaults and IngressClasses.
+pub fn namespaced_only() -> anyhow::Result<bool> {
+    if !std::env::var("NAMESPACED_RBAC").is_ok_and(|v| v == "true") {
+        return Ok(false);
+    }
+    if namespaces_from_env().is_empty() {
+        bail!("NAMESPACED_RBAC needs WATCH_NAMESPACE");
+    }
+    Ok(true)
+}
+
+/// Watches `namespaces`, or all namespaces if there are none, as a single watcher would.
+///
+/// Several namespaces need one watch each, so they can be covered by namespaced Roles.
+pub fn watch<K>(
+    client: &Client,
+    namespaces: &BTreeSet<String>,
+    config: watcher::Config,
+) -> BoxStream<'static, WatchEvent<K>>
+where
+    K: Resource<Scope = NamespaceResourceScope, DynamicType = ()>
+        + Clone
+        + Debug
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+{
+    let mut apis: Vec<(String, Api<K>)> = namespaces
+        .iter()
+        .map(|ns| (ns.clone(), Api::namespaced(client.clone(), ns)))
+        .collect();
+    match apis.len() {
+        0 => watcher(Api::all(client.clone()), config)
+            .default_backoff()
+            .boxed(),
+        1 => watcher(apis.remove(0).1, config).default_backoff().boxed(),
+        _ => {
+            let watchers = apis.into_iter().map(|(ns, api)| {
+                watcher(api, config.clone())
+                    .default_backoff()
+                    .map(move |event| (ns.clone(), event))
+                    .boxed()
+            });
+            let mut merged = Merged::new(namespaces.clone());
+            stream::select_all(watchers)
+                .flat_map(move |(ns, event)| stream::iter(merged.translate(ns, event)))
+                .boxed()
+        }
+    }
+}
+
+/// Combines the events of per-namespace watchers into the events of a single one.
+///
+/// A reflector forgets every object missing from a relist, so the initial listing is only
+/// passed on once all namespaces are listed, and later relists of single namespaces become
+/// applies and deletes.
+struct Merged<K> {
+    /// objects per namespace and name
+    objects: HashMap<String, HashMap<String, K>>,
+    /// objects of namespaces being relisted
+    relisting: HashMap<String, HashMap<String, K>>,
+    /// namespaces not listed yet
+    unlisted: BTreeSet<String>,
+}
+
+impl<K: Resource + Clone> Merged<K> {
+    fn new(namespaces: BTreeSet<String>) -> Self {
+        Self {
+            objects: HashMap::new(),
+            relisting: HashMap::new(),
+            unlisted: namespaces,
+        }
+    }
+
+    fn translate(&mut self, ns: String, event: WatchEvent<K>) -> Vec<WatchEvent<K>> {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => return vec![Err(e)],
+        };
+        let listed = self.unlisted.is_empty();
+        let pass = |event: Event<K>| if listed { vec![Ok(event)] } else { vec![] };
+        match event {
+            Event::Init => {
+                self.relisting.insert(ns, HashMap::new());
+                vec![]
+            }
+            Event::InitApply(object) => {
+                self.relisting
+                    .entry(ns)
+                    .or_default()
+                    .insert(object.name_any(), object.clone());
+                pass(Event::Apply(object))
+            }
+            Event::InitDone => {
+                let relisted = self.relisting.remove(&ns).unwrap_or_default();
+                let previous = self
+                    .objects
+                    .insert(ns.clone(), relisted)
+                    .unwrap_or_default();
+                if listed {
+                    let current = &self.objects[&ns];
+                    return previous
+                        .into_iter()
+                        .filter(|(name, _)| !current.contains_key(name))
+                        .map(|(_, object)| Ok(Event::Delete(object)))
+                        .collect();
+                }
+                self.unlisted.remove(&ns);
+                if !self.unlisted.is_empty() {
+                    return vec![];
+                }
+                iter::once(Ok(Event::Init))
+                    .chain(
+                        self.objects
+                            .values()
+                            .flat_map(HashMap::values)
+                            .map(|object| Ok(Event::InitApply(object.clone()))),
+                    )
+                    .chain(iter::once(Ok(Event::InitDone)))
+                    .collect()
+            }
+            Event::Apply(object) => {
+                self.objects
+                    .entry(ns)
+                    .or_default()
+                    .insert(object.name_any(), object.clone());
+                pass(Event::Apply(object))
+            }
+            Event::Delete(object) => {
+                if let Some(objects) = self.objects.get_mut(&ns) {
+                    objects.remove(&object.name_any());
+                }
+                pass(Event::Delete(object))
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{Json, Router, extract::State, routing::post};
+use axum_server::{Handle, tls_rustls::RustlsConfig};
+use kube::{
+    Resource, ResourceExt,
+    api::DynamicObject,
+    core::{
+        Status,
+        admission::{AdmissionRequest, AdmissionResponse, AdmissionReview},
+        conversion::{ConversionRequest, ConversionResponse, ConversionReview},
+    },
+};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::{
+    host, hostpolicy, lint,
+    pathmap::PathMaps,
+    target,
+    types::{Redirect, v1beta1},
+    webhookcert::SelfSigned,
+};
+
+/// Port of the webhook's HTTPS server.
+pub const WEBHOOK_PORT: u16 = 8443;
+
+/// Time given to in-flight admission reviews on shutdown.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Validates Redirects at admission, against the same checks the controller reports as conditions.
+#[derive(Clone)]
+pub struct Webhook {
+    pub hosts: host::HostIndex,
+    pub path_maps: PathMaps,
+    pub target_policy: Arc<target::TargetPolicy>,
+    pub host_policies: hostpolicy::HostPolicies,
+}
+
+impl Webhook {
+    /// Reasons to reject `redirect`, empty if it is acceptable.
+    ///
+    /// Hosts count as taken if another Redirect without request conditions serves them.
+    pub fn violations(&self, redirect: &Redirect) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        let (_, invalid_hosts) = host::normalize_all(&redirect.spec.hosts);
+        violations.extend(
+            invalid_hosts
+                .into_iter()
+                .map(|h| format!("spec.hosts: {h} is not a valid host name")),
+        );
+        violations.extend(target::check_all(&redirect.spec));
+
+        let namespace = redirect.namespace().unwrap_or_default();
+        for (host, policies) in self
+            .host_policies
+            .denied(&namespace, &host::served_hosts(&redirect.spec).0)
+        {
+            violations.push(format!(
+                "spec.hosts: namespace {namespace} may not claim {host} per RedirectHostPolicy {}",
+                policies.join(", ")
+            ));
+        }
+
+        for (host, other) in self.hosts.conflicts(redirect) {
+            // updates of the Redirect itself before its uid is known to the store
+            if other.namespace() == redirect.namespace() && other.name_any() == redirect.name_any()
+            {
+                continue;
+            }
+            violations.push(format!(
+                "spec.hosts: {host} is already served by Redirect {}/{}",
+                other.namespace().unwrap_or_default(),
+                other.name_any()
+            ));
+        }
+
+        let path_map = self.path_maps.table_for(redirect);
+        violations.extend(
+            self.target_policy
+                .denied(
+                    &redirect.spec,
+                    path_map.iter().flat_map(|table| table.values()),
+                )
+                .into_iter()
+                .map(|target| format!("{target} is not an allowed target")),
+        );
+        violations
+    }
+}
+
+/// `object`, a Redirect of either version, in the storage version `v1alpha1`.
+fn stored(object: &DynamicObject) -> Result<Redirect, String> {
+    let object = serde_json::to_value(object).map_err(|e| e.to_string())?;
+    let object = v1beta1::convert(object, &Redirect::api_version(&()))?;
+    serde_json::from_value(object).map_err(|e| format!("invalid Redirect: {e}"))
+}
+
+async fn validate(
+    State(webhook): State<Webhook>,
+    Json(review): Json<AdmissionReview<DynamicObject>>,
+) -> Json<AdmissionReview<DynamicObject>> {
+    let request: AdmissionRequest<DynamicObject> = match review.try_into() {
+        Ok(request) => request,
+        Err(e) => {
+            warn!("invalid admission review: {:?}", e);
+            return Json(AdmissionResponse::invalid(e.to_string()).into_review());
+        }
+    };
+
+    let mut response = AdmissionResponse::from(&request);
+    // deletions carry no object
+    if let Some(object) = &request.object {
+        let mut redirect = match stored(object) {
+            Ok(redirect) => redirect,
+            Err(e) => {
+                warn!("cannot validate Redirect: {}", e);
+                return Json(response.deny(e).into_review());
+            }
+        };
+        if redirect.metadata.namespace.is_none() {
+            redirect.metadata.namespace = request.namespace.clone();
+        }
+        let violations = webhook.violations(&redirect);
+        if violations.is_empty() {
+            let warnings: Vec<String> = lint::lint(&redirect.spec)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            if !warnings.is_empty() {
+                response.warnings = Some(warnings);
+            }
+        } else {
+            info!(
+                "rejecting Redirect {}/{}: {:?}",
+                redirect.namespace().unwrap_or_default(),
+                redirect.name_any(),
+                violations
+            );
+            response = response.deny(violations.join("; "));
+        }
+    }
+    Json(response.into_review())
+}
+
+/// Converts Redirects between `v1alpha1` and `v1beta1`.
+async fn convert(Json(review): Json<ConversionReview>) -> Json<ConversionReview> {
+    let request = match ConversionRequest::from_review(review) {
+        Ok(request) => request,
+        Err(e) => {
+            warn!("invalid conversion review: {:?}", e);
+            let status = Status::failure(&e.to_string(), "InvalidRequest");
+            return Json(ConversionResponse::invalid(status).into_review());
+        }
+    };
+
+    let converted: Result<Vec<_>, String> = request
+        .objects
+        .iter()
+        .cloned()
+        .map(|object| v1beta1::convert(object, &request.desired_api_version))
+        .collect();
+    let response = ConversionResponse::for_request(request);
+    Json(
+        match converted {
+            Ok(objects) => response.success(objects),
+            Err(e) => {
+                warn!("cannot convert Redirects: {}", e);
+                response.failure(Status::failure(&e, "ConversionFailed"))
+            }
+        }
+        .into_review(),
+    )
+}
+
+/// Serves the webhook until `shutdown` fires.
+///
+/// Off unless the certificates are `self_signed` or `WEBHOOK_CERT_DIR` names a directory with
+/// `tls.crt` and `tls.key`.
+pub async fn serve(
+    webhook: Webhook,
+    self_signed: Option<SelfSigned>,
+    shutdown: oneshot::Receiver<()>,
+) -> anyhow::Result<()> {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let config = if let Some(self_signed) = self_signed {
+        let config = self_signed.tls_config().await?;
+        tokio::spawn(self_signed.rotate(config.clone()));
+        config
+    } else if let Some(cert_dir) = std::env::var_os("WEBHOOK_CERT_DIR").map(PathBuf::from) {
+        RustlsConfig::from_pem_file(cert_dir.join("tls.crt"), cert_dir.join("tls.key")).await?
+    } else {
+        let _ = shutdown.await;
+        return Ok(());
+    };
+
+    let handle = Handle::new();
+    let shutdown_handle = handle.clone();
+    tokio::spawn(async move {
+        let _ = shutdown.await;
+        shutdown_handle.graceful_shutdown(Some(DRAIN_TIMEOUT));
+    });
+
+    let app = Router::new()
+        .route("/validate", post(validate))
+        .route("/convert", post(convert))
+        .with_state(webhook);
+    info!("serving the admission webhook on port {}", WEBHOOK_PORT);
+    axum_server::bind_rustls(SocketAddr::from(([0, 0, 0, 0], WEBHOOK_PORT)), config)
+        .handle(handle)
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn beta_redirects_are_validated_as_stored() {
+        let object: DynamicObject = serde_json::from_value(json!({
+            "apiVersion": "kube.ibotty.net/v1beta1",
+            "kind": "Redirect",
+            "metadata": { "name": "example", "namespace": "default" },
+            "spec": {
+                "hosts": ["example.com"],
+                "to": { "uri": "https://example.org" },
+                "tarpit": { "delayMs": 500 },
+            },
+        }))
+        .unwrap();
+
+        let redirect = stored(&object).unwrap();
+        assert!(redirect.spec.hosts.contains("example.com"));
+        assert_eq!(redirect.spec.tarpit_ms, Some(500));
+        assert!(redirect.spec.ingress.enabled);
+    }
+
+    #[test]
+    fn unknown_versions_are_not_validated() {
+        let object: DynamicObject = serde_json::from_value(json!({
+            "apiVersion": "kube.ibotty.net/v2",
+            "kind": "Redirect",
+            "metadata": { "name": "example", "namespace": "default" },
+        }))
+        .unwrap();
+        assert!(stored(&object).is_err());
+    }
+}
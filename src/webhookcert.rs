@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::time::Duration;
+
+use anyhow::{Context as _, anyhow};
+use axum_server::tls_rustls::RustlsConfig;
+use k8s_openapi::{
+    ByteString,
+    api::{admissionregistration::v1::ValidatingWebhookConfiguration, core::v1::Secret},
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+    jiff::{Span, Timestamp, civil::Date, tz::TimeZone},
+};
+use kube::{
+    Api, Client, CustomResourceExt, ResourceExt,
+    api::{ObjectMeta, Patch, PatchParams, PostParams},
+};
+use rcgen::{
+    BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    KeyUsagePurpose,
+};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::{controller, dryrun, types::Redirect};
+
+/// How long the CA is valid, it is renewed a year before it expires.
+const CA_VALIDITY_DAYS: i64 = 3650;
+const CA_RENEW_BEFORE_DAYS: i64 = 365;
+
+/// How long serving certificates are valid, they are renewed a month before they expire.
+const CERT_VALIDITY_DAYS: i64 = 365;
+const CERT_RENEW_BEFORE_DAYS: i64 = 30;
+
+/// How often the certificates are checked for renewal.
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// The webhook's serving certificate and the CAs clients should trust.
+#[derive(PartialEq)]
+struct Certs {
+    ca_bundle: Vec<u8>,
+    cert: Vec<u8>,
+    key: Vec<u8>,
+}
+
+/// Generates and rotates a self-signed CA and the webhook's serving certificate.
+///
+/// They are kept in a Secret in the operator's namespace, so that all replicas serve the same
+/// certificate. The CA bundle is injected into the webhook configuration and the Redirect
+/// CRD's conversion webhook, replacing cert-manager's CA injector.
+pub struct SelfSigned {
+    client: Client,
+    namespace: String,
+    service_name: String,
+    secret_name: String,
+    webhook_configuration: String,
+    dry_run: bool,
+}
+
+impl SelfSigned {
+    /// Reads `WEBHOOK_SELF_SIGNED`, `WEBHOOK_CERT_SECRET` (default
+    /// `redirect-operator-webhook-certs`) and `WEBHOOK_CONFIGURATION_NAME` (default
+    /// `redirect-operator`).
+    pub fn from_env(client: Client) -> Option<Self> {
+        if !env::var("WEBHOOK_SELF_SIGNED").is_ok_and(|v| v == "true") {
+            return None;
+        }
+        Some(Self {
+            client,
+            namespace: controller::self_namespace(),
+            service_name: controller::self_service_name(),
+            secret_name: env::var("WEBHOOK_CERT_SECRET")
+                .unwrap_or("redirect-operator-webhook-certs".to_string()),
+            webhook_configuration: env::var("WEBHOOK_CONFIGURATION_NAME")
+                .unwrap_or("redirect-operator".to_string()),
+            dry_run: dryrun::enabled(),
+        })
+    }
+
+    /// Ensures usable certificates, injects the CA bundle and returns the TLS config to serve.
+    pub async fn tls_config(&self) -> anyhow::Result<RustlsConfig> {
+        let certs = self.ensure().await?;
+        self.inject(&certs.ca_bundle).await?;
+        Ok(RustlsConfig::from_pem(certs.cert, certs.key).await?)
+    }
+
+    /// Renews the certificates when they are about to expire and reloads `config`.
+    pub async fn rotate(self, config: RustlsConfig) {
+        let mut current = None;
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let certs = match self.ensure().await {
+                Ok(certs) => certs,
+                Err(e) => {
+                    warn!("cannot renew the webhook certificate: {:?}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = self.inject(&certs.ca_bundle).await {
+                warn!("cannot inject the webhook CA bundle: {:?}", e);
+            }
+            if current.as_ref() == Some(&certs) {
+                continue;
+            }
+            match config
+                .reload_from_pem(certs.cert.clone(), certs.key.clone())
+                .await
+            {
+                Ok(()) => current = Some(certs),
+                Err(e) => warn!("cannot reload the webhook certificate: {:?}", e),
+            }
+        }
+    }
+
+    /// The certificates in the Secret, renewed and stored first if needed.
+    async fn ensure(&self) -> anyhow::Result<Certs> {
+        let api: Api<Secret> = Api::namespaced(self.client.clone(), &self.namespace);
+        // replicas starting together race to create or renew, the losers use the winner's
+        for _ in 0..3 {
+            let existing = api.get_opt(&self.secret_name).await?;
+            let data = existing
+                .as_ref()
+                .and_then(|s| s.data.clone())
+                .unwrap_or_default();
+            let get = |key: &str| data.get(key).map(|v| v.0.clone());
+
+            let ca = get("ca.crt")
+                .zip(get("ca.key"))
+                .filter(|(crt, _)| valid_for(crt, CA_RENEW_BEFORE_DAYS));
+            if ca.is_some()
+                && let Some((cert, key)) = get("tls.crt").zip(get("tls.key"))
+                && valid_for(&cert, CERT_RENEW_BEFORE_DAYS)
+            {
+                return Ok(Certs {
+                    ca_bundle: ca_bundle(&data),
+                    cert,
+                    key,
+                });
+            }
+
+            // trust the expiring CA until certificates it signed are gone
+            let (previous_ca, (ca_crt, ca_key)) = match ca {
+                Some(ca) => (get("ca-previous.crt"), ca),
+                None => {
+                    info!("generating the webhook CA");
+                    (get("ca.crt"), generate_ca()?)
+                }
+            };
+            info!("issuing the webhook serving certificate");
+            let (cert, key) = self.issue(&ca_crt, &ca_key)?;
+            let mut renewed = BTreeMap::from([
+                ("ca.crt".to_string(), ByteString(ca_crt)),
+                ("ca.key".to_string(), ByteString(ca_key)),
+                ("tls.crt".to_string(), ByteString(cert.clone())),
+                ("tls.key".to_string(), ByteString(key.clone())),
+            ]);
+            if let Some(previous) = previous_ca.filter(|crt| valid_for(crt, 0)) {
+                renewed.insert("ca-previous.crt".to_string(), ByteString(previous));
+            }
+            let certs = Certs {
+                ca_bundle: ca_bundle(&renewed),
+                cert,
+                key,
+            };
+            if self.dry_run {
+                info!(
+                    "dry run: not storing the webhook certificate in Secret {}/{}",
+                    self.namespace, self.secret_name
+                );
+                return Ok(certs);
+            }
+
+            let stored = match existing {
+                None => api
+                    .create(
+                        &PostParams::default(),
+                        &Secret {
+                            metadata: ObjectMeta {
+                                name: Some(self.secret_name.clone()),
+                                namespace: Some(self.namespace.clone()),
+                                ..ObjectMeta::default()
+                            },
+                            type_: Some("kubernetes.io/tls".to_string()),
+                            data: Some(renewed),
+                            ..Secret::default()
+                        },
+                    )
+                    .await
+                    .map(|_| ()),
+                // the resourceVersion makes a concurrent renewal fail instead of being overwritten
+                Some(secret) => api
+                    .patch(
+                        &self.secret_name,
+                        &PatchParams::default(),
+                        &Patch::Merge(json!({
+                            "metadata": { "resourceVersion": secret.resource_version() },
+                            "data": renewed,
+                        })),
+                    )
+                    .await
+                    .map(|_| ()),
+            };
+            match stored {
+                Ok(()) => return Ok(certs),
+                Err(kube::Error::Api(e)) if e.code == 409 => continue,
+                Err(e) => return Err(e).context("cannot store the webhook certificate"),
+            }
+        }
+        Err(anyhow!(
+            "Secret {}/{} keeps changing",
+            self.namespace,
+            self.secret_name
+        ))
+    }
+
+    /// A serving certificate for the operator's Service, signed by the CA.
+    fn issue(&self, ca_crt: &[u8], ca_key: &[u8]) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+        let ca_key = KeyPair::from_pem(std::str::from_utf8(ca_key)?)?;
+        let ca = CertificateParams::from_ca_cert_pem(std::str::from_utf8(ca_crt)?)?
+            .self_signed(&ca_key)?;
+
+        let (service, ns) = (&self.service_name, &self.namespace);
+        let mut params = CertificateParams::new(vec![
+            service.clone(),
+            format!("{service}.{ns}"),
+            format!("{service}.{ns}.svc"),
+            format!("{service}.{ns}.svc.cluster.local"),
+        ])?;
+        params
+            .distinguished_name
+            .push(DnType::CommonName, format!("{service}.{ns}.svc"));
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        set_validity(&mut params, CERT_VALIDITY_DAYS)?;
+        let key = KeyPair::generate()?;
+        let cert = params.signed_by(&key, &ca, &ca_key)?;
+        Ok((cert.pem().into_bytes(), key.serialize_pem().into_bytes()))
+    }
+
+    /// Sets the CA bundle of the webhook configuration and the conversion webhook.
+    async fn inject(&self, ca_bundle: &[u8]) -> anyhow::Result<()> {
+        if self.dry_run {
+            info!("dry run: not injecting the webhook CA bundle");
+            return Ok(());
+        }
+        let api: Api<ValidatingWebhookConfiguration> = Api::all(self.client.clone());
+        match api.get_opt(&self.webhook_configuration).await? {
+            Some(mut config) => {
+                let webhooks = config.webhooks.iter_mut().flatten();
+                let mut changed = false;
+                for webhook in webhooks {
+                    if webhook.client_config.ca_bundle.as_ref().map(|b| &b.0[..]) != Some(ca_bundle)
+                    {
+                        webhook.client_config.ca_bundle = Some(ByteString(ca_bundle.to_vec()));
+                        changed = true;
+                    }
+                }
+                if changed {
+                    info!(
+                        "injecting the CA bundle into ValidatingWebhookConfiguration {}",
+                        self.webhook_configuration
+                    );
+                    api.replace(&self.webhook_configuration, &PostParams::default(), &config)
+                        .await?;
+                }
+            }
+            None => warn!(
+                "ValidatingWebhookConfiguration {} does not exist",
+                self.webhook_configuration
+            ),
+        }
+
+        let api: Api<CustomResourceDefinition> = Api::all(self.client.clone());
+        let Some(crd) = api.get_opt(Redirect::crd_name()).await? else {
+            return Ok(());
+        };
+        let webhook = crd
+            .spec
+            .conversion
+            .as_ref()
+            .filter(|c| c.strategy == "Webhook")
+            .and_then(|c| c.webhook.as_ref());
+        if let Some(webhook) = webhook
+            && webhook
+                .client_config
+                .as_ref()
+                .and_then(|c| c.ca_bundle.as_ref())
+                .map(|b| &b.0[..])
+                != Some(ca_bundle)
+        {
+            info!("injecting the CA bundle into CRD {}", crd.name_any());
+            api.patch(
+                &crd.name_any(),
+                &PatchParams::default(),
+                &Patch::Merge(json!({
+                    "spec": { "conversion": { "webhook": { "clientConfig": {
+                        "caBundle": ByteString(ca_bundle.to_vec()),
+                    } } } }
+                })),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// The CAs to trust: the current one and the one it replaced, until that expires.
+fn ca_bundle(data: &BTreeMap<String, ByteString>) -> Vec<u8> {
+    ["ca.crt", "ca-previous.crt"]
+        .iter()
+        .filter_map(|key| data.get(*key))
+        .filter(|crt| valid_for(&crt.0, 0))
+        .flat_map(|crt| crt.0.iter().copied())
+        .collect()
+}
+
+/// Whether the PEM certificate `crt` is valid for at least `days` more.
+fn valid_for(crt: &[u8], days: i64) -> bool {
+    let Ok((_, pem)) = x509_parser::pem::parse_x509_pem(crt) else {
+        return false;
+    };
+    pem.parse_x509().is_ok_and(|certificate| {
+        certificate.validity().not_after.timestamp() > Timestamp::now().as_second() + days * 86400
+    })
+}
+
+/// Makes `params` valid from today for `days`.
+fn set_validity(params: &mut CertificateParams, days: i64) -> anyhow::Result<()> {
+    let today = Timestamp::now().to_zoned(TimeZone::UTC).date();
+    let until = today.checked_add(Span::new().days(days))?;
+    let ymd = |d: Date| rcgen::date_time_ymd(d.year().into(), d.month() as u8, d.day() as u8);
+    params.not_before = ymd(today);
+    params.not_after = ymd(until);
+    Ok(())
+}
+
+/// A new CA certificate and key, as PEM.
+fn generate_ca() -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let mut params = CertificateParams::new(Vec::<String>::new())?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "redirect-operator webhook CA");
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+    set_validity(&mut params, CA_VALIDITY_DAYS)?;
+    let key = KeyPair::generate()?;
+    let cert = params.self_signed(&key)?;
+    Ok((cert.pem().into_bytes(), key.serialize_pem().into_bytes()))
+}
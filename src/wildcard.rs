@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+
+use anyhow::bail;
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{
+    Api, Client, ResourceExt,
+    api::{ListParams, ObjectMeta, Patch, PatchParams},
+    runtime::{
+        WatchStreamExt,
+        watcher::{self, watcher},
+    },
+};
+use tracing::{info, warn};
+
+use crate::{controller::REDIRECT_KUBE_SLUG, types::Error};
+
+/// Label on copies of the wildcard TLS Secret.
+pub const COPY_LABEL: &str = "redirect.kube.ibotty.net/wildcard-tls-copy";
+
+/// A TLS Secret, e.g. with a wildcard certificate, used by all generated Ingresses.
+///
+/// It is copied next to the Ingresses, under its own name, and the copies are updated when it
+/// is rotated.
+#[derive(Clone, Debug)]
+pub struct WildcardSecret {
+    pub namespace: String,
+    pub name: String,
+}
+
+impl WildcardSecret {
+    /// Reads `WILDCARD_TLS_SECRET` as `namespace/name`.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(secret) = env::var("WILDCARD_TLS_SECRET") else {
+            return Ok(None);
+        };
+        match secret.split_once('/') {
+            Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() => {
+                Ok(Some(Self {
+                    namespace: namespace.to_string(),
+                    name: name.to_string(),
+                }))
+            }
+            _ => bail!("invalid WILDCARD_TLS_SECRET {secret}, expected namespace/name"),
+        }
+    }
+
+    fn copy(&self, source: &Secret, namespace: &str) -> Secret {
+        Secret {
+            metadata: ObjectMeta {
+                name: Some(self.name.clone()),
+                namespace: Some(namespace.to_string()),
+                labels: Some(BTreeMap::from([(
+                    COPY_LABEL.to_string(),
+                    "true".to_string(),
+                )])),
+                ..ObjectMeta::default()
+            },
+            type_: source.type_.clone(),
+            data: source.data.clone(),
+            ..Secret::default()
+        }
+    }
+
+    async fn copy_from(
+        &self,
+        client: &Client,
+        source: &Secret,
+        namespace: &str,
+        params: &PatchParams,
+    ) -> Result<(), Error> {
+        if namespace == self.namespace {
+            return Ok(());
+        }
+        let api: Api<Secret> = Api::namespaced(client.clone(), namespace);
+        api.patch(
+            &self.name,
+            params,
+            &Patch::Apply(self.copy(source, namespace)),
+        )
+        .await
+        .map_err(Error::SecretCopyFailed)?;
+        Ok(())
+    }
+
+    /// Copies the Secret into `namespace`, for Ingresses there to use it.
+    pub async fn copy_to(
+        &self,
+        client: &Client,
+        namespace: &str,
+        params: &PatchParams,
+    ) -> Result<(), Error> {
+        let api: Api<Secret> = Api::namespaced(client.clone(), &self.namespace);
+        let source = api
+            .get(&self.name)
+            .await
+            .map_err(Error::SecretFetchFailed)?;
+        self.copy_from(client, &source, namespace, params).await
+    }
+
+    /// Updates the copy in `self_namespace` and all other copies from `source`.
+    ///
+    /// Copies are looked for in `copy_namespaces`, or in all namespaces if there are none.
+    async fn sync_copies(
+        &self,
+        client: &Client,
+        source: &Secret,
+        self_namespace: &str,
+        copy_namespaces: &BTreeSet<String>,
+        params: &PatchParams,
+    ) {
+        let apis: Vec<Api<Secret>> = if copy_namespaces.is_empty() {
+            vec![Api::all(client.clone())]
+        } else {
+            copy_namespaces
+                .iter()
+                .map(|ns| Api::namespaced(client.clone(), ns))
+                .collect()
+        };
+        let mut namespaces = BTreeSet::from([self_namespace.to_string()]);
+        for copies in apis {
+            match copies
+                .list_metadata(&ListParams::default().labels(COPY_LABEL))
+                .await
+            {
+                Ok(copies) => namespaces.extend(copies.items.iter().filter_map(|c| c.namespace())),
+                Err(e) => warn!("cannot list wildcard TLS Secret copies: {:?}", e),
+            }
+        }
+        for namespace in namespaces {
+            match self.copy_from(client, source, &namespace, params).await {
+                Ok(()) => info!(
+                    "synced wildcard TLS Secret {} into {}",
+                    self.name, namespace
+                ),
+                Err(e) => warn!("{}", e),
+            }
+        }
+    }
+
+    /// Keeps the copies in sync with the Secret, e.g. when it is rotated.
+    pub fn spawn(
+        self,
+        client: Client,
+        self_namespace: String,
+        copy_namespaces: BTreeSet<String>,
+        params: PatchParams,
+    ) {
+        let api: Api<Secret> = Api::namespaced(client.clone(), &self.namespace);
+        let config = watcher::Config::default().fields(&format!("metadata.name={}", self.name));
+        tokio::spawn(async move {
+            let mut sources = watcher(api, config)
+                .default_backoff()
+                .applied_objects()
+                .boxed();
+            while let Some(source) = sources.next().await {
+                match source {
+                    Ok(source) => {
+                        self.sync_copies(
+                            &client,
+                            &source,
+                            &self_namespace,
+                            &copy_namespaces,
+                            &params,
+                        )
+                        .await
+                    }
+                    Err(e) => warn!("watching the wildcard TLS Secret failed: {:?}", e),
+                }
+            }
+        });
+    }
+}